@@ -0,0 +1,50 @@
+//! Times how long the lisp takes to call and bake embedded operations
+//!
+//! Run with `cargo run --release --example bake_bench`. Each script runs
+//! 1000 times inside a lisp loop, so the "empty loop" line is the cost of
+//! the loop itself and should be subtracted from the others
+//!
+
+use codebake::lisp::{default_reader, parse_eval, Environment};
+use std::time::Instant;
+
+const ITERATIONS: u32 = 1000;
+const RUNS: u32 = 20;
+
+fn main() {
+    let reader = default_reader();
+    let mut env = Environment::default();
+    let mut run = |src: &str| {
+        parse_eval(&reader, &mut env, &src.to_string())
+            .unwrap_or_else(|e| panic!("failed to run {}: {}", src, e))
+    };
+
+    run("(def d d\"hello world\")");
+    run("(def bound (rot13 13))");
+    run("(def r (recipe (rot13 13) reverse))");
+
+    let scripts = [
+        ("empty loop", "nil"),
+        ("bind a parameterized op", "(rot13 13)"),
+        ("apply a bound op", "(bound d)"),
+        ("call a parameterized op", "((rot13 13) d)"),
+        ("bake a prebuilt recipe", "(bake r d)"),
+        (
+            "build and bake a recipe",
+            "(bake (recipe (rot13 13) reverse) d)",
+        ),
+    ];
+
+    for (label, body) in scripts {
+        run(&format!(
+            "(defn bench (n) (if (= n 0) nil (do {} (bench (- n 1)))))",
+            body
+        ));
+
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            run(&format!("(bench {})", ITERATIONS));
+        }
+        println!("{:>24}: {:?}", label, start.elapsed() / (RUNS * ITERATIONS));
+    }
+}
@@ -0,0 +1,39 @@
+//! Times byte-wise operations on a large dish, to compare builds with and
+//! without the `parallel` feature
+//!
+//! Run with `cargo run --release --example parallel_bench`, then again with
+//! `--features parallel`. `reverse` doesn't go through `map_bytes_parallel`,
+//! so it should take about as long either way
+//!
+
+use codebake::{Dish, OperationArguments};
+use std::time::Instant;
+
+const SIZE: usize = 100 * 1024 * 1024;
+const RUNS: u32 = 5;
+
+fn main() {
+    println!(
+        "parallel feature: {}",
+        if cfg!(feature = "parallel") {
+            "on"
+        } else {
+            "off"
+        }
+    );
+
+    let input: Vec<u8> = (0..SIZE).map(|i| (i * 31) as u8).collect();
+    let mut rot13 = OperationArguments::new();
+    rot13.insert("n", 13);
+    let ops = [("rot13", rot13), ("reverse", OperationArguments::new())];
+
+    for (name, args) in &ops {
+        let mut dish = Dish::from_bytes(input.clone());
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            dish.apply_named(name, args);
+        }
+        assert!(dish.is_success());
+        println!("{:>8} over 100MB: {:?}", name, start.elapsed() / RUNS);
+    }
+}
@@ -13,6 +13,7 @@ extern crate urlencoding;
 pub mod lisp;
 pub mod ops;
 
+use serde::Serialize;
 use std::collections::HashMap;
 use std::convert::Into;
 use std::fmt;
@@ -26,7 +27,28 @@ pub static EMPTY_ARGS: OperationArguments = OperationArguments { inner: None };
 /// on some DishData. This is the `E` type in `codebake::Result`.
 ///
 #[derive(Clone, Debug)]
-pub struct DishError(String);
+pub struct DishError {
+    kind: DishErrorKind,
+    message: String,
+}
+
+/// What kind of error a `DishError` is, so callers can handle
+/// failures differently without picking apart the message
+///
+///   * WrongType       - the dish holds the wrong type of data, like binary data for a textual operation
+///   * DecodeError     - the dish's data couldn't be parsed or decoded
+///   * InvalidArgument - an argument to the operation is missing or invalid
+///   * Io              - reading or writing something outside of codebake failed
+///   * Unsupported     - the operation doesn't support what was asked of it
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DishErrorKind {
+    WrongType,
+    DecodeError,
+    InvalidArgument,
+    Io,
+    Unsupported,
+}
 
 /// DishData represents both the type of data and
 /// the data contained within it. The types are not very rich
@@ -57,18 +79,41 @@ pub enum Dish {
 
 /// Represents an argument to an Operation declaratively
 ///
-#[derive(Debug)]
+///   * Integer - a whole number
+///   * String  - any string
+///   * Bytes   - raw bytes, taken from a dish, a string, or a list of numbers in the lisp
+///   * Choice  - a string that must be one of the listed values
+///
+/// Serializes as `"integer"`, `"string"` and `"bytes"`, with a choice
+/// as `{"choice": [...]}`
+///
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum OperationArgType {
     Integer,
     String,
+    Bytes,
+    Choice(&'static [&'static str]),
 }
 
 /// Actually holds an argument value for an Operation
 ///
+/// `OperationArgType::Choice` arguments are stored as `OperationArg::String`
+///
 #[derive(Clone, Debug)]
 pub enum OperationArg {
     Integer(i64),
     String(String),
+    Bytes(Vec<u8>),
+}
+
+/// A statically declared default value for an argument
+///
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum OperationArgDefault {
+    Integer(i64),
+    String(&'static str),
 }
 
 /// Function pointer to an operation
@@ -84,9 +129,11 @@ type Operation = fn(&OperationArguments, &mut DishData) -> DishResult;
 ///   * authors     - list of the authors who have contributed to the operation; feel free to
 ///                   add yourself if you've worked on this operation, even if only a small change!
 ///   * category    - category the operation belongs to; valid categories are:
-///                   `Textual`, `Data Format`
+///                   `Textual`, `Data Format`, `Utility`, `Analysis`, `Structured`, `Crypto`, `Datetime`, `Checksums`
 ///   * arguments   - list of 2-tuples where the first element is the name of the argument
 ///                 and the second argument is the type of the argument
+///   * defaults    - default values, by name, for trailing arguments that may be left out
+///   * aliases     - other names the operation can be called by, named like `name`
 ///   * op          - function pointer to the operation itself
 ///
 #[derive(Clone)]
@@ -96,6 +143,8 @@ pub struct OperationInfo {
     pub authors: &'static [&'static str],
     pub category: &'static str,
     pub arguments: &'static [(&'static str, OperationArgType)],
+    pub defaults: &'static [(&'static str, OperationArgDefault)],
+    pub aliases: &'static [&'static str],
     pub op: Operation,
 }
 
@@ -104,14 +153,51 @@ pub struct OperationInfo {
 ///
 /// Essentially acts as an Option<&HashMap<String, OperationArg>>
 ///
+#[derive(Clone)]
 pub struct OperationArguments {
     inner: Option<HashMap<String, OperationArg>>,
 }
 
+/// A list of operations, applied in order by `Recipe::bake`. This is the
+/// Rust-side equivalent of a `recipe` in the lisp
+///
+#[derive(Clone, Default)]
+pub struct Recipe {
+    steps: Vec<(String, OperationArguments)>,
+}
+
 /// The Result type of codebake
 ///
 pub type DishResult = result::Result<(), DishError>;
 
+impl OperationInfo {
+    /// Gets the default value of an argument, if it has one
+    ///
+    pub fn default_for(&self, name: &str) -> Option<&OperationArgDefault> {
+        self.defaults
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, d)| d)
+    }
+
+    /// Checks whether `name` is the name of this operation or one of its aliases
+    ///
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    /// Builds the arguments this operation gets when none are given,
+    /// one for every default
+    ///
+    pub fn default_arguments(&self) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        for (arg, default) in self.defaults {
+            args.insert(arg, OperationArg::from(default));
+        }
+        args
+    }
+}
+
 impl PartialEq for OperationInfo {
     fn eq(&self, other: &OperationInfo) -> bool {
         self.name == other.name
@@ -133,6 +219,50 @@ impl Dish {
         Dish::Success(DishData::Bin(data))
     }
 
+    /// Whether or not the dish still holds data
+    pub fn is_success(&self) -> bool {
+        matches!(self, Dish::Success(_))
+    }
+
+    /// Whether or not an operation on the dish has failed
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Dish::Failure(_))
+    }
+
+    /// The dish's error, if it has failed
+    pub fn error(&self) -> Option<&DishError> {
+        match self {
+            Dish::Success(_) => None,
+            Dish::Failure(e) => Some(e),
+        }
+    }
+
+    /// The dish's data, if it hasn't failed
+    pub fn data(&self) -> Option<&DishData> {
+        match self {
+            Dish::Success(data) => Some(data),
+            Dish::Failure(_) => None,
+        }
+    }
+
+    /// Formats the dish like `Display` does, but without cutting off long data
+    pub fn display_full(&self) -> String {
+        match self {
+            Dish::Success(data) => format!("Dish({})", data.display_full()),
+            Dish::Failure(_) => format!("{}", self),
+        }
+    }
+
+    /// Makes an independent copy of this `Dish`, data and all
+    ///
+    /// `Dish` owns its data, so this is the same as `clone`. It exists to make it
+    /// obvious at the call site that a dish shared through an `Rc<RefCell<Dish>>`
+    /// is being copied rather than shared
+    ///
+    pub fn deep_clone(&self) -> Dish {
+        self.clone()
+    }
+
     /// Takes a function of type `DishData -> DishResult` (AKA an operation)
     /// and consumes `self`, producing a new `Dish` with the
     /// operation applied.
@@ -146,16 +276,175 @@ impl Dish {
         }
         self
     }
+
+    /// Applies the operation called `name` (as it's named in the lisp) with `args`.
+    /// Arguments with a default can be left out of `args`.
+    ///
+    /// If there's no operation called `name`, the dish becomes a failure
+    ///
+    pub fn apply_named(&mut self, name: &str, args: &OperationArguments) -> &mut Dish {
+        let oi = match ops::OPERATIONS.iter().find(|oi| oi.is_named(name)) {
+            Some(oi) => oi,
+            None => {
+                let e = DishError::unsupported(format!("no operation named '{}'", name));
+                *self = Dish::Failure(e);
+                return self;
+            }
+        };
+
+        if oi.defaults.is_empty() {
+            return self.apply(oi.op, args);
+        }
+        let mut args = match args.inner {
+            Some(_) => args.clone(),
+            None => OperationArguments::new(),
+        };
+        for (arg, default) in oi.defaults {
+            if !args.contains(arg) {
+                args.insert(arg, OperationArg::from(default));
+            }
+        }
+        self.apply(oi.op, &args)
+    }
+}
+
+impl DishError {
+    /// Creates a new error of the given kind
+    pub fn new<S: Into<String>>(kind: DishErrorKind, message: S) -> DishError {
+        DishError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Shorthand for a `DishErrorKind::WrongType` error
+    pub fn wrong_type<S: Into<String>>(message: S) -> DishError {
+        DishError::new(DishErrorKind::WrongType, message)
+    }
+
+    /// Shorthand for a `DishErrorKind::DecodeError` error
+    pub fn decode<S: Into<String>>(message: S) -> DishError {
+        DishError::new(DishErrorKind::DecodeError, message)
+    }
+
+    /// Shorthand for a `DishErrorKind::InvalidArgument` error
+    pub fn invalid_argument<S: Into<String>>(message: S) -> DishError {
+        DishError::new(DishErrorKind::InvalidArgument, message)
+    }
+
+    /// Shorthand for a `DishErrorKind::Io` error
+    pub fn io<S: Into<String>>(message: S) -> DishError {
+        DishError::new(DishErrorKind::Io, message)
+    }
+
+    /// Shorthand for a `DishErrorKind::Unsupported` error
+    pub fn unsupported<S: Into<String>>(message: S) -> DishError {
+        DishError::new(DishErrorKind::Unsupported, message)
+    }
+
+    /// What kind of error this is
+    pub fn kind(&self) -> DishErrorKind {
+        self.kind
+    }
+
+    /// The message describing what went wrong, without the `dish error:` prefix
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Recipe {
+    /// Creates a recipe with no steps
+    pub fn new() -> Recipe {
+        Recipe { steps: Vec::new() }
+    }
+
+    /// Adds the operation called `name` (as it's named in the lisp) with `args`
+    /// to the end of the recipe. Arguments with a default can be left out of `args`
+    ///
+    pub fn push(mut self, name: &str, args: &OperationArguments) -> Recipe {
+        self.steps.push((name.to_string(), args.clone()));
+        self
+    }
+
+    /// Applies every step of the recipe to `dish`, in order
+    ///
+    /// Baking stops at the first step that fails, and the dish's error says
+    /// which step that was. A dish that has already failed is left alone.
+    /// For example:
+    ///
+    /// ```
+    /// use codebake::{Dish, Recipe, EMPTY_ARGS};
+    ///
+    /// let recipe = Recipe::new()
+    ///     .push("to-base64", &EMPTY_ARGS)
+    ///     .push("reverse", &EMPTY_ARGS);
+    /// let mut dish = Dish::from_string("hello".to_string());
+    /// recipe.bake(&mut dish);
+    /// assert_eq!(format!("{}", dish), "Dish(\"=8GbsVGa\")");
+    ///
+    /// let mut dish = Dish::from_string("hello".to_string());
+    /// Recipe::new().push("from-hex", &EMPTY_ARGS).bake(&mut dish);
+    /// assert!(format!("{}", dish).contains("step 1 (from-hex)"));
+    /// ```
+    ///
+    pub fn bake<'a>(&self, dish: &'a mut Dish) -> &'a mut Dish {
+        if let Dish::Failure(_) = dish {
+            return dish;
+        }
+        for (step, (name, args)) in self.steps.iter().enumerate() {
+            if let Dish::Failure(e) = dish.apply_named(name, args) {
+                e.message = format!("step {} ({}): {}", step + 1, name, e.message);
+                break;
+            }
+        }
+        dish
+    }
 }
 
 impl DishData {
+    /// Formats the data like `Display` does, cutting it off with a `...` after
+    /// `max` characters. If `max` is `None`, nothing is cut off
+    ///
+    pub fn display_truncated(&self, max: Option<usize>) -> String {
+        let (mut s, quotes) = match self {
+            DishData::Str(s) => (s.clone(), ("\"", "\"")),
+            DishData::Bin(b) => (String::from_utf8_lossy(b).into_owned(), ("[", "]")),
+        };
+        if let Some((i, _)) = max.and_then(|max| s.char_indices().nth(max)) {
+            s.truncate(i);
+            s.push_str("...");
+        }
+        format!("{}{}{}", quotes.0, s, quotes.1)
+    }
+
+    /// Formats the data like `Display` does, but without cutting off long data
+    ///
+    pub fn display_full(&self) -> String {
+        self.display_truncated(None)
+    }
+
     /// Helper method that converts any DishData to bytes
-    fn as_bytes(&self) -> &[u8] {
+    pub fn as_bytes(&self) -> &[u8] {
         match self {
             DishData::Str(s) => s.as_bytes(),
             DishData::Bin(b) => b,
         }
     }
+
+    /// Helper method that gives mutable access to the bytes of any DishData,
+    /// so operations can work in place instead of building a new one
+    ///
+    /// # Safety
+    /// For `DishData::Str`, the bytes must still be valid UTF-8 once the
+    /// borrow ends, the same as `str::as_bytes_mut`
+    ///
+    pub(crate) unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        match self {
+            DishData::Str(s) => s.as_bytes_mut(),
+            DishData::Bin(b) => b,
+        }
+    }
 }
 
 impl OperationArguments {
@@ -173,39 +462,58 @@ impl OperationArguments {
         }
     }
 
+    /// Whether or not there's an argument called `name`
+    ///
+    pub fn contains(&self, name: &str) -> bool {
+        match &self.inner {
+            None => false,
+            Some(h) => h.contains_key(name),
+        }
+    }
+
+    /// Get an argument out of the OperationArguments by name
+    ///
+    fn get(&self, name: &str) -> Result<&OperationArg, DishError> {
+        self.inner
+            .as_ref()
+            .and_then(|h| h.get(name))
+            .ok_or_else(|| DishError::invalid_argument(format!("argument '{}' not found", name)))
+    }
+
+    /// helper function for the error when an argument has the wrong type
+    ///
+    fn wrong_type(name: &str, expected: &str, got: &OperationArg) -> DishError {
+        DishError::invalid_argument(format!(
+            "argument '{}' expected {}, got {}",
+            name, expected, got
+        ))
+    }
+
     /// Get an integer out of the OperationArguments by-name
     ///
     pub fn get_integer(&self, name: &str) -> Result<i64, DishError> {
-        match &self.inner {
-            None => return Err(DishError("empty arguments".to_string())),
-            Some(h) => match h.get(name) {
-                None => Err(DishError("no such argument".to_string())),
-                Some(arg) => {
-                    if let OperationArg::Integer(i) = arg {
-                        Ok(*i)
-                    } else {
-                        Err(DishError("wrong argument type".to_string()))
-                    }
-                }
-            },
+        match self.get(name)? {
+            OperationArg::Integer(i) => Ok(*i),
+            arg => Err(Self::wrong_type(name, "integer", arg)),
         }
     }
 
     /// Get a string out of the OperationArguments by name
+    /// This is also used for `OperationArgType::Choice` arguments
     ///
     pub fn get_string(&self, name: &str) -> Result<String, DishError> {
-        match &self.inner {
-            None => return Err(DishError("empty arguments".to_string())),
-            Some(h) => match h.get(name) {
-                None => Err(DishError("no such argument".to_string())),
-                Some(arg) => {
-                    if let OperationArg::String(s) = arg {
-                        Ok(s.clone())
-                    } else {
-                        Err(DishError("wrong argument type".to_string()))
-                    }
-                }
-            },
+        match self.get(name)? {
+            OperationArg::String(s) => Ok(s.clone()),
+            arg => Err(Self::wrong_type(name, "string", arg)),
+        }
+    }
+
+    /// Get raw bytes out of the OperationArguments by name
+    ///
+    pub fn get_bytes(&self, name: &str) -> Result<Vec<u8>, DishError> {
+        match self.get(name)? {
+            OperationArg::Bytes(b) => Ok(b.clone()),
+            arg => Err(Self::wrong_type(name, "bytes", arg)),
         }
     }
 }
@@ -221,32 +529,48 @@ impl fmt::Display for Dish {
 
 impl fmt::Display for DishData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            DishData::Str(s) => {
-                let mut truncated = s.clone();
-                let new_char = truncated.char_indices().nth(80);
-                if new_char.is_some() {
-                    truncated.truncate(new_char.unwrap().0);
-                    truncated.push_str("...");
-                }
-                write!(f, "\"{}\"", truncated)
-            }
-            DishData::Bin(b) => {
-                let mut truncated = String::from_utf8_lossy(b).into_owned();
-                let new_char = truncated.char_indices().nth(32);
-                if new_char.is_some() {
-                    truncated.truncate(new_char.unwrap().0);
-                    truncated.push_str("...");
-                }
-                write!(f, "[{}]", truncated)
-            }
-        }
+        let max = match self {
+            DishData::Str(_) => 80,
+            DishData::Bin(_) => 32,
+        };
+        write!(f, "{}", self.display_truncated(Some(max)))
     }
 }
 
 impl fmt::Display for DishError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "dish error: {}", self.0)
+        write!(f, "dish error: {}", self.message)
+    }
+}
+
+impl From<&OperationArgDefault> for OperationArg {
+    fn from(d: &OperationArgDefault) -> Self {
+        match d {
+            OperationArgDefault::Integer(i) => OperationArg::Integer(*i),
+            OperationArgDefault::String(s) => OperationArg::String(s.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for DishErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            DishErrorKind::WrongType => "wrong-type",
+            DishErrorKind::DecodeError => "decode-error",
+            DishErrorKind::InvalidArgument => "invalid-argument",
+            DishErrorKind::Io => "io",
+            DishErrorKind::Unsupported => "unsupported",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl fmt::Display for OperationArgDefault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OperationArgDefault::Integer(i) => write!(f, "{}", i),
+            OperationArgDefault::String(s) => write!(f, "\"{}\"", s),
+        }
     }
 }
 
@@ -255,6 +579,7 @@ impl fmt::Display for OperationArg {
         let s = match self {
             OperationArg::Integer(_) => "integer",
             OperationArg::String(_) => "string",
+            OperationArg::Bytes(_) => "bytes",
         };
         write!(f, "{}", s)
     }
@@ -271,3 +596,9 @@ impl Into<OperationArg> for String {
         OperationArg::String(self)
     }
 }
+
+impl From<Vec<u8>> for OperationArg {
+    fn from(b: Vec<u8>) -> OperationArg {
+        OperationArg::Bytes(b)
+    }
+}
@@ -4,10 +4,18 @@
 //! tutorial: https://stopa.io/post/222
 //!
 
-use crate::lisp::{Environment, Error, Expression, Lambda};
+use crate::lisp::functions::call;
+use crate::lisp::{Environment, Error, EvalLimits, Expression, Lambda};
+use crate::Dish;
 use std::collections::HashMap;
 use std::rc::Rc;
 
+/// how close to the end of the stack evaluation can get before it's grown
+const STACK_RED_ZONE: usize = 256 * 1024;
+
+/// how much stack is added each time it's grown
+const STACK_GROW_SIZE: usize = 4 * 1024 * 1024;
+
 pub fn eval(expr: &Expression, env: &mut Environment) -> Result<Expression, Error> {
     match expr {
         Expression::Symbol(k) => {
@@ -21,37 +29,47 @@ pub fn eval(expr: &Expression, env: &mut Environment) -> Result<Expression, Erro
         }
         Expression::Number(_) => Ok(expr.clone()),
         Expression::Bool(_) => Ok(expr.clone()),
+        Expression::Nil => Ok(Expression::Nil),
         Expression::String(_) => Ok(expr.clone()),
-        Expression::List(list) => {
-            let first_form = list
-                .first()
-                .ok_or_else(|| Error("expected a non-empty list.".to_string()))?;
-
-            let arg_forms = &list[1..];
-            match eval_builtin_form(first_form, arg_forms, env) {
-                Some(res) => res,
-                None => {
-                    let first_eval = eval(first_form, env)?;
-                    match first_eval {
-                        Expression::Func(f) => f(&eval_forms(arg_forms, env)?),
-                        Expression::Lambda(f) => {
-                            let new_env = &mut env_for_lambda(f.params, arg_forms, env)?;
-                            eval(&f.body, new_env)
-                        }
-                        other => Err(Error(format!(
-                            "expected first expression to be a function. got '{}'.",
-                            other
-                        ))),
-                    }
+        Expression::List(_) => {
+            let _depth = DepthGuard::enter(&env.limits)?;
+            // the depth limit is far more than a normal thread's stack can fit,
+            // so the stack is grown on the heap as it runs out
+            stacker::maybe_grow(STACK_RED_ZONE, STACK_GROW_SIZE, || {
+                match eval_tail(expr, env)? {
+                    Tail::Done(res) => Ok(res),
+                    Tail::Call(f, args) => call_lambda(f, args, env),
                 }
-            }
+            })
         }
         Expression::Func(_) => Err(Error("cannot eval function.".to_string())),
+        Expression::Operation(..) => Err(Error("cannot eval operation.".to_string())),
         Expression::Lambda(_) => Err(Error("cannot eval lambda function.".to_string())),
         Expression::Dish(_) => Ok(expr.clone()),
     }
 }
 
+/// Counts one level of evaluation depth for as long as it's alive
+///
+struct DepthGuard(Rc<EvalLimits>);
+
+impl DepthGuard {
+    fn enter(limits: &Rc<EvalLimits>) -> Result<DepthGuard, Error> {
+        let depth = limits.depth.get();
+        if depth >= limits.max_depth.get() {
+            return Err(Error("maximum recursion depth exceeded".to_string()));
+        }
+        limits.depth.set(depth + 1);
+        Ok(DepthGuard(limits.clone()))
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        self.0.depth.set(self.0.depth.get() - 1);
+    }
+}
+
 fn env_get(k: &str, env: &Environment) -> Option<Expression> {
     match env.data.get(k) {
         Some(expr) => Some(expr.clone()),
@@ -66,28 +84,137 @@ fn eval_forms(arg_forms: &[Expression], env: &mut Environment) -> Result<Vec<Exp
     arg_forms.iter().map(|x| eval(x, env)).collect()
 }
 
-fn env_for_lambda<'a>(
-    params: Rc<Expression>,
-    arg_forms: &[Expression],
-    outer_env: &'a mut Environment,
-) -> Result<Environment<'a>, Error> {
-    let ks = parse_list_of_symbol_strings(params)?;
-    if ks.len() != arg_forms.len() {
+/// What evaluating an expression in tail position left to do
+///
+enum Tail {
+    /// the expression was fully evaluated
+    Done(Expression),
+    /// the expression is a call to this lambda, with these (evaluated) arguments
+    Call(Lambda, Vec<Expression>),
+}
+
+/// Calls the lambda `f`, with `env` as the caller's environment
+///
+/// This is a trampoline: when the body ends in another lambda call (directly, or through
+/// the branches of `if` and `do`), the frame is replaced instead of recursing into
+/// `eval`, so tail recursion runs in constant stack space
+///
+fn call_lambda(
+    f: Lambda,
+    args: Vec<Expression>,
+    env: &mut Environment,
+) -> Result<Expression, Error> {
+    // the body sees its arguments, what it captured, and the globals. not the caller's locals
+    let mut global: &Environment = env;
+    while let Some(outer) = global.outer {
+        global = outer;
+    }
+
+    let mut f = f;
+    let mut data = bind_lambda_args(&f, args)?;
+    let mut tail_calls = 0;
+    loop {
+        let mut frame = Environment {
+            data,
+            outer: Some(global),
+            limits: global.limits.clone(),
+        };
+        match eval_tail(&f.body, &mut frame)? {
+            Tail::Done(res) => return Ok(res),
+            Tail::Call(next, args) => {
+                tail_calls += 1;
+                if tail_calls > global.limits.max_tail_calls.get() {
+                    return Err(Error("maximum recursion depth exceeded".to_string()));
+                }
+                data = bind_lambda_args(&next, args)?;
+                f = next;
+            }
+        }
+    }
+}
+
+/// Evaluates `expr` as the tail of a lambda body, handing a final lambda call back to
+/// `call_lambda` rather than making it
+///
+fn eval_tail(expr: &Expression, env: &mut Environment) -> Result<Tail, Error> {
+    let list = match expr {
+        Expression::List(list) => list,
+        _ => return eval(expr, env).map(Tail::Done),
+    };
+    // the empty list evaluates to nil
+    let first_form = match list.first() {
+        Some(first_form) => first_form,
+        None => return Ok(Tail::Done(Expression::Nil)),
+    };
+
+    let arg_forms = &list[1..];
+    match first_form {
+        Expression::Symbol(s) if s == "if" => {
+            let branch = eval_if_branch(arg_forms, env)?;
+            eval_tail(branch, env)
+        }
+        Expression::Symbol(s) if s == "do" => {
+            let (last, init) = arg_forms
+                .split_last()
+                .ok_or_else(|| Error("expected at least 1 expression. got nothing.".to_string()))?;
+            eval_forms(init, env)?;
+            eval_tail(last, env)
+        }
+        _ => match eval_builtin_form(first_form, arg_forms, env) {
+            Some(res) => res.map(Tail::Done),
+            None => match eval(first_form, env)? {
+                first_eval @ (Expression::Func(_) | Expression::Operation(..)) => {
+                    call(&first_eval, &eval_forms(arg_forms, env)?).map(Tail::Done)
+                }
+                Expression::Lambda(f) => Ok(Tail::Call(f, eval_forms(arg_forms, env)?)),
+                other => Err(Error(format!(
+                    "expected first expression to be a function. got '{}'.",
+                    other
+                ))),
+            },
+        },
+    }
+}
+
+/// Binds `args` to the parameters of `f`, on top of the variables `f` captured
+/// (and `f` itself, if it's a named local function so it can call itself)
+///
+fn bind_lambda_args(
+    f: &Lambda,
+    args: Vec<Expression>,
+) -> Result<HashMap<String, Expression>, Error> {
+    let ks = parse_list_of_symbol_strings(f.params.clone())?;
+    if ks.len() != args.len() {
         return Err(Error(format!(
             "expected {} arguments. got {}.",
             ks.len(),
-            arg_forms.len()
+            args.len()
         )));
     }
-    let vs = eval_forms(arg_forms, outer_env)?;
-    let mut data: HashMap<String, Expression> = HashMap::new();
-    for (k, v) in ks.iter().zip(vs.iter()) {
-        data.insert(k.clone(), v.clone());
+    let mut data: HashMap<String, Expression> = f.captured.as_ref().clone();
+    if let Some(name) = &f.name {
+        data.insert(name.clone(), Expression::Lambda(f.clone()));
     }
-    Ok(Environment {
-        data,
-        outer: Some(outer_env),
-    })
+    for (k, v) in ks.into_iter().zip(args) {
+        data.insert(k, v);
+    }
+    Ok(data)
+}
+
+/// Flattens the local variables of `env` into one map for a lambda to capture, inner
+/// scopes shadowing outer ones. The outermost (global) environment is left out
+/// since it's still reachable when the lambda gets called
+///
+fn capture_locals(env: &Environment) -> HashMap<String, Expression> {
+    let mut captured = HashMap::new();
+    let mut scope = env;
+    while let Some(outer) = scope.outer {
+        for (k, v) in &scope.data {
+            captured.entry(k.clone()).or_insert_with(|| v.clone());
+        }
+        scope = outer;
+    }
+    captured
 }
 
 fn parse_list_of_symbol_strings(form: Rc<Expression>) -> Result<Vec<String>, Error> {
@@ -114,10 +241,18 @@ pub fn eval_builtin_form(
     match expr {
         Expression::Symbol(s) => match s.as_ref() {
             "if" => Some(eval_if_args(arg_forms, env)),
+            "do" => Some(eval_do_args(arg_forms, env)),
             "def" => Some(eval_def_args(arg_forms, env)),
-            "fn" => Some(eval_lambda_args(arg_forms)),
+            "fn" => Some(eval_lambda_args(arg_forms, env)),
             "defn" => Some(eval_defn_args(arg_forms, env)),
             "quote" => Some(eval_quote_args(arg_forms)),
+            "quasiquote" => Some(eval_quasiquote_args(arg_forms, env)),
+            "eval" => Some(eval_eval_args(arg_forms, env)),
+            "try" => Some(eval_try_args(arg_forms, env)),
+            "unquote" | "unquote-splicing" => Some(Err(Error(format!(
+                "'{}' is only valid inside a quasiquote.",
+                s
+            )))),
             _ => None,
         },
         _ => None,
@@ -125,23 +260,42 @@ pub fn eval_builtin_form(
 }
 
 pub fn eval_if_args(exprs: &[Expression], env: &mut Environment) -> Result<Expression, Error> {
+    let branch = eval_if_branch(exprs, env)?;
+    eval(branch, env)
+}
+
+/// Evaluates the test of an `if` and returns the branch that should be evaluated next
+///
+fn eval_if_branch<'a>(
+    exprs: &'a [Expression],
+    env: &mut Environment,
+) -> Result<&'a Expression, Error> {
     let test_form = exprs
         .first()
         .ok_or_else(|| Error("expected test expression. got nothing.".to_string()))?;
-    let test_eval = eval(test_form, env)?;
-    match test_eval {
-        Expression::Bool(b) => {
-            let form_idx = if b { 1 } else { 2 };
-            let res_form = exprs
-                .get(form_idx)
-                .ok_or_else(|| Error(format!("expected branch. got '{}'.", form_idx)))?;
-            eval(res_form, env)
+    // nil is as good as false
+    let test = match eval(test_form, env)? {
+        Expression::Bool(b) => b,
+        Expression::Nil => false,
+        _ => {
+            return Err(Error(format!(
+                "expected boolean expression. got '{}'.",
+                test_form
+            )))
         }
-        _ => Err(Error(format!(
-            "expected boolean expression. got '{}'.",
-            test_form
-        ))),
-    }
+    };
+    let form_idx = if test { 1 } else { 2 };
+    exprs
+        .get(form_idx)
+        .ok_or_else(|| Error(format!("expected branch. got '{}'.", form_idx)))
+}
+
+pub fn eval_do_args(exprs: &[Expression], env: &mut Environment) -> Result<Expression, Error> {
+    let (last, init) = exprs
+        .split_last()
+        .ok_or_else(|| Error("expected at least 1 expression. got nothing.".to_string()))?;
+    eval_forms(init, env)?;
+    eval(last, env)
 }
 
 pub fn eval_def_args(exprs: &[Expression], env: &mut Environment) -> Result<Expression, Error> {
@@ -166,7 +320,7 @@ pub fn eval_def_args(exprs: &[Expression], env: &mut Environment) -> Result<Expr
     Ok(first_form.clone())
 }
 
-pub fn eval_lambda_args(arg_forms: &[Expression]) -> Result<Expression, Error> {
+pub fn eval_lambda_args(arg_forms: &[Expression], env: &Environment) -> Result<Expression, Error> {
     let params_expr = arg_forms
         .first()
         .ok_or_else(|| Error("expected parameters. got nothing.".to_string()))?;
@@ -181,6 +335,8 @@ pub fn eval_lambda_args(arg_forms: &[Expression]) -> Result<Expression, Error> {
     Ok(Expression::Lambda(Lambda {
         body: Rc::new(body_expr.clone()),
         params: Rc::new(params_expr.clone()),
+        captured: Rc::new(capture_locals(env)),
+        name: None,
     }))
 }
 
@@ -199,13 +355,14 @@ pub fn eval_defn_args(exprs: &[Expression], env: &mut Environment) -> Result<Exp
         .get(2)
         .ok_or_else(|| Error("expected function body".to_string()))?;
 
-    env.data.insert(
-        name,
-        Expression::Lambda(Lambda {
-            body: Rc::new(body_expr.clone()),
-            params: Rc::new(params_expr.clone()),
-        }),
-    );
+    let lambda = Expression::Lambda(Lambda {
+        body: Rc::new(body_expr.clone()),
+        params: Rc::new(params_expr.clone()),
+        captured: Rc::new(capture_locals(env)),
+        // a global function can already find itself in the global environment
+        name: env.outer.map(|_| name.clone()),
+    });
+    env.data.insert(name, lambda);
 
     Ok(first_form.clone())
 }
@@ -220,3 +377,114 @@ fn eval_quote_args(exprs: &[Expression]) -> Result<Expression, Error> {
 
     Ok(exprs[0].clone())
 }
+
+/// Evaluates the argument, then evaluates whatever it evaluated to. That way
+/// `(eval '(+ 1 2))` runs the quoted expression in the current environment
+///
+fn eval_eval_args(exprs: &[Expression], env: &mut Environment) -> Result<Expression, Error> {
+    if exprs.len() != 1 {
+        return Err(Error(format!(
+            "expected exactly 1 argument. got {}.",
+            exprs.len()
+        )));
+    }
+
+    let form = eval(&exprs[0], env)?;
+    eval(&form, env)
+}
+
+/// Evaluates `(try expr handler)`. If evaluating `expr` fails, or gives a failed dish,
+/// `handler` is evaluated instead with the error message bound to `error`. For a failed
+/// dish, the kind of error (like `"decode-error"`) is bound to `error-kind`, otherwise it's nil
+///
+fn eval_try_args(exprs: &[Expression], env: &mut Environment) -> Result<Expression, Error> {
+    if exprs.len() != 2 {
+        return Err(Error(format!(
+            "expected exactly 2 arguments. got {}.",
+            exprs.len()
+        )));
+    }
+
+    let (message, kind) = match eval(&exprs[0], env) {
+        Ok(Expression::Dish(d)) => match &*d.borrow() {
+            Dish::Failure(e) => (e.to_string(), Expression::String(e.kind().to_string())),
+            Dish::Success(_) => return Ok(Expression::Dish(d.clone())),
+        },
+        Ok(res) => return Ok(res),
+        Err(e) => (e.to_string(), Expression::Nil),
+    };
+
+    let mut data = HashMap::new();
+    data.insert("error".to_string(), Expression::String(message));
+    data.insert("error-kind".to_string(), kind);
+    let mut handler_env = Environment {
+        data,
+        outer: Some(env),
+        limits: env.limits.clone(),
+    };
+    eval(&exprs[1], &mut handler_env)
+}
+
+fn eval_quasiquote_args(exprs: &[Expression], env: &mut Environment) -> Result<Expression, Error> {
+    if exprs.len() != 1 {
+        return Err(Error(format!(
+            "expected exactly 1 argument. got {}.",
+            exprs.len()
+        )));
+    }
+
+    quasiquote(&exprs[0], env)
+}
+
+/// Returns `expr` literally, except that `(unquote x)` forms are replaced by the result
+/// of evaluating `x` and `(unquote-splicing x)` forms have the list `x` evaluates to
+/// spliced into the surrounding list
+///
+fn quasiquote(expr: &Expression, env: &mut Environment) -> Result<Expression, Error> {
+    let list = match expr {
+        Expression::List(list) => list,
+        other => return Ok(other.clone()),
+    };
+
+    if let Some(form) = unquoted_form("unquote", expr)? {
+        return eval(form, env);
+    }
+
+    let mut out = Vec::with_capacity(list.len());
+    for item in list {
+        match unquoted_form("unquote-splicing", item)? {
+            Some(form) => match eval(form, env)? {
+                Expression::List(spliced) => out.extend(spliced),
+                // the empty list evaluates to nil, so that splices nothing
+                Expression::Nil => {}
+                other => {
+                    return Err(Error(format!(
+                        "expected unquote-splicing to produce a list. got '{}'.",
+                        other
+                    )))
+                }
+            },
+            None => out.push(quasiquote(item, env)?),
+        }
+    }
+
+    Ok(Expression::List(out))
+}
+
+/// helper function that returns `x` if `expr` is the form `(name x)`
+///
+fn unquoted_form<'a>(name: &str, expr: &'a Expression) -> Result<Option<&'a Expression>, Error> {
+    match expr {
+        Expression::List(list) if matches!(list.first(), Some(Expression::Symbol(s)) if s == name) => {
+            match list.len() {
+                2 => Ok(Some(&list[1])),
+                n => Err(Error(format!(
+                    "expected exactly 1 argument to {}. got {}.",
+                    name,
+                    n - 1
+                ))),
+            }
+        }
+        _ => Ok(None),
+    }
+}
@@ -6,79 +6,146 @@
 //! that points to the closure to call from the lisp.
 //!
 
-use crate::lisp::{Environment, Error, Expression, LispResult};
-use crate::{Dish, OperationArg, OperationArgType, OperationArguments, OperationInfo, EMPTY_ARGS};
-use std::fs;
+use crate::lisp::{default_reader, Environment, Error, EvalLimits, Expression, LispResult};
+use crate::ops::{regex_helper, shannon_entropy};
+use crate::{
+    Dish, DishData, DishError, OperationArg, OperationArgType, OperationArguments, OperationInfo,
+    EMPTY_ARGS,
+};
 use std::cell::RefCell;
+use std::fs;
+use std::io::Read;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 pub fn embed_operation(oi: &'static OperationInfo, env: &mut Environment) {
-    // if the operation has no arguments, don't add the argument parsing
-    // wrapper closure. just embed it raw
-    if oi.arguments.len() == 0 {
-        env.data.insert(
-            oi.name.to_string(),
-            Expression::Func(Rc::new(move |args: &[Expression]| -> LispResult {
-                ensure_exact_args(args, 1)?;
-
-                if let Expression::Dish(dish) = &args[0] {
-                    dish.borrow_mut().apply(oi.op, &EMPTY_ARGS);
-                    Ok(Expression::Dish(dish.clone()))
-                } else {
-                    Err(Error("1st argument must be a Dish".to_string()))
-                }
-            })),
-        );
-        return;
+    // operations are embedded as plain data rather than closures. this way
+    // recipes made of them can be inspected and sent across threads
+    env.data
+        .insert(oi.name.to_string(), Expression::Operation(oi, None));
+    for alias in oi.aliases {
+        env.data
+            .insert(alias.to_string(), Expression::Operation(oi, None));
     }
+}
+
+/// Calls a builtin function or operation with already-evaluated arguments
+///
+/// An operation that takes arguments and hasn't been given them yet
+/// binds `args` and returns the bound operation. Otherwise, it is
+/// applied to the single `Dish` in `args`.
+///
+pub fn call(f: &Expression, args: &[Expression]) -> LispResult {
+    match f {
+        Expression::Func(f) => f(args),
+        Expression::Operation(oi, None) if !oi.arguments.is_empty() => {
+            // an operation whose arguments all have defaults can be applied straight to a dish
+            let all_defaults = oi
+                .arguments
+                .iter()
+                .all(|(n, _)| oi.default_for(n).is_some());
+            if all_defaults && matches!(args, [Expression::Dish(_)]) {
+                let hargs = parse_args(oi, &[])?;
+                return call(&Expression::Operation(oi, Some(Rc::new(hargs))), args);
+            }
 
-    // otherwise, INCLUDE the wrapper closure that parses args
-    env.data.insert(
-        oi.name.to_string(),
-        Expression::Func(Rc::new(move |args: &[Expression]| -> LispResult {
             let hargs = parse_args(oi, args)?;
-            Ok(Expression::Func(Rc::new(
-                move |args: &[Expression]| -> LispResult {
-                    ensure_exact_args(args, 1)?;
+            Ok(Expression::Operation(oi, Some(Rc::new(hargs))))
+        }
+        Expression::Operation(oi, hargs) => {
+            ensure_exact_args(args, 1)?;
 
-                    if let Expression::Dish(dish) = &args[0] {
-                        dish.borrow_mut().apply(oi.op, &hargs);
-                        Ok(Expression::Dish(dish.clone()))
-                    } else {
-                        Err(Error("1st argument must be a Dish".to_string()))
-                    }
-                },
-            )))
-        })),
-    );
+            if let Expression::Dish(dish) = &args[0] {
+                let hargs = hargs.as_deref().unwrap_or(&EMPTY_ARGS);
+                dish.borrow_mut().apply(oi.op, hargs);
+                Ok(Expression::Dish(dish.clone()))
+            } else {
+                Err(Error("1st argument must be a Dish".to_string()))
+            }
+        }
+        _ => Err(Error(format!("expected a function. got '{}'.", f))),
+    }
 }
 
 fn parse_arg(typ: &OperationArgType, expr: &Expression) -> Result<OperationArg, Error> {
     match typ {
-        OperationArgType::Integer => {
-            if let Expression::Number(n) = expr {
+        // numbers are all floats in the lisp, so anything with a fractional part (or
+        // too big for an i64) is rejected here instead of being silently truncated
+        OperationArgType::Integer => match expr {
+            Expression::Number(n)
+                if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n < i64::MAX as f64 =>
+            {
                 Ok(OperationArg::Integer(*n as i64))
+            }
+            _ => Err(Error(format!("expected an integer. got {}.", expr))),
+        },
+        OperationArgType::String => Ok(OperationArg::String(expr.to_string())),
+        OperationArgType::Bytes => match expr {
+            Expression::Dish(d) => match &*d.borrow() {
+                Dish::Success(data) => Ok(OperationArg::Bytes(data.as_bytes().to_vec())),
+                Dish::Failure(e) => {
+                    Err(Error(format!("cannot use a failed dish as bytes. ({})", e)))
+                }
+            },
+            Expression::String(s) => Ok(OperationArg::Bytes(s.as_bytes().to_vec())),
+            Expression::List(l) => Ok(OperationArg::Bytes(
+                l.iter()
+                    .map(parse_byte)
+                    .collect::<Result<Vec<u8>, Error>>()?,
+            )),
+            _ => Err(Error(format!(
+                "expected a dish, string, or list of bytes. got {}.",
+                expr
+            ))),
+        },
+        OperationArgType::Choice(choices) => {
+            let s = expr.to_string();
+            if choices.contains(&s.as_str()) {
+                Ok(OperationArg::String(s))
             } else {
-                Err(Error(format!("expected an integer. got {}.", expr)))
+                Err(Error(format!(
+                    "expected one of {}. got {}.",
+                    choices.join(", "),
+                    expr
+                )))
             }
         }
-        OperationArgType::String => Ok(OperationArg::String(expr.to_string())),
     }
 }
 
 fn parse_args(oi: &OperationInfo, exprs: &[Expression]) -> Result<OperationArguments, Error> {
-    if oi.arguments.len() != exprs.len() {
-        return Err(Error(format!(
-            "expected exactly {} arguments. got {}.",
-            oi.arguments.len(),
-            exprs.len()
-        )));
+    // trailing arguments with defaults can be left out
+    let required = oi.arguments.len()
+        - oi.arguments
+            .iter()
+            .rev()
+            .take_while(|(name, _)| oi.default_for(name).is_some())
+            .count();
+    if exprs.len() < required || exprs.len() > oi.arguments.len() {
+        return Err(Error(if required == oi.arguments.len() {
+            format!(
+                "expected exactly {} arguments. got {}.",
+                oi.arguments.len(),
+                exprs.len()
+            )
+        } else {
+            format!(
+                "expected between {} and {} arguments. got {}.",
+                required,
+                oi.arguments.len(),
+                exprs.len()
+            )
+        }));
     }
 
     let mut ret: OperationArguments = OperationArguments::new();
 
-    for ((name, typ), expr) in oi.arguments.iter().zip(exprs) {
-        ret.insert(name, parse_arg(typ, expr)?);
+    for (i, (name, typ)) in oi.arguments.iter().enumerate() {
+        match (exprs.get(i), oi.default_for(name)) {
+            (Some(expr), _) => ret.insert(name, parse_arg(typ, expr)?),
+            (None, Some(default)) => ret.insert(name, OperationArg::from(default)),
+            (None, None) => unreachable!("missing arguments are checked above"),
+        }
     }
 
     Ok(ret)
@@ -112,8 +179,8 @@ pub fn lisp_apply() -> Expression {
         ensure_exact_args(args, 2)?;
 
         match &args[0] {
-            Expression::Func(f) => match &args[1] {
-                Expression::List(l) => f(l),
+            Expression::Func(_) | Expression::Operation(..) => match &args[1] {
+                Expression::List(l) => call(&args[0], l),
                 _ => Err(Error("2nd argument to 'apply' must be a list.".to_string())),
             },
             _ => Err(Error(
@@ -128,12 +195,8 @@ pub fn lisp_head() -> Expression {
         ensure_exact_args(args, 1)?;
 
         match &args[0] {
-            Expression::List(v) => {
-                if v.len() == 0 {
-                    return Ok(Expression::Symbol("nil".to_string()));
-                }
-                Ok(v.get(0).map(|x| x.clone()).unwrap())
-            }
+            Expression::List(v) => Ok(v.first().cloned().unwrap_or(Expression::Nil)),
+            Expression::Nil => Ok(Expression::Nil),
             _ => Err(Error(format!("expected a list. got '{}'.", &args[0]))),
         }
     }))
@@ -144,10 +207,8 @@ pub fn lisp_last() -> Expression {
         ensure_exact_args(args, 1)?;
 
         match &args[0] {
-            Expression::List(v) => v
-                .get(v.len() - 1)
-                .ok_or_else(|| Error("empty list".to_string()))
-                .map(|x| x.clone()),
+            Expression::List(v) => Ok(v.last().cloned().unwrap_or(Expression::Nil)),
+            Expression::Nil => Ok(Expression::Nil),
             _ => Err(Error(format!("expected a list. got '{}'.", &args[0]))),
         }
     }))
@@ -165,6 +226,7 @@ pub fn lisp_rest() -> Expression {
                     iter.map(|x| x.clone()).collect::<Vec<Expression>>(),
                 ))
             }
+            Expression::Nil => Ok(Expression::List(vec![])),
             _ => Err(Error(format!("expected a list. got '{}'.", &args[0]))),
         }
     }))
@@ -177,15 +239,22 @@ pub fn lisp_init() -> Expression {
         match &args[0] {
             Expression::List(v) => Ok(Expression::List(
                 v.iter()
-                    .take(v.len() - 1)
+                    .take(v.len().saturating_sub(1))
                     .map(|x| x.clone())
                     .collect::<Vec<Expression>>(),
             )),
+            Expression::Nil => Ok(Expression::List(vec![])),
             _ => Err(Error(format!("expected a list. got '{}'.", &args[0]))),
         }
     }))
 }
 
+/// Creates a new `Dish` from a string, or a binary `Dish` from a list of bytes
+///
+/// Dishes are shared, not copied: binding a dish to another name, or passing it
+/// to a function, refers to the same dish, so operating on one operates on both.
+/// Use `clone-dish` to get an independent copy
+///
 pub fn lisp_dish() -> Expression {
     Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
         ensure_exact_args(args, 1)?;
@@ -194,8 +263,21 @@ pub fn lisp_dish() -> Expression {
             Expression::String(s) => Ok(Expression::Dish(Rc::new(RefCell::new(
                 Dish::from_string(s.clone()),
             )))),
+            Expression::List(l) => {
+                let bytes = l
+                    .iter()
+                    .map(parse_byte)
+                    .collect::<Result<Vec<u8>, Error>>()?;
+                Ok(Expression::Dish(Rc::new(RefCell::new(Dish::from_bytes(
+                    bytes,
+                )))))
+            }
+            Expression::Nil => Ok(Expression::Dish(Rc::new(RefCell::new(Dish::from_bytes(
+                vec![],
+            ))))),
             _ => Err(Error(
-                "unsupported expression type for Dish. (must be string)".to_string(),
+                "unsupported expression type for Dish. (must be string or list of bytes)"
+                    .to_string(),
             )),
         }
     }))
@@ -208,7 +290,7 @@ pub fn lisp_recipe() -> Expression {
         let mut funcs: Vec<Expression> = Vec::new();
         for expr in args {
             match expr {
-                Expression::Func(_) => funcs.push(expr.clone()),
+                Expression::Func(_) | Expression::Operation(..) => funcs.push(expr.clone()),
                 _ => return Err(Error("expected function".to_string())),
             }
         }
@@ -217,197 +299,2087 @@ pub fn lisp_recipe() -> Expression {
     }))
 }
 
+/// What `bake` does when a step of the recipe fails
+///
+#[derive(Clone, Copy, PartialEq)]
+enum ErrorPolicy {
+    /// the dish stays failed and the remaining steps do nothing
+    Stop,
+    /// the failing step is undone and the recipe continues
+    Skip,
+    /// like `Skip`, but the dish fails at the end with every error encountered
+    Collect,
+}
+
+fn parse_error_policy(args: &[Expression]) -> Result<ErrorPolicy, Error> {
+    match (args.get(2), args.get(3)) {
+        (None, _) => Ok(ErrorPolicy::Stop),
+        (Some(Expression::Symbol(s)), Some(Expression::String(policy))) if s == ":on-error" => {
+            match policy.as_ref() {
+                "stop" => Ok(ErrorPolicy::Stop),
+                "skip" => Ok(ErrorPolicy::Skip),
+                "collect" => Ok(ErrorPolicy::Collect),
+                _ => Err(Error(format!(
+                    "expected either 'stop', 'skip', or 'collect'. got {}",
+                    policy
+                ))),
+            }
+        }
+        (Some(a), _) => Err(Error(format!(
+            "expected symbol ':on-error' followed by a string. got {}",
+            a
+        ))),
+    }
+}
+
+/// Applies a recipe to a dish, or to every dish in a list of dishes
+///
+/// Given a single dish, the same dish is returned. Given a list, each
+/// dish is baked in turn and the list is returned
+///
 pub fn lisp_bake() -> Expression {
     Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
-        ensure_exact_args(args, 2)?;
+        ensure_at_least_args(args, 2)?;
 
         let recipe = match &args[0] {
             Expression::List(v) => Ok(v),
             _ => Err(Error("expected list".to_string())),
         }?;
 
-        match &args[1] {
-            Expression::Dish(_) => Ok(()),
-            _ => Err(Error("expected Dish".to_string())),
-        }?;
+        let dishes = match &args[1] {
+            Expression::Dish(_) => None,
+            Expression::List(dishes) => Some(dishes.as_slice()),
+            // `()` is an empty list of dishes
+            Expression::Nil => Some(&[][..]),
+            _ => return Err(Error("expected Dish or list of dishes".to_string())),
+        };
+
+        let policy = parse_error_policy(args)?;
 
         // i cannot believe it inferred the type of the Vec here
         let mut funcs = Vec::new();
         for expr in recipe {
             match expr {
-                Expression::Func(f) => funcs.push(f.clone()),
+                Expression::Func(_) | Expression::Operation(..) => funcs.push(expr),
                 _ => return Err(Error("recipe must be list of functions.".to_string())),
             }
         }
 
-        for func in funcs {
-            func(&[args[1].clone()])?;
+        match dishes {
+            None => bake_dish(&funcs, &args[1], policy),
+            Some(dishes) => {
+                if let Some(other) = dishes.iter().find(|d| !matches!(d, Expression::Dish(_))) {
+                    return Err(Error(format!("expected a list of dishes. got {}", other)));
+                }
+                for dish in dishes {
+                    bake_dish(&funcs, dish, policy)?;
+                }
+                Ok(args[1].clone())
+            }
         }
-
-        Ok(args[1].clone())
     }))
 }
 
-pub fn lisp_empty() -> Expression {
-    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
-        ensure_exact_args(args, 1)?;
+/// helper function that applies every step of a recipe to a single dish for `bake`
+///
+fn bake_dish(funcs: &[&Expression], arg: &Expression, policy: ErrorPolicy) -> LispResult {
+    let dish = match arg {
+        Expression::Dish(d) => d,
+        _ => return Err(Error("expected Dish".to_string())),
+    };
 
-        let nil = Expression::Symbol("nil".to_string());
+    // under stop a failed dish is already passed through untouched by every step.
+    // the other policies would report it once per step, so it's handed back as is
+    if policy != ErrorPolicy::Stop && dish.borrow().is_failure() {
+        return Ok(arg.clone());
+    }
 
-        Ok(match &args[0] {
-            Expression::List(v) => Expression::Bool(v.is_empty()),
-            Expression::String(s) => Expression::Bool(s.is_empty()),
-            Expression::Dish(d) => match &*d.borrow() {
-                Dish::Success(data) => Expression::Bool(data.as_bytes().len() == 0),
-                _ => Expression::Bool(false),
+    let mut errors = Vec::new();
+    for (step, func) in funcs.iter().enumerate() {
+        if policy == ErrorPolicy::Stop {
+            call(func, std::slice::from_ref(arg))?;
+            continue;
+        }
+
+        // a function that errors goes through the policy the same as a failed operation
+        let before = dish.borrow().clone();
+        let failed = match call(func, std::slice::from_ref(arg)) {
+            Err(e) => Some(DishError::invalid_argument(format!(
+                "step {}: {}",
+                step + 1,
+                e
+            ))),
+            Ok(_) => match &*dish.borrow() {
+                Dish::Failure(e) => Some(DishError::new(
+                    e.kind(),
+                    format!("step {}: {}", step + 1, e.message()),
+                )),
+                Dish::Success(_) => None,
             },
-            _ => nil,
-        })
-    }))
+        };
+        if let Some(e) = failed {
+            errors.push(e);
+            *dish.borrow_mut() = before;
+        }
+    }
+
+    if policy == ErrorPolicy::Collect && !errors.is_empty() {
+        *dish.borrow_mut() = Dish::Failure(join_errors(&errors));
+    }
+
+    Ok(arg.clone())
 }
 
-pub fn lisp_cons() -> Expression {
+/// Checks that a recipe can be baked, without baking it
+///
+/// Every step has to be a function, and operations that take required
+/// arguments have to have been given them. Returns `true`, or an error
+/// naming the first step that's wrong
+///
+pub fn lisp_validate_recipe() -> Expression {
     Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
-        ensure_exact_args(args, 2)?;
+        ensure_exact_args(args, 1)?;
 
-        if let Expression::List(mut l) = args[1].clone() {
-            l.insert(0, args[0].clone());
-            Ok(Expression::List(l))
-        } else {
-            Err(Error("expected 2nd argument to be a list.".to_string()))
+        let recipe = match &args[0] {
+            Expression::List(v) => Ok(v),
+            _ => Err(Error("expected list".to_string())),
+        }?;
+
+        for (step, expr) in recipe.iter().enumerate() {
+            match expr {
+                Expression::Func(_) | Expression::Operation(_, Some(_)) => {}
+                Expression::Operation(oi, None) => {
+                    let missing: Vec<&str> = oi
+                        .arguments
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .filter(|name| oi.default_for(name).is_none())
+                        .collect();
+                    if !missing.is_empty() {
+                        return Err(Error(format!(
+                            "step {}: operation '{}' needs arguments ({}) but was given none",
+                            step + 1,
+                            oi.name,
+                            missing.join(", ")
+                        )));
+                    }
+                }
+                _ => {
+                    return Err(Error(format!(
+                        "step {}: expected a function. got {}",
+                        step + 1,
+                        expr
+                    )))
+                }
+            }
         }
+
+        Ok(Expression::Bool(true))
     }))
 }
 
-pub fn lisp_eq() -> Expression {
+/// Applies a recipe to a dish like `bake`, timing every step
+///
+/// Returns a list of the dish and a report with one `name: duration` line
+/// per step, followed by the total. A failing step fails the dish as usual,
+/// and the steps after it are still listed
+///
+pub fn lisp_bake_timed() -> Expression {
     Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
-        ensure_at_least_args(args, 1)?;
+        ensure_exact_args(args, 2)?;
 
-        let mut iter = args.iter();
-        let fst = iter.next().unwrap();
-        Ok(Expression::Bool(iter.all(|x| x == fst)))
+        let recipe = match &args[0] {
+            Expression::List(v) => Ok(v),
+            _ => Err(Error("expected list".to_string())),
+        }?;
+        if !matches!(args[1], Expression::Dish(_)) {
+            return Err(Error("expected Dish".to_string()));
+        }
+
+        let mut lines = Vec::new();
+        let mut total = Duration::ZERO;
+        for expr in recipe {
+            let name = match expr {
+                Expression::Func(_) => "built-in function",
+                Expression::Operation(oi, _) => oi.name,
+                _ => return Err(Error("recipe must be list of functions.".to_string())),
+            };
+
+            let start = Instant::now();
+            call(expr, std::slice::from_ref(&args[1]))?;
+            let elapsed = start.elapsed();
+
+            total += elapsed;
+            lines.push(format!("{}: {:?}", name, elapsed));
+        }
+        lines.push(format!("total: {:?}", total));
+
+        Ok(Expression::List(vec![
+            args[1].clone(),
+            Expression::String(lines.join("\n")),
+        ]))
     }))
 }
 
-pub fn lisp_slurp() -> Expression {
+/// Applies a recipe to every dish in a list, returning the list
+///
+/// `(bake-all recipe dishes :parallel true)` spreads the dishes across
+/// threads when codebake is built with the `parallel` feature, and is the
+/// same as a serial `bake-all` otherwise. Only operations are `Send`, so a
+/// parallel recipe must be made entirely of operations. each thread works
+/// on an owned copy of its dishes, and the results are written back into
+/// the original dishes once every thread is done.
+///
+pub fn lisp_bake_all() -> Expression {
     Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
-        ensure_at_least_args(args, 1)?;
+        ensure_at_least_args(args, 2)?;
 
+        let recipe = match &args[0] {
+            Expression::List(v) => Ok(v),
+            _ => Err(Error("expected list".to_string())),
+        }?;
 
-        let filename = match &args[0] {
-            Expression::String(s) => s,
-            _ => return Err(Error(format!("expected a string. got {}", &args[0]))),
+        let dishes = match &args[1] {
+            Expression::List(v) => v
+                .iter()
+                .map(|d| match d {
+                    Expression::Dish(d) => Ok(d.clone()),
+                    _ => Err(Error(format!("expected list of dishes. got {}", d))),
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+            _ => return Err(Error("expected list of dishes".to_string())),
         };
 
-        let text_mode = match args.get(1) {
-            Some(a) => match a {
-                Expression::Symbol(s) => if s == ":mode" {
-                    ensure_at_least_args(args, 3)?;
-                    if let Expression::String(st) = &args[2] {
-                        match st.as_ref() {
-                            "text" => true,
-                            "bin" => false,
-                            _ => return Err(Error(format!("expected either 'text' or 'bin'. got {}", &args[2]))),
-                        }
-                    } else {
-                        return Err(Error(format!("expected symbol ':mode'. got {}", a)));
-                    }
-                } else { return Err(Error(format!("expected symbol ':mode'. got {}", a))) },
-                _ => return Err(Error(format!("expected symbol ':mode'. got {}", a))),
+        let parallel = match (args.get(2), args.get(3)) {
+            (None, _) => false,
+            (Some(Expression::Symbol(s)), Some(Expression::Bool(b))) if s == ":parallel" => *b,
+            (Some(a), _) => {
+                return Err(Error(format!(
+                    "expected symbol ':parallel' followed by a boolean. got {}",
+                    a
+                )))
             }
-            None => false,
         };
 
-        let bytes = fs::read(filename)
-            .map_err(|e| Error(format!("could not read file '{}'. ({})", filename, e)))?;
+        if parallel {
+            let steps = recipe
+                .iter()
+                .map(|expr| match expr {
+                    Expression::Operation(oi, Some(hargs)) => Ok((*oi, (**hargs).clone())),
+                    // like in `call`, an operation whose arguments all have defaults needs none
+                    Expression::Operation(oi, None)
+                        if oi
+                            .arguments
+                            .iter()
+                            .all(|(n, _)| oi.default_for(n).is_some()) =>
+                    {
+                        Ok((*oi, oi.default_arguments()))
+                    }
+                    _ => Err(Error(format!(
+                        "a parallel recipe must only contain operations. got {}",
+                        expr
+                    ))),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
 
-        let dish = if text_mode {
-            Dish::from_string(String::from_utf8_lossy(&bytes).into_owned())
+            let owned: Vec<Dish> = dishes.iter().map(|d| d.borrow().clone()).collect();
+            for (dish, result) in dishes.iter().zip(bake_owned(&steps, owned)) {
+                *dish.borrow_mut() = result;
+            }
         } else {
-            Dish::from_bytes(bytes)
-        };
+            for dish in &dishes {
+                for func in recipe {
+                    call(func, &[Expression::Dish(dish.clone())])?;
+                }
+            }
+        }
 
-        Ok(Expression::Dish(Rc::new(RefCell::new(dish))))
+        Ok(Expression::List(
+            dishes.into_iter().map(Expression::Dish).collect(),
+        ))
     }))
 }
 
-pub fn lisp_spit() -> Expression {
+/// helper function that applies `steps` to each dish on a pool of scoped threads
+///
+#[cfg(feature = "parallel")]
+fn bake_owned(
+    steps: &[(&'static OperationInfo, OperationArguments)],
+    mut dishes: Vec<Dish>,
+) -> Vec<Dish> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = dishes.len().div_ceil(threads).max(1);
+
+    std::thread::scope(|scope| {
+        for chunk in dishes.chunks_mut(chunk_size) {
+            scope.spawn(move || {
+                for dish in chunk {
+                    for (oi, hargs) in steps {
+                        dish.apply(oi.op, hargs);
+                    }
+                }
+            });
+        }
+    });
+
+    dishes
+}
+
+/// without the `parallel` feature (e.g on wasm, which has no threads) the
+/// owned dishes are just baked one after another
+///
+#[cfg(not(feature = "parallel"))]
+fn bake_owned(
+    steps: &[(&'static OperationInfo, OperationArguments)],
+    mut dishes: Vec<Dish>,
+) -> Vec<Dish> {
+    for dish in &mut dishes {
+        for (oi, hargs) in steps {
+            dish.apply(oi.op, hargs);
+        }
+    }
+
+    dishes
+}
+
+pub fn lisp_clone_dish() -> Expression {
     Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
-        ensure_at_least_args(args, 2)?;
+        ensure_exact_args(args, 1)?;
 
-        let dish = match &args[0] {
-            Expression::Dish(d) => d,
-            _ => return Err(Error(format!("expected a dish. got {}.", &args[0]))),
-        };
+        match &args[0] {
+            Expression::Dish(d) => Ok(Expression::Dish(Rc::new(RefCell::new(
+                d.borrow().deep_clone(),
+            )))),
+            _ => Err(Error("expected Dish".to_string())),
+        }
+    }))
+}
 
-        let filename = match &args[1] {
-            Expression::String(s) => s,
-            _ => return Err(Error(format!("expected a string. got {}", &args[1]))),
-        };
+/// Returns the bytes of a dish as a list of numbers
+///
+pub fn lisp_dish_bytes() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 1)?;
 
-        let inner = &*dish.borrow();
-        let bytes = match inner {
-            Dish::Success(data) => data.as_bytes(),
-            Dish::Failure(err) => err.0.as_bytes(),
+        with_dish_data(&args[0], |data| {
+            let bytes = data.as_bytes().iter();
+            Expression::List(bytes.map(|b| Expression::Number(*b as f64)).collect())
+        })
+    }))
+}
+
+/// Returns the contents of a dish as a string, replacing invalid UTF-8
+///
+pub fn lisp_dish_string() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 1)?;
+
+        with_dish_data(&args[0], |data| {
+            Expression::String(String::from_utf8_lossy(data.as_bytes()).into_owned())
+        })
+    }))
+}
+
+/// Returns the length of a dish in bytes, leaving the dish alone
+///
+pub fn lisp_dish_length() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 1)?;
+
+        with_dish_data(&args[0], |data| {
+            Expression::Number(data.as_bytes().len() as f64)
+        })
+    }))
+}
+
+/// Returns a one-line summary of a dish: whether it failed, what type of
+/// data it holds, its length in bytes, and its shannon entropy. The dish is
+/// left alone
+///
+pub fn lisp_inspect() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 1)?;
+
+        let summary = match &args[0] {
+            Expression::Dish(d) => match &*d.borrow() {
+                Dish::Success(data) => {
+                    let kind = match data {
+                        DishData::Str(_) => "Str",
+                        DishData::Bin(_) => "Bin",
+                    };
+                    let bytes = data.as_bytes();
+                    format!(
+                        "success, {}, {} bytes, entropy {:.3} bits per byte",
+                        kind,
+                        bytes.len(),
+                        shannon_entropy(bytes)
+                    )
+                }
+                Dish::Failure(e) => format!("failure, {}: {}", e.kind(), e.message()),
+            },
+            other => return Err(Error(format!("expected a dish. got {}", other))),
         };
+        Ok(Expression::String(summary))
+    }))
+}
 
-        fs::write(filename, bytes)
-            .map_err(|e| Error(format!("failed to write to file '{}'. ({})", filename, e)))?;
+/// Splits a dish on a delimiter into a list of new dishes
+///
+/// The delimiter can be anything an operation's bytes argument can be (a
+/// string, a dish, or a list of bytes). Pieces of a string dish stay strings
+///
+pub fn lisp_dish_split() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 2)?;
 
-        Ok(Expression::Dish(dish.clone()))
+        let delimiter = parse_bytes(&args[1])?;
+        if delimiter.is_empty() {
+            return Err(Error("cannot split on an empty delimiter".to_string()));
+        }
+
+        with_dish_data(&args[0], |data| {
+            let is_str = matches!(data, DishData::Str(_));
+            let dishes = split_bytes(data.as_bytes(), &delimiter)
+                .into_iter()
+                .map(|piece| bytes_to_dish(piece.to_vec(), is_str))
+                .map(|dish| Expression::Dish(Rc::new(RefCell::new(dish))))
+                .collect();
+            Expression::List(dishes)
+        })
     }))
 }
 
-pub fn lisp_print() -> Expression {
+/// Concatenates a list of dishes into one new dish, with an optional
+/// separator between each of them. The result is a string dish only
+/// if every dish in the list is one
+///
+pub fn lisp_dish_concat() -> Expression {
     Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
         ensure_at_least_args(args, 1)?;
+        if args.len() > 2 {
+            return Err(Error(format!("expected 1 or 2 args. got {}.", args.len())));
+        }
 
-        match &args[0] {
-            Expression::Dish(d) => {
-                let inner = &*d.borrow();
-                match inner {
-                    Dish::Success(data) => println!("{}", String::from_utf8_lossy(data.as_bytes())),
-                    Dish::Failure(err) => println!("{}", String::from_utf8_lossy(err.0.as_bytes())),
-                };
+        let dishes = match &args[0] {
+            Expression::List(l) => l,
+            Expression::Nil => &Vec::new(),
+            _ => {
+                return Err(Error(format!(
+                    "expected a list of dishes. got {}.",
+                    args[0]
+                )))
+            }
+        };
+        let separator = match args.get(1) {
+            Some(expr) => parse_bytes(expr)?,
+            None => Vec::new(),
+        };
+
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut is_str = true;
+        for (i, dish) in dishes.iter().enumerate() {
+            if i > 0 {
+                bytes.extend_from_slice(&separator);
             }
-            _ => println!("{}", &args[0]),
+            with_dish_data(dish, |data| {
+                is_str &= matches!(data, DishData::Str(_));
+                bytes.extend_from_slice(data.as_bytes());
+            })?;
         }
 
-        Ok(Expression::Symbol("nil".to_string()))
+        Ok(Expression::Dish(Rc::new(RefCell::new(bytes_to_dish(
+            bytes, is_str,
+        )))))
     }))
 }
 
-fn parse_list_of_floats(args: &[Expression]) -> Result<Vec<f64>, Error> {
-    args.iter().map(parse_single_float).collect()
+/// Splits a dish into a list of new dishes of `size` bytes each, in order.
+/// The last one is shorter if the dish doesn't divide evenly
+///
+pub fn lisp_chunk_bytes() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 2)?;
+
+        let size = match &args[1] {
+            Expression::Number(n) if n.fract() == 0.0 && *n >= 1.0 => *n as usize,
+            other => {
+                return Err(Error(format!(
+                    "expected a positive integer size. got {}.",
+                    other
+                )))
+            }
+        };
+
+        with_dish_data(&args[0], |data| {
+            let is_str = matches!(data, DishData::Str(_));
+            let dishes = data
+                .as_bytes()
+                .chunks(size)
+                .map(|chunk| bytes_to_dish(chunk.to_vec(), is_str))
+                .map(|dish| Expression::Dish(Rc::new(RefCell::new(dish))))
+                .collect();
+            Expression::List(dishes)
+        })
+    }))
 }
 
-fn parse_single_float(expr: &Expression) -> Result<f64, Error> {
-    match expr {
-        Expression::Number(num) => Ok(*num),
-        _ => Err(Error(format!("expected a number. got '{}'.", expr))),
+/// helper function that splits `bytes` on every occurrence of `delimiter`
+///
+fn split_bytes<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i + delimiter.len() <= bytes.len() {
+        if bytes[i..].starts_with(delimiter) {
+            pieces.push(&bytes[start..i]);
+            i += delimiter.len();
+            start = i;
+        } else {
+            i += 1;
+        }
     }
+    pieces.push(&bytes[start..]);
+    pieces
 }
 
-fn ensure_exact_args(args: &[Expression], n: usize) -> LispResult {
-    if args.len() != n {
-        return Err(Error(format!(
-            "expected exactly {} args. got {}.",
-            n,
-            args.len()
-        )));
+/// helper function that makes a string dish out of `bytes` when `is_str`
+/// is set and they're valid UTF-8, and a binary dish otherwise
+///
+fn bytes_to_dish(bytes: Vec<u8>, is_str: bool) -> Dish {
+    if !is_str {
+        return Dish::from_bytes(bytes);
     }
+    match String::from_utf8(bytes) {
+        Ok(s) => Dish::from_string(s),
+        Err(e) => Dish::from_bytes(e.into_bytes()),
+    }
+}
 
-    Ok(Expression::Bool(true))
+/// helper function that calls `f` with the data of a successful dish
+///
+fn with_dish_data<T>(expr: &Expression, f: impl FnOnce(&DishData) -> T) -> Result<T, Error> {
+    match expr {
+        Expression::Dish(d) => match &*d.borrow() {
+            Dish::Success(data) => Ok(f(data)),
+            Dish::Failure(e) => Err(Error(format!("dish failed: {}", e))),
+        },
+        _ => Err(Error(format!("expected a dish. got {}.", expr))),
+    }
 }
 
-fn ensure_at_least_args(args: &[Expression], n: usize) -> LispResult {
-    if args.len() < n {
-        return Err(Error(format!(
-            "expected at least {} args. got {}.",
-            n,
-            args.len()
-        )));
+/// Checks that `f` can be given to `run_on_dish`
+///
+/// Builtins like `tee` don't get the environment that a `fn` lambda needs to run,
+/// so only builtin functions, operations, and recipes of them are accepted
+///
+fn ensure_dish_function(name: &str, f: &Expression) -> Result<(), Error> {
+    let steps = match f {
+        Expression::List(steps) => steps.as_slice(),
+        _ => std::slice::from_ref(f),
+    };
+    let unsupported = steps
+        .iter()
+        .find(|step| !matches!(step, Expression::Func(_) | Expression::Operation(..)));
+    match unsupported {
+        None => Ok(()),
+        Some(Expression::Lambda(_)) => Err(Error(format!(
+            "'{}' can't run `fn` lambdas. give it an operation, builtin, or recipe instead",
+            name
+        ))),
+        Some(other) => Err(Error(format!(
+            "'{}' expected an operation, builtin, or recipe. got '{}'.",
+            name, other
+        ))),
     }
+}
 
-    Ok(Expression::Bool(true))
+/// Runs `dish` through a single function or operation, or through every
+/// step of a recipe when given a list. `f` should pass `ensure_dish_function`
+///
+fn run_on_dish(f: &Expression, dish: &Expression) -> LispResult {
+    match f {
+        Expression::List(steps) => {
+            for step in steps {
+                call(step, std::slice::from_ref(dish))?;
+            }
+            Ok(dish.clone())
+        }
+        _ => call(f, std::slice::from_ref(dish)),
+    }
+}
+
+/// helper function that combines several errors into one, which has the kind of the first.
+/// `errors` must not be empty
+///
+fn join_errors(errors: &[DishError]) -> DishError {
+    let messages: Vec<&str> = errors.iter().map(|e| e.message()).collect();
+    DishError::new(errors[0].kind(), messages.join("; "))
+}
+
+pub fn lisp_tee() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 2)?;
+
+        let dish = match &args[0] {
+            Expression::Dish(d) => Ok(d),
+            _ => Err(Error("expected Dish".to_string())),
+        }?;
+
+        for f in &args[1..] {
+            ensure_dish_function("tee", f)?;
+        }
+
+        // every branch gets its own copy, so they can't see each other's changes
+        let mut results = Vec::new();
+        for f in &args[1..] {
+            let copy = Expression::Dish(Rc::new(RefCell::new(dish.borrow().deep_clone())));
+            results.push(run_on_dish(f, &copy)?);
+        }
+
+        Ok(Expression::List(results))
+    }))
+}
+
+/// Applies a function or recipe to each line of a string dish
+///
+/// `(map-lines f dish)` fails the result if any line fails, naming each failed line.
+/// `(map-lines f dish true)` passes failed lines through unchanged instead, including
+/// lines where `f` errors. `f` is an operation, builtin, or recipe, not a `fn` lambda
+///
+pub fn lisp_map_lines() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 2)?;
+        ensure_dish_function("map-lines", &args[0])?;
+
+        let text = match &args[1] {
+            Expression::Dish(d) => match &*d.borrow() {
+                Dish::Success(DishData::Str(s)) => Ok(s.clone()),
+                Dish::Success(DishData::Bin(_)) => Err(Error("expected a string dish".to_string())),
+                Dish::Failure(e) => Err(Error(format!("cannot map over a failed dish. ({})", e))),
+            },
+            _ => Err(Error("expected Dish".to_string())),
+        }?;
+
+        let passthrough = match args.get(2) {
+            None => false,
+            Some(Expression::Bool(b)) => *b,
+            Some(other) => return Err(Error(format!("expected a boolean. got {}", other))),
+        };
+
+        let mut lines: Vec<DishData> = Vec::new();
+        let mut errors = Vec::new();
+        for (i, line) in text.split('\n').enumerate() {
+            let dish = Rc::new(RefCell::new(Dish::from_string(line.to_string())));
+            let outcome = match run_on_dish(&args[0], &Expression::Dish(dish.clone())) {
+                Ok(_) => dish.borrow().deep_clone(),
+                Err(e) if passthrough => Dish::Failure(DishError::invalid_argument(e.0)),
+                Err(e) => return Err(e),
+            };
+
+            match outcome {
+                Dish::Success(data) => lines.push(data),
+                Dish::Failure(e) => {
+                    let message = format!("line {}: {}", i + 1, e.message());
+                    errors.push(DishError::new(e.kind(), message));
+                    lines.push(DishData::Str(line.to_string()));
+                }
+            }
+        }
+
+        let result = if !errors.is_empty() && !passthrough {
+            Dish::Failure(join_errors(&errors))
+        } else if let Some(strs) = lines
+            .iter()
+            .map(|l| match l {
+                DishData::Str(s) => Some(s.as_str()),
+                DishData::Bin(_) => None,
+            })
+            .collect::<Option<Vec<&str>>>()
+        {
+            Dish::from_string(strs.join("\n"))
+        } else {
+            // if any line became binary, the whole result has to be
+            Dish::from_bytes(
+                lines
+                    .iter()
+                    .map(|l| l.as_bytes())
+                    .collect::<Vec<_>>()
+                    .join(&b'\n'),
+            )
+        };
+
+        Ok(Expression::Dish(Rc::new(RefCell::new(result))))
+    }))
+}
+
+/// Applies a function or operation to a dish only if the dish matches a regex
+///
+/// `(when-match "pattern" operation dish)` returns the dish, changed or not. like
+/// `tee` and `map-lines`, it doesn't take `fn` lambdas
+///
+pub fn lisp_when_match() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 3)?;
+
+        let pattern = match &args[0] {
+            Expression::String(s) => Ok(s),
+            _ => Err(Error(format!(
+                "expected a string pattern. got {}",
+                &args[0]
+            ))),
+        }?;
+        let re = regex_helper(pattern).map_err(|e| Error(e.message().to_string()))?;
+        ensure_dish_function("when-match", &args[1])?;
+
+        let matches = match &args[2] {
+            Expression::Dish(d) => match &*d.borrow() {
+                Dish::Success(data) => re.is_match(&String::from_utf8_lossy(data.as_bytes())),
+                Dish::Failure(_) => false,
+            },
+            _ => return Err(Error("expected Dish".to_string())),
+        };
+
+        if matches {
+            run_on_dish(&args[1], &args[2])
+        } else {
+            Ok(args[2].clone())
+        }
+    }))
+}
+
+pub fn lisp_empty() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 1)?;
+
+        Ok(match &args[0] {
+            Expression::Nil => Expression::Bool(true),
+            Expression::List(v) => Expression::Bool(v.is_empty()),
+            Expression::String(s) => Expression::Bool(s.is_empty()),
+            Expression::Dish(d) => match &*d.borrow() {
+                Dish::Success(data) => Expression::Bool(data.as_bytes().len() == 0),
+                _ => Expression::Bool(false),
+            },
+            _ => Expression::Nil,
+        })
+    }))
+}
+
+pub fn lisp_cons() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 2)?;
+
+        match args[1].clone() {
+            Expression::List(mut l) => {
+                l.insert(0, args[0].clone());
+                Ok(Expression::List(l))
+            }
+            Expression::Nil => Ok(Expression::List(vec![args[0].clone()])),
+            _ => Err(Error("expected 2nd argument to be a list.".to_string())),
+        }
+    }))
+}
+
+pub fn lisp_dish_push_byte() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 2)?;
+
+        insert_dish_byte(&args[0], &args[1], |v, b| v.push(b))
+    }))
+}
+
+pub fn lisp_dish_unshift_byte() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 2)?;
+
+        insert_dish_byte(&args[0], &args[1], |v, b| v.insert(0, b))
+    }))
+}
+
+/// helper for `dish-push-byte` and `dish-unshift-byte`
+/// validates the byte and mutates the dish in place with `insert`.
+/// string dishes stay strings as long as the result is still valid utf8
+///
+fn insert_dish_byte(
+    dish_expr: &Expression,
+    byte_expr: &Expression,
+    insert: fn(&mut Vec<u8>, u8),
+) -> LispResult {
+    let dish = match dish_expr {
+        Expression::Dish(d) => d,
+        _ => return Err(Error(format!("expected a dish. got {}.", dish_expr))),
+    };
+    let byte = parse_byte(byte_expr)?;
+
+    match &mut *dish.borrow_mut() {
+        Dish::Success(DishData::Bin(v)) => insert(v, byte),
+        Dish::Success(data) => {
+            let mut v = data.as_bytes().to_vec();
+            insert(&mut v, byte);
+            *data = match String::from_utf8(v) {
+                Ok(s) => DishData::Str(s),
+                Err(e) => DishData::Bin(e.into_bytes()),
+            };
+        }
+        Dish::Failure(e) => return Err(Error(format!("cannot modify a failed dish. ({})", e))),
+    }
+
+    Ok(Expression::Dish(dish.clone()))
+}
+
+pub fn lisp_eq() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 1)?;
+
+        let mut iter = args.iter();
+        let fst = iter.next().unwrap();
+        Ok(Expression::Bool(iter.all(|x| x == fst)))
+    }))
+}
+
+pub fn lisp_slurp(limits: Rc<EvalLimits>) -> Expression {
+    Expression::Func(Rc::new(move |args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 1)?;
+
+
+        let filename = match &args[0] {
+            Expression::String(s) => s,
+            _ => return Err(Error(format!("expected a string. got {}", &args[0]))),
+        };
+
+        let text_mode = match args.get(1) {
+            Some(a) => match a {
+                Expression::Symbol(s) => if s == ":mode" {
+                    ensure_at_least_args(args, 3)?;
+                    if let Expression::String(st) = &args[2] {
+                        match st.as_ref() {
+                            "text" => true,
+                            "bin" => false,
+                            _ => return Err(Error(format!("expected either 'text' or 'bin'. got {}", &args[2]))),
+                        }
+                    } else {
+                        return Err(Error(format!("expected symbol ':mode'. got {}", a)));
+                    }
+                } else { return Err(Error(format!("expected symbol ':mode'. got {}", a))) },
+                _ => return Err(Error(format!("expected symbol ':mode'. got {}", a))),
+            }
+            None => false,
+        };
+
+        let read_err =
+            |e: std::io::Error| Error(format!("could not read file '{}'. ({})", filename, e));
+        let bytes = match limits.max_file_mem.get() {
+            // the size from the file's metadata can't be trusted (`/dev/zero`, fifos,
+            // `/proc`), so one byte past the limit is read to see if it's over
+            Some(max) => {
+                let mut bytes = Vec::new();
+                fs::File::open(filename)
+                    .and_then(|f| f.take(max.saturating_add(1)).read_to_end(&mut bytes))
+                    .map_err(read_err)?;
+                if bytes.len() as u64 > max {
+                    return Err(Error(format!(
+                        "file '{}' is more than the limit of {} bytes",
+                        filename, max
+                    )));
+                }
+                bytes
+            }
+            None => fs::read(filename).map_err(read_err)?,
+        };
+
+        let dish = if text_mode {
+            Dish::from_string(String::from_utf8_lossy(&bytes).into_owned())
+        } else {
+            Dish::from_bytes(bytes)
+        };
+
+        Ok(Expression::Dish(Rc::new(RefCell::new(dish))))
+    }))
+}
+
+pub fn lisp_spit() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 2)?;
+
+        let dish = match &args[0] {
+            Expression::Dish(d) => d,
+            _ => return Err(Error(format!("expected a dish. got {}.", &args[0]))),
+        };
+
+        let filename = match &args[1] {
+            Expression::String(s) => s,
+            _ => return Err(Error(format!("expected a string. got {}", &args[1]))),
+        };
+
+        let inner = &*dish.borrow();
+        let bytes = match inner {
+            Dish::Success(data) => data.as_bytes(),
+            Dish::Failure(err) => err.message().as_bytes(),
+        };
+
+        fs::write(filename, bytes)
+            .map_err(|e| Error(format!("failed to write to file '{}'. ({})", filename, e)))?;
+
+        Ok(Expression::Dish(dish.clone()))
+    }))
+}
+
+/// Runs an external command with the dish on its stdin, returning a new
+/// dish of whatever it wrote to stdout
+///
+/// `(pipe dish "rev")`, or `(pipe dish "tr" '("a-z" "A-Z"))` with arguments.
+/// This runs anything the script asks for with the interpreter's own
+/// permissions, so it's only built in with the `pipe` feature. Never enable
+/// it somewhere untrusted scripts are evaluated
+///
+#[cfg(feature = "pipe")]
+pub fn lisp_pipe() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        ensure_at_least_args(args, 2)?;
+
+        let input = match &args[0] {
+            Expression::Dish(d) => match &*d.borrow() {
+                Dish::Success(data) => data.as_bytes().to_vec(),
+                Dish::Failure(e) => return Err(Error(format!("cannot pipe a failed dish. {}", e))),
+            },
+            _ => return Err(Error(format!("expected a dish. got {}.", &args[0]))),
+        };
+
+        let cmd = match &args[1] {
+            Expression::String(s) => s,
+            _ => return Err(Error(format!("expected a string. got {}", &args[1]))),
+        };
+
+        let mut cmd_args = Vec::new();
+        match args.get(2) {
+            None | Some(Expression::Nil) => {}
+            Some(Expression::List(l)) => {
+                for arg in l {
+                    match arg {
+                        Expression::String(s) => cmd_args.push(s.clone()),
+                        _ => return Err(Error(format!("expected a string argument. got {}", arg))),
+                    }
+                }
+            }
+            Some(other) => {
+                return Err(Error(format!(
+                    "expected a list of arguments. got {}",
+                    other
+                )))
+            }
+        }
+
+        let mut child = Command::new(cmd)
+            .args(&cmd_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error(format!("failed to run '{}'. ({})", cmd, e)))?;
+
+        // stdin is written from another thread so a command that writes a lot
+        // before reading everything can't deadlock against us
+        let mut stdin = child.stdin.take().unwrap();
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let output = child
+            .wait_with_output()
+            .map_err(|e| Error(format!("failed to run '{}'. ({})", cmd, e)))?;
+        // a command that exits without reading all of stdin is fine
+        let _ = writer.join();
+
+        if !output.status.success() {
+            let code = match output.status.code() {
+                Some(code) => code.to_string(),
+                None => "none".to_string(),
+            };
+            return Err(Error(format!(
+                "'{}' failed with exit code {}. {}",
+                cmd,
+                code,
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            )));
+        }
+
+        // most commands print text, so output that's valid utf-8 is a string dish
+        let dish = bytes_to_dish(output.stdout, true);
+        Ok(Expression::Dish(Rc::new(RefCell::new(dish))))
+    }))
+}
+
+/// Gets the value of an environment variable, or nil if it isn't set
+///
+pub fn lisp_getenv() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 1)?;
+
+        let name = match &args[0] {
+            Expression::String(s) => s,
+            _ => return Err(Error(format!("expected a string. got {}", &args[0]))),
+        };
+
+        // a variable that isn't valid unicode can't be a lisp string, so it's nil too
+        match std::env::var(name) {
+            Ok(value) => Ok(Expression::String(value)),
+            Err(_) => Ok(Expression::Nil),
+        }
+    }))
+}
+
+/// Gets the arguments codebake was started with as a list of strings,
+/// starting with the program name
+///
+pub fn lisp_argv() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 0)?;
+        Ok(Expression::List(
+            std::env::args_os()
+                .map(|arg| Expression::String(arg.to_string_lossy().into_owned()))
+                .collect(),
+        ))
+    }))
+}
+
+/// Reads all of stdin into a new dish, a string one if it's valid UTF-8
+///
+/// With the REPL, this is everything after the line that called it. Only
+/// native builds have a stdin to read
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub fn lisp_read_stdin() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        use std::io::Read;
+
+        ensure_exact_args(args, 0)?;
+
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error(format!("could not read stdin. ({})", e)))?;
+
+        let dish = bytes_to_dish(bytes, true);
+        Ok(Expression::Dish(Rc::new(RefCell::new(dish))))
+    }))
+}
+
+/// Reads one line of stdin as a string without its line ending, or nil
+/// once stdin is closed
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub fn lisp_read_line() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 0)?;
+
+        let mut line = String::new();
+        let read = std::io::stdin()
+            .read_line(&mut line)
+            .map_err(|e| Error(format!("could not read stdin. ({})", e)))?;
+        if read == 0 {
+            return Ok(Expression::Nil);
+        }
+
+        let len = line.trim_end_matches(['\r', '\n']).len();
+        line.truncate(len);
+        Ok(Expression::String(line))
+    }))
+}
+
+/// diffs larger than this many lines are cut off
+const DIFF_MAX_LINES: usize = 1000;
+
+/// line diffs whose LCS table would be bigger than this are only summarized
+const DIFF_MAX_CELLS: usize = 16_000_000;
+
+/// lines of unchanged context shown around each hunk
+const DIFF_CONTEXT: usize = 3;
+
+pub fn lisp_diff() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 2)?;
+
+        let mut data = Vec::new();
+        for arg in args {
+            match arg {
+                Expression::Dish(d) => match &*d.borrow() {
+                    Dish::Success(dd) => data.push(dd.clone()),
+                    Dish::Failure(e) => {
+                        return Err(Error(format!("cannot diff a failed dish. ({})", e)))
+                    }
+                },
+                _ => return Err(Error(format!("expected Dish. got {}", arg))),
+            }
+        }
+
+        let out = match (&data[0], &data[1]) {
+            (DishData::Str(a), DishData::Str(b)) => line_diff(a, b),
+            (a, b) => byte_diff(a.as_bytes(), b.as_bytes()),
+        };
+
+        Ok(Expression::Dish(Rc::new(RefCell::new(Dish::from_string(
+            out,
+        )))))
+    }))
+}
+
+/// helper function that produces a unified diff of two strings, line by line
+/// an empty string means the inputs are the same
+///
+fn line_diff(a: &str, b: &str) -> String {
+    let a: Vec<&str> = a.lines().collect();
+    let b: Vec<&str> = b.lines().collect();
+
+    // only the middle, after any common prefix and suffix, needs the LCS table
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    if a_mid.is_empty() && b_mid.is_empty() {
+        return String::new();
+    }
+    if (a_mid.len() + 1) * (b_mid.len() + 1) > DIFF_MAX_CELLS {
+        return format!(
+            "inputs are too large to diff: lines {} to {} differ ({} lines vs {} lines)",
+            prefix + 1,
+            prefix + a_mid.len().max(b_mid.len()),
+            a.len(),
+            b.len()
+        );
+    }
+
+    // lcs[i][j] is the length of the longest common subsequence of a_mid[i..] and b_mid[j..]
+    let width = b_mid.len() + 1;
+    let mut lcs = vec![0u32; (a_mid.len() + 1) * width];
+    for i in (0..a_mid.len()).rev() {
+        for j in (0..b_mid.len()).rev() {
+            lcs[i * width + j] = if a_mid[i] == b_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    // the full edit script as (tag, line index in a, line index in b)
+    let mut edits: Vec<(char, usize, usize)> = (0..prefix).map(|i| (' ', i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a_mid.len() || j < b_mid.len() {
+        if i < a_mid.len() && j < b_mid.len() && a_mid[i] == b_mid[j] {
+            edits.push((' ', prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if i < a_mid.len()
+            && (j == b_mid.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            // deletions come before insertions, like `diff -u`
+            edits.push(('-', prefix + i, prefix + j));
+            i += 1;
+        } else {
+            edits.push(('+', prefix + i, prefix + j));
+            j += 1;
+        }
+    }
+    for k in 0..suffix {
+        edits.push((' ', a.len() - suffix + k, b.len() - suffix + k));
+    }
+
+    // group the changes into hunks, merging ones whose context would overlap
+    let changes: Vec<usize> = (0..edits.len()).filter(|k| edits[*k].0 != ' ').collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for k in changes {
+        let start = k.saturating_sub(DIFF_CONTEXT);
+        let end = (k + DIFF_CONTEXT + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut lines = vec!["--- a".to_string(), "+++ b".to_string()];
+    for (start, end) in hunks {
+        let hunk = &edits[start..end];
+        let a_count = hunk.iter().filter(|e| e.0 != '+').count();
+        let b_count = hunk.iter().filter(|e| e.0 != '-').count();
+        // like `diff -u`, an empty range starts at the line before it
+        let a_start = if a_count == 0 {
+            hunk[0].1
+        } else {
+            hunk[0].1 + 1
+        };
+        let b_start = if b_count == 0 {
+            hunk[0].2
+        } else {
+            hunk[0].2 + 1
+        };
+        lines.push(format!(
+            "@@ -{},{} +{},{} @@",
+            a_start, a_count, b_start, b_count
+        ));
+        for (tag, ai, bi) in hunk {
+            let line = if *tag == '+' { b[*bi] } else { a[*ai] };
+            lines.push(format!("{}{}", tag, line));
+        }
+    }
+
+    truncate_diff_lines(lines)
+}
+
+/// helper function that lists the offsets where two byte strings differ
+///
+fn byte_diff(a: &[u8], b: &[u8]) -> String {
+    let mut lines = Vec::new();
+    if a.len() != b.len() {
+        lines.push(format!("length: {} -> {}", a.len(), b.len()));
+    }
+
+    // a byte past the end of the shorter input is shown as `--`
+    let byte = |data: &[u8], i: usize| match data.get(i) {
+        Some(b) => format!("{:02x}", b),
+        None => "--".to_string(),
+    };
+    for i in 0..a.len().max(b.len()) {
+        if a.get(i) != b.get(i) {
+            lines.push(format!("{:08x}: {} -> {}", i, byte(a, i), byte(b, i)));
+        }
+    }
+
+    truncate_diff_lines(lines)
+}
+
+/// helper function that joins diff output, cutting it off at `DIFF_MAX_LINES`
+///
+fn truncate_diff_lines(mut lines: Vec<String>) -> String {
+    if lines.len() > DIFF_MAX_LINES {
+        let rest = lines.len() - DIFF_MAX_LINES;
+        lines.truncate(DIFF_MAX_LINES);
+        lines.push(format!("... {} more lines", rest));
+    }
+    lines.join("\n")
+}
+
+/// Parses a string into an expression without evaluating it
+///
+pub fn lisp_read() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_exact_args(args, 1)?;
+
+        match &args[0] {
+            Expression::String(s) => default_reader().parse(s),
+            _ => Err(Error("expected String".to_string())),
+        }
+    }))
+}
+
+pub fn lisp_print() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 1)?;
+
+        println!("{}", display_text(&args[0]));
+        Ok(Expression::Nil)
+    }))
+}
+
+/// Builds a string from a template and arguments, like `(format "~a is ~s" x y)`.
+///
+/// Each directive in the template is replaced by the next argument:
+///   * `~a` - the argument as text, so a string has no quotes and a dish gives its data
+///   * `~s` - the argument as it would be written in the lisp, so strings are quoted
+///   * `~~` - a literal `~`, which doesn't use up an argument
+///
+pub fn lisp_format() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 1)?;
+        Ok(Expression::String(format_template(&args[0], &args[1..])?))
+    }))
+}
+
+/// Formats its arguments like `format`, and prints the result on its own line
+///
+pub fn lisp_println() -> Expression {
+    Expression::Func(Rc::new(|args: &[Expression]| -> LispResult {
+        ensure_at_least_args(args, 1)?;
+
+        println!("{}", format_template(&args[0], &args[1..])?);
+        Ok(Expression::Nil)
+    }))
+}
+
+/// helper function that fills in the directives of a `format` template with `args`
+///
+fn format_template(template: &Expression, args: &[Expression]) -> Result<String, Error> {
+    let template = match template {
+        Expression::String(s) => s,
+        other => return Err(Error(format!("expected a string template. got {}", other))),
+    };
+
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+
+        let directive = chars.next();
+        if directive == Some('~') {
+            out.push('~');
+            continue;
+        }
+        let quoted = match directive {
+            Some('a') => false,
+            Some('s') => true,
+            Some(other) => return Err(Error(format!("unknown format directive `~{}`", other))),
+            None => return Err(Error("format template ends with a lone `~`".to_string())),
+        };
+        let arg = args
+            .next()
+            .ok_or_else(|| Error("not enough arguments for the format template".to_string()))?;
+        if quoted {
+            out.push_str(&display_quoted(arg));
+        } else {
+            out.push_str(&display_text(arg));
+        }
+    }
+
+    if args.next().is_some() {
+        return Err(Error(
+            "too many arguments for the format template".to_string(),
+        ));
+    }
+    Ok(out)
+}
+
+/// helper function that shows an expression as plain text. strings aren't
+/// quoted, and a dish is shown as its data (or its error, if it failed)
+///
+fn display_text(expr: &Expression) -> String {
+    match expr {
+        Expression::Dish(d) => match &*d.borrow() {
+            Dish::Success(data) => String::from_utf8_lossy(data.as_bytes()).into_owned(),
+            Dish::Failure(err) => err.message().to_string(),
+        },
+        _ => format!("{}", expr),
+    }
+}
+
+/// helper function that shows an expression the way it's written in the lisp,
+/// so strings are quoted and escaped
+///
+fn display_quoted(expr: &Expression) -> String {
+    match expr {
+        Expression::String(s) => {
+            let mut out = String::from("\"");
+            for c in s.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\t' => out.push_str("\\t"),
+                    '\r' => out.push_str("\\r"),
+                    c if c.is_control() && (c as u32) < 0x100 => {
+                        out.push_str(&format!("\\x{:02x}", c as u32))
+                    }
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+            out
+        }
+        Expression::Dish(d) => d.borrow().display_full(),
+        _ => format!("{}", expr),
+    }
+}
+
+fn parse_list_of_floats(args: &[Expression]) -> Result<Vec<f64>, Error> {
+    args.iter().map(parse_single_float).collect()
+}
+
+fn parse_single_float(expr: &Expression) -> Result<f64, Error> {
+    match expr {
+        Expression::Number(num) => Ok(*num),
+        _ => Err(Error(format!("expected a number. got '{}'.", expr))),
+    }
+}
+
+/// helper function that reads bytes the same way as an operation's bytes argument
+///
+fn parse_bytes(expr: &Expression) -> Result<Vec<u8>, Error> {
+    match parse_arg(&OperationArgType::Bytes, expr)? {
+        OperationArg::Bytes(b) => Ok(b),
+        _ => unreachable!("bytes arguments are always parsed to bytes"),
+    }
+}
+
+fn parse_byte(expr: &Expression) -> Result<u8, Error> {
+    match expr {
+        Expression::Number(n) if n.fract() == 0.0 && (0.0..=255.0).contains(n) => Ok(*n as u8),
+        _ => Err(Error(format!("expected a byte (0-255). got '{}'.", expr))),
+    }
+}
+
+fn ensure_exact_args(args: &[Expression], n: usize) -> LispResult {
+    if args.len() != n {
+        return Err(Error(format!(
+            "expected exactly {} args. got {}.",
+            n,
+            args.len()
+        )));
+    }
+
+    Ok(Expression::Bool(true))
+}
+
+fn ensure_at_least_args(args: &[Expression], n: usize) -> LispResult {
+    if args.len() < n {
+        return Err(Error(format!(
+            "expected at least {} args. got {}.",
+            n,
+            args.len()
+        )));
+    }
+
+    Ok(Expression::Bool(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lisp::functions::{byte_diff, DIFF_MAX_LINES};
+    use crate::lisp::{default_env, parse_eval, Environment, Expression, Reader};
+    use crate::{Dish, DishData};
+
+    fn eval_dish(src: &str) -> DishData {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        eval_dish_in(&reader, &mut env, src)
+    }
+
+    fn eval_dish_in(reader: &Reader, env: &mut Environment, src: &str) -> DishData {
+        match parse_eval(reader, env, &src.to_string()) {
+            Ok(Expression::Dish(d)) => match &*d.borrow() {
+                Dish::Success(data) => data.clone(),
+                Dish::Failure(e) => panic!("dish failed: {}", e),
+            },
+            Ok(other) => panic!("expected a dish. got {}", other),
+            Err(e) => panic!("eval failed: {}", e),
+        }
+    }
+
+    fn eval_str(src: &str) -> String {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        eval_str_in(&reader, &mut env, src)
+    }
+
+    fn eval_str_in(reader: &Reader, env: &mut Environment, src: &str) -> String {
+        match parse_eval(reader, env, &src.to_string()) {
+            Ok(res) => format!("{}", res),
+            Err(e) => panic!("eval failed: {}", e),
+        }
+    }
+
+    fn eval_error(src: &str) -> String {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        eval_error_in(&reader, &mut env, src)
+    }
+
+    fn eval_error_in(reader: &Reader, env: &mut Environment, src: &str) -> String {
+        match parse_eval(reader, env, &src.to_string()) {
+            Ok(res) => panic!("expected an error. got {}", res),
+            Err(e) => format!("{}", e),
+        }
+    }
+
+    #[test]
+    fn test_dish_push_byte() {
+        let data = eval_dish("(dish-push-byte (dish-push-byte d[] 104) 105)");
+        assert_eq!(data, DishData::Bin(vec![104, 105]));
+
+        let data = eval_dish("(dish-push-byte d\"\" 255)");
+        assert_eq!(data, DishData::Bin(vec![255]));
+
+        let data = eval_dish("(dish-push-byte d\"h\" 105)");
+        assert_eq!(data, DishData::Str("hi".to_string()));
+    }
+
+    #[test]
+    fn test_dish_unshift_byte() {
+        let data = eval_dish("(dish-unshift-byte (dish-unshift-byte d[] 105) 104)");
+        assert_eq!(data, DishData::Bin(vec![104, 105]));
+
+        let data = eval_dish("(dish-unshift-byte d\"\" 104)");
+        assert_eq!(data, DishData::Str("h".to_string()));
+    }
+
+    #[test]
+    fn test_dish_push_byte_range() {
+        for src in [
+            "(dish-push-byte d[] 256)",
+            "(dish-push-byte d[] -1)",
+            "(dish-unshift-byte d[] 1.5)",
+        ] {
+            eval_error(src);
+        }
+    }
+
+    fn eval_failure(src: &str) -> String {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        match parse_eval(&reader, &mut env, &src.to_string()) {
+            Ok(Expression::Dish(d)) => match &*d.borrow() {
+                Dish::Failure(e) => e.to_string(),
+                Dish::Success(data) => panic!("expected a failed dish. got {:?}", data),
+            },
+            Ok(other) => panic!("expected a dish. got {}", other),
+            Err(e) => panic!("eval failed: {}", e),
+        }
+    }
+
+    // `from-roman` fails on the reversed input, but `reverse` and `rot13` don't
+    static FAILING_RECIPE: &str = "(recipe reverse from-roman (rot13 13))";
+
+    #[test]
+    fn test_bake_error_policy_stop() {
+        let src = format!("(bake {} d\"hello\")", FAILING_RECIPE);
+        assert!(eval_failure(&src).contains("roman"));
+
+        let src = format!("(bake {} d\"hello\" :on-error \"stop\")", FAILING_RECIPE);
+        assert!(eval_failure(&src).contains("roman"));
+    }
+
+    #[test]
+    fn test_bake_error_policy_skip() {
+        let src = format!("(bake {} d\"hello\" :on-error \"skip\")", FAILING_RECIPE);
+        assert_eq!(eval_dish(&src), DishData::Str("byyru".to_string()));
+    }
+
+    #[test]
+    fn test_bake_error_policy_collect() {
+        let src = "(bake (recipe from-roman reverse from-roman) d\"hello\" :on-error \"collect\")";
+        let err = eval_failure(src);
+        assert!(err.contains("step 1:"));
+        assert!(err.contains("step 3:"));
+        assert!(!err.contains("step 2:"));
+
+        // nothing to report if every step succeeds
+        let src = "(bake (recipe reverse (rot13 13)) d\"hello\" :on-error \"collect\")";
+        assert_eq!(eval_dish(src), DishData::Str("byyru".to_string()));
+    }
+
+    #[test]
+    fn test_bake_error_policy_func() {
+        // `first` errors on a dish instead of failing it
+        static FUNC_RECIPE: &str = "(recipe reverse first (rot13 13))";
+
+        let src = format!("(bake {} d\"hello\" :on-error \"skip\")", FUNC_RECIPE);
+        assert_eq!(eval_dish(&src), DishData::Str("byyru".to_string()));
+
+        let src = format!("(bake {} d\"hello\" :on-error \"collect\")", FUNC_RECIPE);
+        let err = eval_failure(&src);
+        assert!(err.contains("step 2:"));
+        assert!(!err.contains("step 1:"));
+        assert!(!err.contains("step 3:"));
+    }
+
+    #[test]
+    fn test_bake_error_policy_failed_input() {
+        // a dish that has already failed keeps its one error under every policy
+        let failed = "(bake (recipe from-roman) d\"hello\")";
+        for policy in ["stop", "skip", "collect"] {
+            let src = format!(
+                "(bake (recipe reverse (rot13 13)) {} :on-error \"{}\")",
+                failed, policy
+            );
+            assert_eq!(eval_failure(&src), eval_failure(failed));
+        }
+    }
+
+    #[test]
+    fn test_bake_error_policy_invalid() {
+        eval_error("(bake (recipe reverse) d\"hi\" :on-error \"ignore\")");
+    }
+
+    fn eval_list(src: &str) -> Vec<DishData> {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        match parse_eval(&reader, &mut env, &src.to_string()) {
+            Ok(Expression::List(l)) => l
+                .iter()
+                .map(|e| match e {
+                    Expression::Dish(d) => match &*d.borrow() {
+                        Dish::Success(data) => data.clone(),
+                        Dish::Failure(e) => panic!("dish failed: {}", e),
+                    },
+                    other => panic!("expected a dish. got {}", other),
+                })
+                .collect(),
+            Ok(other) => panic!("expected a list. got {}", other),
+            Err(e) => panic!("eval failed: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_bake_all_serial_matches_parallel() {
+        let dishes: Vec<String> = (0..64).map(|i| format!("d\"dish number {}\"", i)).collect();
+        let src = format!(
+            "(bake-all (recipe (rot13 13) reverse to-base64) (quote ({})))",
+            dishes.join(" ")
+        );
+        let serial = eval_list(&src);
+        let parallel = eval_list(&format!("{} :parallel true)", &src[..src.len() - 1]));
+
+        assert_eq!(serial.len(), 64);
+        assert_eq!(serial, parallel);
+        let single = eval_dish("(bake (recipe (rot13 13) reverse to-base64) d\"dish number 0\")");
+        assert_eq!(serial[0], single);
+    }
+
+    #[test]
+    fn test_bake_all_parallel_requires_operations() {
+        // `print` is a builtin function rather than an operation
+        eval_error("(bake-all (recipe print) (quote (d\"hi\")) :parallel true)");
+    }
+
+    #[test]
+    fn test_operation_default_args() {
+        let data = eval_dish("((extract \"ip\") d\"1.2.3.4 and 1.2.3.4\")");
+        assert_eq!(data, DishData::Str("1.2.3.4".to_string()));
+
+        let data = eval_dish("((extract \"ip\" 0) d\"1.2.3.4 and 1.2.3.4\")");
+        assert_eq!(data, DishData::Str("1.2.3.4\n1.2.3.4".to_string()));
+
+        for src in ["(extract)", "(extract \"ip\" 0 1)", "(extract \"mac\")"] {
+            eval_error(src);
+        }
+    }
+
+    #[test]
+    fn test_choice_arguments() {
+        let data = eval_dish("((to-table \",\" \"markdown\" 0) d\"1\")");
+        assert_eq!(data, DishData::Str("|     |\n|-----|\n| 1   |".to_string()));
+
+        let err = eval_error("((to-table \",\" \"html\" 0) d\"1\")");
+        assert_eq!(err, "expected one of ascii, markdown. got html.");
+    }
+
+    #[test]
+    fn test_diff_strings() {
+        let data = eval_dish("(diff d\"a\nb\nc\nd\" d\"a\nc\nd\ne\")");
+        let expected = "--- a\n+++ b\n@@ -1,4 +1,4 @@\n a\n-b\n c\n d\n+e";
+        assert_eq!(data, DishData::Str(expected.to_string()));
+
+        // far apart changes get separate hunks
+        let a: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let mut b = a.clone();
+        b[1] = "two".to_string();
+        b[17] = "eighteen".to_string();
+        let src = format!("(diff d\"{}\" d\"{}\")", a.join("\n"), b.join("\n"));
+        match eval_dish(&src) {
+            DishData::Str(s) => {
+                assert!(s.contains("@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3"));
+                assert!(s.contains("@@ -15,6 +15,6 @@\n 15\n 16\n 17\n-18\n+eighteen\n 19\n 20"));
+            }
+            DishData::Bin(_) => panic!("expected a string dish"),
+        }
+
+        let data = eval_dish("(diff d\"same\" d\"same\")");
+        assert_eq!(data, DishData::Str(String::new()));
+    }
+
+    #[test]
+    fn test_diff_bytes() {
+        let data = eval_dish("(diff d[1 2 3] d[1 9 3 4])");
+        let expected = "length: 3 -> 4\n00000001: 02 -> 09\n00000003: -- -> 04";
+        assert_eq!(data, DishData::Str(expected.to_string()));
+    }
+
+    #[test]
+    fn test_diff_truncated() {
+        assert_eq!(
+            byte_diff(&[0; 5000], &[1; 5000]).lines().count(),
+            DIFF_MAX_LINES + 1
+        );
+        assert!(byte_diff(&[0; 5000], &[1; 5000]).ends_with("... 4000 more lines"));
+    }
+
+    #[test]
+    fn test_tee() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        eval_str_in(&reader, &mut env, "(def d d\"hello\")");
+
+        let src = "(tee d reverse (recipe (rot13 13) to-base64))";
+        assert_eq!(
+            eval_str_in(&reader, &mut env, src),
+            "(Dish(\"olleh\") Dish(\"dXJ5eWI=\"))"
+        );
+
+        // the original is untouched
+        let original = eval_dish_in(&reader, &mut env, "d");
+        assert_eq!(original, DishData::Str("hello".to_string()));
+    }
+
+    #[test]
+    fn test_clone_dish() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        for src in [
+            "(def a d\"hello\")",
+            "(def b (clone-dish a))",
+            "(def c a)",
+            "(reverse b)",
+        ] {
+            eval_str_in(&reader, &mut env, src);
+        }
+
+        let a = eval_dish_in(&reader, &mut env, "a");
+        assert_eq!(a, DishData::Str("hello".to_string()));
+        let b = eval_dish_in(&reader, &mut env, "b");
+        assert_eq!(b, DishData::Str("olleh".to_string()));
+
+        // without a clone, both names are the same dish
+        eval_dish_in(&reader, &mut env, "(to-base64 c)");
+        let a = eval_dish_in(&reader, &mut env, "a");
+        assert_eq!(a, DishData::Str("aGVsbG8=".to_string()));
+    }
+
+    #[test]
+    fn test_map_lines() {
+        // `from-base64` produces binary, so the result is too
+        let data = eval_dish("(map-lines from-base64 d\"aGk=\ndGhlcmU=\")");
+        assert_eq!(data, DishData::Bin(b"hi\nthere".to_vec()));
+
+        let data = eval_dish("(map-lines (recipe reverse (rot13 13)) d\"abc\nxyz\")");
+        assert_eq!(data, DishData::Str("pon\nmlk".to_string()));
+
+        // a failing line fails the whole result, unless it's passed through
+        let failure = eval_failure("(map-lines from-roman d\"XIV\nnope\nIX\")");
+        assert!(failure.contains("line 2:"));
+        let data = eval_dish("(map-lines from-roman d\"XIV\nnope\nIX\" true)");
+        assert_eq!(data, DishData::Str("14\nnope\n9".to_string()));
+
+        // so are lines where the function errors, rather than failing
+        let data = eval_dish("(map-lines (recipe reverse first) d\"ab\ncd\" true)");
+        assert_eq!(data, DishData::Str("ab\ncd".to_string()));
+    }
+
+    #[test]
+    fn test_dish_functions_reject_lambdas() {
+        for src in [
+            "(map-lines (fn (d) d) d\"a\nb\")",
+            "(map-lines (fn (d) d) d\"a\nb\" true)",
+            "(tee d\"a\" reverse (fn (d) d))",
+            "(when-match \"a\" (fn (d) d) d\"a\")",
+        ] {
+            assert!(
+                eval_error(src).contains("can't run `fn` lambdas"),
+                "{}",
+                src
+            );
+        }
+    }
+
+    #[test]
+    fn test_when_match() {
+        let looks_like_base64 = "\"^[A-Za-z0-9+/]+=*$\"";
+        let src = format!("(when-match {} from-base64 d\"aGk=\")", looks_like_base64);
+        assert_eq!(eval_dish(&src), DishData::Bin(b"hi".to_vec()));
+
+        let src = format!(
+            "(when-match {} from-base64 d\"not base64!\")",
+            looks_like_base64
+        );
+        assert_eq!(eval_dish(&src), DishData::Str("not base64!".to_string()));
+
+        eval_error("(when-match \"(unclosed\" reverse d\"hi\")");
+    }
+
+    #[test]
+    fn test_dish_from_list() {
+        let data = eval_dish("(dish (map (fn (x) (+ x 65)) '(0 1 2)))");
+        assert_eq!(data, DishData::Bin(b"ABC".to_vec()));
+        assert_eq!(eval_dish("(dish '())"), DishData::Bin(vec![]));
+
+        for src in [
+            "(dish '(1 256))",
+            "(dish '(1 -1))",
+            "(dish '(1.5))",
+            "(dish '(\"a\"))",
+        ] {
+            eval_error(src);
+        }
+    }
+
+    #[test]
+    fn test_dish_bytes_and_string() {
+        assert_eq!(eval_str("(dish-bytes d\"AB\")"), "(65 66)");
+        assert_eq!(eval_str("(dish-bytes d[0 255])"), "(0 255)");
+        assert_eq!(eval_str("(dish-string d[104 105])"), "hi");
+        assert_eq!(eval_str("(dish-string d[104 255])"), "h\u{fffd}");
+
+        // round trip through the list functions and back
+        let data = eval_dish("(dish (map (fn (b) (+ b 1)) (dish-bytes d\"HAL\")))");
+        assert_eq!(data, DishData::Bin(b"IBM".to_vec()));
+        let data = eval_dish("(dish (dish-string (dish (dish-bytes d\"round trip\"))))");
+        assert_eq!(data, DishData::Str("round trip".to_string()));
+    }
+
+    #[test]
+    fn test_dish_length() {
+        assert_eq!(eval_str("(dish-length d\"\")"), "0");
+        assert_eq!(eval_str("(dish-length d\"hello\")"), "5");
+        assert_eq!(eval_str("(dish-length d[0 1 2 255])"), "4");
+
+        // the dish is left as it was
+        let src = "(do (def d d\"abc\") (if (= (dish-length d) 3) d nil))";
+        assert_eq!(eval_dish(src), DishData::Str("abc".to_string()));
+    }
+
+    #[test]
+    fn test_dish_split_and_concat() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        eval_str_in(
+            &reader,
+            &mut env,
+            "(def fields (dish-split d\"a,bc,,d\" \",\"))",
+        );
+        assert_eq!(
+            eval_str_in(&reader, &mut env, "fields"),
+            "(Dish(\"a\") Dish(\"bc\") Dish(\"\") Dish(\"d\"))"
+        );
+        let joined = eval_dish_in(&reader, &mut env, "(dish-concat fields \",\")");
+        assert_eq!(joined, DishData::Str("a,bc,,d".to_string()));
+        let joined = eval_dish_in(&reader, &mut env, "(dish-concat fields)");
+        assert_eq!(joined, DishData::Str("abcd".to_string()));
+        assert_eq!(
+            eval_dish("(dish-concat (quote ()) \",\")"),
+            DishData::Str(String::new())
+        );
+
+        // binary dishes split on a byte sequence and stay binary
+        eval_str_in(
+            &reader,
+            &mut env,
+            "(def parts (dish-split d[1 0 0 2 0 0] (quote (0 0))))",
+        );
+        assert_eq!(
+            eval_str_in(&reader, &mut env, "(dish-length (first parts))"),
+            "1"
+        );
+        assert_eq!(
+            eval_str_in(&reader, &mut env, "(dish-length (last parts))"),
+            "0"
+        );
+
+        let src = "(dish-concat (dish-split d[1 0 0 2] d[0 0]) d[9])";
+        assert_eq!(eval_dish(src), DishData::Bin(vec![1, 9, 2]));
+
+        for src in ["(dish-split d\"abc\" \"\")", "(dish-concat (quote (1 2)))"] {
+            eval_error(src);
+        }
+    }
+
+    #[test]
+    fn test_chunk_bytes() {
+        assert_eq!(
+            eval_str("(chunk-bytes d\"abcdefg\" 3)"),
+            "(Dish(\"abc\") Dish(\"def\") Dish(\"g\"))"
+        );
+        assert_eq!(eval_str("(chunk-bytes d\"\" 3)"), "()");
+
+        for src in ["d\"hello, world\"", "d[0 1 2 3 4 5 6 7 8 9]", "d\"👾👾\""] {
+            let original = eval_dish(src);
+            for size in [1, 3, 4, 100] {
+                let src = format!("(dish-concat (chunk-bytes {} {}))", src, size);
+                assert_eq!(eval_dish(&src).as_bytes(), original.as_bytes());
+            }
+        }
+
+        for src in ["(chunk-bytes d\"abc\" 0)", "(chunk-bytes d\"abc\" 1.5)"] {
+            eval_error(src);
+        }
+    }
+
+    #[test]
+    fn test_slurp_max_file_mem() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        let path = std::env::temp_dir().join(format!("codebake-slurp-{}", std::process::id()));
+        std::fs::write(&path, "hello world").unwrap();
+        let src = format!("(slurp \"{}\" :mode \"text\")", path.display());
+
+        // there's no limit unless one is set
+        assert_eq!(env.max_file_mem(), None);
+        let slurped = eval_dish_in(&reader, &mut env, &src);
+        assert_eq!(slurped, DishData::Str("hello world".to_string()));
+
+        env.set_max_file_mem(Some(4));
+        let res = parse_eval(&reader, &mut env, &src);
+        std::fs::remove_file(&path).unwrap();
+
+        let err = format!("{}", res.err().expect("slurp should be over the limit"));
+        assert!(err.contains("more than the limit of 4 bytes"), "{}", err);
+
+        // files that don't report their real size are still held to the limit
+        #[cfg(unix)]
+        {
+            let err = eval_error_in(&reader, &mut env, "(slurp \"/dev/zero\")");
+            assert!(err.contains("more than the limit of 4 bytes"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_integer_arguments() {
+        eval_dish("((rot13 13) d\"uryyb\")");
+        eval_dish("((to-radix 16) d\"a\")");
+        eval_dish("((to-radix 1.6e1) d\"a\")");
+
+        for src in [
+            "((to-radix 16.0000001) d\"a\")",
+            "((rot13 0.5) d\"uryyb\")",
+            "((take-bytes 1e300) d\"abc\")",
+            "((take-bytes \"3\") d\"abc\")",
+        ] {
+            let err = eval_error(src);
+            assert!(err.starts_with("expected an integer."), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_bake_list() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        eval_str_in(
+            &reader,
+            &mut env,
+            "(def dishes (cons d\"one\" (cons d\"two\" (cons d\"three\" ()))))",
+        );
+        assert_eq!(
+            eval_str_in(
+                &reader,
+                &mut env,
+                "(bake (recipe to-base64 reverse) dishes)"
+            ),
+            "(Dish(\"l52b\") Dish(\"vdHd\") Dish(\"=UWZyhGd\"))"
+        );
+        // the dishes in the list are baked in place
+        assert_eq!(
+            eval_str_in(&reader, &mut env, "(first dishes)"),
+            "Dish(\"l52b\")"
+        );
+
+        // the error policy applies to each dish separately
+        eval_str_in(
+            &reader,
+            &mut env,
+            "(def dishes (cons d\"aGk=\" (cons d\"!\" ())))",
+        );
+        assert_eq!(
+            eval_str_in(
+                &reader,
+                &mut env,
+                "(bake (recipe from-base64 reverse) dishes :on-error \"skip\")"
+            ),
+            "(Dish([ih]) Dish(\"!\"))"
+        );
+        assert_eq!(
+            eval_str_in(&reader, &mut env, "(bake (recipe reverse) ())"),
+            "nil"
+        );
+
+        eval_error("(bake (recipe reverse) (cons 1 ()))");
+    }
+
+    #[test]
+    fn test_inspect() {
+        assert_eq!(
+            eval_str("(inspect d\"aabb\")"),
+            "success, Str, 4 bytes, entropy 1.000 bits per byte"
+        );
+        assert_eq!(
+            eval_str("(inspect d[0 1 2 3 4 5 6 7])"),
+            "success, Bin, 8 bytes, entropy 3.000 bits per byte"
+        );
+        assert_eq!(
+            eval_str("(inspect d\"\")"),
+            "success, Str, 0 bytes, entropy 0.000 bits per byte"
+        );
+        let failed = eval_str("(inspect (from-base64 (dish \"not base64!\")))");
+        assert!(failed.starts_with("failure, decode-error: "), "{}", failed);
+
+        // inspecting doesn't change the dish
+        let src = "(do (def d d\"hello\") (inspect d) d)";
+        assert_eq!(eval_dish(src), DishData::Str("hello".to_string()));
+        eval_error("(inspect \"hello\")");
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(eval_str("(format \"no directives\")"), "no directives");
+        assert_eq!(eval_str("(format \"~a + ~a\" 1 2.5)"), "1 + 2.5");
+        assert_eq!(
+            eval_str("(format \"~a and ~s\" \"hi\" \"hi\")"),
+            "hi and \"hi\""
+        );
+        assert_eq!(
+            eval_str("(format \"~s\" \"a \\\"quote\\\"\\n\")"),
+            "\"a \\\"quote\\\"\\n\""
+        );
+        assert_eq!(eval_str("(format \"~a.txt\" d\"output\")"), "output.txt");
+        assert_eq!(eval_str("(format \"~s\" d\"output\")"), "Dish(\"output\")");
+        assert_eq!(eval_str("(format \"~~~a~~\" (quote (1 2)))"), "~(1 2)~");
+
+        // the formatted string can be read back in with `read`
+        assert_eq!(eval_str("(read (format \"~s\" \"tab\\t\"))"), "tab\t");
+
+        for src in [
+            "(format \"~a ~a\" 1)",
+            "(format \"~a\" 1 2)",
+            "(format \"~x\" 1)",
+            "(format \"trailing ~\")",
+            "(format 1)",
+            "(println \"~a\")",
+        ] {
+            eval_error(src);
+        }
+        assert_eq!(eval_str("(println \"~a ~s\" 1 \"two\")"), "nil");
+    }
+
+    #[test]
+    fn test_getenv_argv() {
+        std::env::set_var("CODEBAKE_TEST_GETENV", "some value");
+        assert_eq!(eval_str("(getenv \"CODEBAKE_TEST_GETENV\")"), "some value");
+        std::env::remove_var("CODEBAKE_TEST_GETENV");
+        assert_eq!(eval_str("(getenv \"CODEBAKE_TEST_GETENV\")"), "nil");
+
+        // the test binary always has at least its own name
+        let argv0 = std::env::args().next().unwrap();
+        assert_eq!(eval_str("(first (argv))"), argv0);
+    }
+
+    #[cfg(all(feature = "pipe", unix))]
+    #[test]
+    fn test_pipe() {
+        let data = eval_dish("(pipe d\"hello\" \"cat\")");
+        assert_eq!(data, DishData::Str("hello".to_string()));
+        let data = eval_dish("(pipe d\"hello\" \"tr\" '(\"a-z\" \"A-Z\"))");
+        assert_eq!(data, DishData::Str("HELLO".to_string()));
+
+        // the original dish isn't touched
+        let src = "(do (def d d\"abc\") (pipe d \"rev\") d)";
+        assert_eq!(eval_dish(src), DishData::Str("abc".to_string()));
+
+        let err = eval_error("(pipe d\"hi\" \"sh\" '(\"-c\" \"echo oops >&2; exit 3\"))");
+        assert_eq!(err, "'sh' failed with exit code 3. oops");
+
+        eval_error("(pipe d\"hi\" \"codebake-no-such-command\")");
+    }
 }
@@ -12,10 +12,10 @@ mod functions;
 mod functions_nonnative;
 mod parser;
 
-pub use crate::lisp::parser::Reader;
+pub use crate::lisp::parser::{default_reader, Reader};
 use crate::ops::OPERATIONS;
-use crate::Dish;
-use std::cell::RefCell;
+use crate::{Dish, DishData, OperationArguments, OperationInfo};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fmt;
 use std::io::{self, Write};
@@ -28,9 +28,11 @@ pub type LispResult = std::result::Result<Expression, Error>;
 ///   * Symbol - a raw symbol
 ///   * Number - a floating point number
 ///   * Bool   - a boolean value (`true` and `false`)
+///   * Nil    - nothing, what `()` evaluates to and what functions with no result return
 ///   * String - a string
 ///   * List   - a list of expressions
 ///   * Func   - a pointer to a function object
+///   * Operation - an operation, along with its arguments once they've been bound
 ///   * Lambda - an expression with a set of captured variables
 ///   * Dish   - a pointer to a **mutable** Dish object
 ///
@@ -39,9 +41,11 @@ pub enum Expression {
     Symbol(String),
     Number(f64),
     Bool(bool),
+    Nil,
     String(String),
     List(Vec<Expression>),
     Func(Rc<dyn Fn(&[Expression]) -> LispResult>),
+    Operation(&'static OperationInfo, Option<Rc<OperationArguments>>),
     Lambda(Lambda),
     Dish(Rc<RefCell<Dish>>),
 }
@@ -60,6 +64,43 @@ pub struct Error(String);
 pub struct Environment<'a> {
     data: HashMap<String, Expression>,
     outer: Option<&'a Environment<'a>>,
+    limits: Rc<EvalLimits>,
+}
+
+/// The default for how deeply expressions can nest while being evaluated
+pub const DEFAULT_MAX_DEPTH: usize = 10000;
+
+/// The default for how many tail calls a function call can make in a row
+pub const DEFAULT_MAX_TAIL_CALLS: usize = 1_000_000;
+
+/// A stack size for threads running the interpreter that has room for
+/// `DEFAULT_MAX_DEPTH` levels of evaluation without growing the stack.
+/// Evaluation grows the stack on its own when it runs low, so this is
+/// only needed to avoid the cost of doing that
+pub const EVAL_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Limits that stop runaway recursion with an error instead of a stack overflow
+/// or a hang, and that keep `slurp` from reading files that are too big.
+/// An environment shares these with every scope made inside it
+///
+pub struct EvalLimits {
+    max_depth: Cell<usize>,
+    max_tail_calls: Cell<usize>,
+    /// the biggest file `slurp` will read, if there is a limit
+    max_file_mem: Cell<Option<u64>>,
+    /// how deeply nested the evaluation currently is
+    depth: Cell<usize>,
+}
+
+impl Default for EvalLimits {
+    fn default() -> EvalLimits {
+        EvalLimits {
+            max_depth: Cell::new(DEFAULT_MAX_DEPTH),
+            max_tail_calls: Cell::new(DEFAULT_MAX_TAIL_CALLS),
+            max_file_mem: Cell::new(None),
+            depth: Cell::new(0),
+        }
+    }
 }
 
 impl<'a> Environment<'a> {
@@ -67,21 +108,82 @@ impl<'a> Environment<'a> {
         Environment {
             data: HashMap::new(),
             outer: None,
+            limits: Rc::new(EvalLimits::default()),
         }
     }
+
+    /// Sets how deeply expressions can nest while being evaluated before giving up
+    /// with a "maximum recursion depth exceeded" error
+    ///
+    pub fn set_max_depth(&self, max_depth: usize) {
+        self.limits.max_depth.set(max_depth);
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.limits.max_depth.get()
+    }
+
+    /// Sets how many tail calls a single function call can make in a row before
+    /// giving up. Tail calls don't use up any depth, so this is what stops an
+    /// endless loop like `(defn loop () (loop))`
+    ///
+    pub fn set_max_tail_calls(&self, max_tail_calls: usize) {
+        self.limits.max_tail_calls.set(max_tail_calls);
+    }
+
+    pub fn max_tail_calls(&self) -> usize {
+        self.limits.max_tail_calls.get()
+    }
+
+    /// Sets the biggest file, in bytes, that `slurp` will read into a dish,
+    /// or `None` for no limit, which is the default. Only the builtins of
+    /// the environment made by `default_env` see this
+    ///
+    pub fn set_max_file_mem(&self, bytes: Option<u64>) {
+        self.limits.max_file_mem.set(bytes);
+    }
+
+    pub fn max_file_mem(&self) -> Option<u64> {
+        self.limits.max_file_mem.get()
+    }
+
+    /// Binds `name` to `value` in this environment, replacing any
+    /// existing binding
+    ///
+    pub fn define(&mut self, name: &str, value: Expression) {
+        self.data.insert(name.to_string(), value);
+    }
 }
 
 impl<'a> Default for Environment<'a> {
     fn default() -> Environment<'a> {
-        let reader = Reader::new();
-        default_env(&reader)
+        default_env(&default_reader())
     }
 }
 
 #[derive(Clone)]
+/// A lambda function
+///
+/// `captured` is a snapshot of the local variables that were in scope where the
+/// lambda was made, which is what lets a returned lambda keep using them.
+/// `name` is set for functions made with `defn` inside another function
+///
 pub struct Lambda {
     params: Rc<Expression>,
     body: Rc<Expression>,
+    captured: Rc<HashMap<String, Expression>>,
+    name: Option<String>,
+}
+
+impl Expression {
+    /// Whether this is a successful `Dish` holding binary data
+    ///
+    pub fn is_binary_dish(&self) -> bool {
+        match self {
+            Expression::Dish(d) => matches!(&*d.borrow(), Dish::Success(DishData::Bin(_))),
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for Expression {
@@ -90,12 +192,14 @@ impl fmt::Display for Expression {
             Expression::Symbol(k) => k.clone(),
             Expression::Number(k) => k.to_string(),
             Expression::Bool(k) => k.to_string(),
+            Expression::Nil => "nil".to_string(),
             Expression::String(k) => k.clone(),
             Expression::List(k) => {
                 let xs: Vec<String> = k.iter().map(|x| x.to_string()).collect();
                 format!("({})", xs.join(" "))
             }
             Expression::Func(_) => "built-in function".to_string(),
+            Expression::Operation(oi, _) => format!("operation '{}'", oi.name),
             Expression::Lambda(_) => "lambda function".to_string(),
             Expression::Dish(dish) => {
                 // so much deref
@@ -120,6 +224,7 @@ impl PartialEq for Expression {
             (Expression::String(s1), Expression::String(s2)) => s1 == s2,
             (Expression::Number(s1), Expression::Number(s2)) => s1 == s2,
             (Expression::Bool(s1), Expression::Bool(s2)) => s1 == s2,
+            (Expression::Nil, Expression::Nil) => true,
             (Expression::Dish(s1), Expression::Dish(s2)) => match (&*s1.borrow(), &*s2.borrow()) {
                 (Dish::Success(d1), Dish::Success(d2)) => d1 == d2,
                 _ => false,
@@ -132,16 +237,15 @@ impl PartialEq for Expression {
 pub type InterpreterResult = std::result::Result<String, Error>;
 
 pub struct Interpreter<'a> {
-    reader: Reader,
+    reader: Rc<Reader>,
     env: Environment<'a>,
 }
 
 impl<'a> Interpreter<'a> {
     pub fn new() -> Self {
-        let reader = Reader::new();
+        let reader = default_reader();
         let mut env = Environment::empty();
-        env.data
-            .insert(":ans".to_string(), Expression::Symbol("nil".to_string()));
+        env.data.insert(":ans".to_string(), Expression::Nil);
 
         Interpreter { reader, env }
     }
@@ -150,7 +254,11 @@ impl<'a> Interpreter<'a> {
         match parse_eval(&self.reader, &mut self.env, &s) {
             Ok(res) => {
                 self.env.data.insert(":ans".to_string(), res.clone());
-                Ok(format!("{}", res))
+                // the REPL shows all of a dish, rather than the start of it
+                match &res {
+                    Expression::Dish(dish) => Ok(dish.borrow().display_full()),
+                    _ => Ok(format!("{}", res)),
+                }
             }
             Err(e) => Err(e),
         }
@@ -184,6 +292,12 @@ impl<'a> Interpreter<'a> {
                 }
             }
 
+            // blank lines and lines that are only a comment have nothing to evaluate
+            let line = expr.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
             match self.eval(&expr) {
                 Ok(s) => println!("{}", s),
                 Err(e) => println!("error: {}", e),
@@ -194,10 +308,9 @@ impl<'a> Interpreter<'a> {
 
 impl<'a> Default for Interpreter<'a> {
     fn default() -> Self {
-        let reader = Reader::new();
+        let reader = default_reader();
         let mut env = default_env(&reader);
-        env.data
-            .insert(":ans".to_string(), Expression::Symbol("nil".to_string()));
+        env.data.insert(":ans".to_string(), Expression::Nil);
         Interpreter { reader, env }
     }
 }
@@ -209,8 +322,20 @@ pub fn parse_eval(reader: &Reader, env: &mut Environment, expr: &String) -> Lisp
 fn check_parens(s: &String) -> bool {
     let mut count = 0;
     let mut string_mode = false;
+    let mut escaped = false;
+    let mut comment = false;
     for i in s.chars() {
+        // the character after a backslash in a string can't close it
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
         match i {
+            '\n' if comment => comment = false,
+            _ if comment => {}
+            ';' if !string_mode => comment = true,
+            '\\' if string_mode => escaped = true,
             '(' => {
                 if !string_mode {
                     count += 1
@@ -236,9 +361,12 @@ fn check_parens(s: &String) -> bool {
 /// all the builtin functions and values
 ///
 pub fn default_env<'a>(reader: &Reader) -> Environment<'a> {
+    // builtins that need the limits, like `slurp`, hold on to them themselves
+    let limits = Rc::new(EvalLimits::default());
     let mut data: HashMap<String, Expression> = HashMap::new();
     data.insert("true".to_string(), Expression::Bool(true));
     data.insert("false".to_string(), Expression::Bool(false));
+    data.insert("nil".to_string(), Expression::Nil);
 
     data.insert("+".to_string(), functions::lisp_add());
     data.insert("-".to_string(), functions::lisp_subtract());
@@ -252,14 +380,55 @@ pub fn default_env<'a>(reader: &Reader) -> Environment<'a> {
     data.insert("cons".to_string(), functions::lisp_cons());
 
     data.insert("dish".to_string(), functions::lisp_dish());
+    data.insert("clone-dish".to_string(), functions::lisp_clone_dish());
+    data.insert("dish-bytes".to_string(), functions::lisp_dish_bytes());
+    data.insert("dish-string".to_string(), functions::lisp_dish_string());
+    data.insert("dish-length".to_string(), functions::lisp_dish_length());
+    data.insert("dish-split".to_string(), functions::lisp_dish_split());
+    data.insert("inspect".to_string(), functions::lisp_inspect());
+    data.insert("dish-concat".to_string(), functions::lisp_dish_concat());
+    data.insert("chunk-bytes".to_string(), functions::lisp_chunk_bytes());
     data.insert("recipe".to_string(), functions::lisp_recipe());
     data.insert("bake".to_string(), functions::lisp_bake());
-
+    data.insert("bake-all".to_string(), functions::lisp_bake_all());
+    data.insert("bake-timed".to_string(), functions::lisp_bake_timed());
+    data.insert(
+        "validate-recipe".to_string(),
+        functions::lisp_validate_recipe(),
+    );
+    data.insert("diff".to_string(), functions::lisp_diff());
+    data.insert("tee".to_string(), functions::lisp_tee());
+    data.insert("map-lines".to_string(), functions::lisp_map_lines());
+    data.insert("when-match".to_string(), functions::lisp_when_match());
+    data.insert(
+        "dish-push-byte".to_string(),
+        functions::lisp_dish_push_byte(),
+    );
+    data.insert(
+        "dish-unshift-byte".to_string(),
+        functions::lisp_dish_unshift_byte(),
+    );
+
+    data.insert("read".to_string(), functions::lisp_read());
     data.insert("print".to_string(), functions::lisp_print());
-    data.insert("slurp".to_string(), functions::lisp_slurp());
+    data.insert("println".to_string(), functions::lisp_println());
+    data.insert("format".to_string(), functions::lisp_format());
+    data.insert("slurp".to_string(), functions::lisp_slurp(limits.clone()));
     data.insert("spit".to_string(), functions::lisp_spit());
-
-    let mut env = Environment { data, outer: None };
+    data.insert("getenv".to_string(), functions::lisp_getenv());
+    data.insert("argv".to_string(), functions::lisp_argv());
+    #[cfg(not(target_arch = "wasm32"))]
+    data.insert("read-stdin".to_string(), functions::lisp_read_stdin());
+    #[cfg(not(target_arch = "wasm32"))]
+    data.insert("read-line".to_string(), functions::lisp_read_line());
+    #[cfg(feature = "pipe")]
+    data.insert("pipe".to_string(), functions::lisp_pipe());
+
+    let mut env = Environment {
+        data,
+        outer: None,
+        limits,
+    };
 
     for oi in OPERATIONS {
         functions::embed_operation(oi, &mut env);
@@ -277,7 +446,21 @@ pub fn default_env<'a>(reader: &Reader) -> Environment<'a> {
 mod tests {
     use std::{cell::RefCell, rc::Rc};
 
-    use crate::{lisp::Expression, Dish};
+    use crate::{
+        lisp::{
+            check_parens, default_env, parse_eval, Environment, Error, Expression, Reader,
+            DEFAULT_MAX_DEPTH, EVAL_STACK_SIZE,
+        },
+        Dish,
+    };
+
+    fn try_eval_str(reader: &Reader, env: &mut Environment, src: &str) -> Result<String, Error> {
+        parse_eval(reader, env, &src.to_string()).map(|res| format!("{}", res))
+    }
+
+    fn eval_str(reader: &Reader, env: &mut Environment, src: &str) -> String {
+        try_eval_str(reader, env, src).unwrap()
+    }
 
     #[test]
     fn test_symbol_eq() {
@@ -321,6 +504,356 @@ mod tests {
         assert!(lhs != rhs);
     }
 
+    #[test]
+    fn test_is_binary_dish() {
+        let bin = Expression::Dish(Rc::new(RefCell::new(Dish::from_bytes(vec![0, 1]))));
+        assert!(bin.is_binary_dish());
+
+        let s = Expression::Dish(Rc::new(RefCell::new(Dish::from_string("hi".to_owned()))));
+        assert!(!s.is_binary_dish());
+        assert!(!Expression::String("hi".to_owned()).is_binary_dish());
+    }
+
+    #[test]
+    fn test_check_parens_escaped_quote() {
+        assert!(check_parens(&r#"(dish "\")")"#.to_string()));
+        assert!(!check_parens(&r#"(dish "\\"))"#.to_string()));
+    }
+
+    #[test]
+    fn test_check_parens_comments() {
+        assert!(check_parens(&"(+ 1 ; (\n 2)".to_string()));
+        assert!(check_parens(&"(+ 1 2) ; )".to_string()));
+        assert!(check_parens(&"(dish \";(\")".to_string()));
+        assert!(!check_parens(&"(+ 1 ; 2)".to_string()));
+    }
+
+    #[test]
+    fn test_quasiquote() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        assert_eq!(eval_str(&reader, &mut env, "`(1 ,(+ 1 1) 3)"), "(1 2 3)");
+        assert_eq!(eval_str(&reader, &mut env, "(def xs '(2 3))"), "xs");
+        assert_eq!(
+            eval_str(&reader, &mut env, "`(1 ,@xs (4 ,(+ 2 3)))"),
+            "(1 2 3 (4 5))"
+        );
+        assert_eq!(eval_str(&reader, &mut env, "`sym"), "sym");
+        assert_eq!(eval_str(&reader, &mut env, "`(1 ,@nil 2)"), "(1 2)");
+        assert_eq!(eval_str(&reader, &mut env, "`(1 ,@'() 2)"), "(1 2)");
+
+        assert!(parse_eval(&reader, &mut env, &",xs".to_string()).is_err());
+        assert!(parse_eval(&reader, &mut env, &"`(1 ,@(+ 1 1))".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_closures() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        eval_str(&reader, &mut env, "(def adder (fn (x) (fn (y) (+ x y))))");
+        assert_eq!(eval_str(&reader, &mut env, "((adder 3) 4)"), "7");
+
+        // the captured `x` wins over one in the caller's scope
+        eval_str(&reader, &mut env, "(def add3 (adder 3))");
+        eval_str(&reader, &mut env, "(defn call-with-x (x f) (f 1))");
+        assert_eq!(eval_str(&reader, &mut env, "(call-with-x 100 add3)"), "4");
+
+        // a local function can call itself
+        eval_str(
+            &reader,
+            &mut env,
+            "(defn outer (n) (do (defn down (n) (if (= n 0) 0 (down (- n 1)))) (down n)))",
+        );
+        assert_eq!(eval_str(&reader, &mut env, "(outer 5)"), "0");
+    }
+
+    #[test]
+    fn test_tail_calls() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        eval_str(
+            &reader,
+            &mut env,
+            "(defn count-down (n) (if (= n 0) (quote done) (count-down (- n 1))))",
+        );
+        assert_eq!(eval_str(&reader, &mut env, "(count-down 100000)"), "done");
+
+        eval_str(
+            &reader,
+            &mut env,
+            "(defn sum-to (n acc) (if (= n 0) acc (do n (sum-to (- n 1) (+ acc n)))))",
+        );
+        assert_eq!(
+            eval_str(&reader, &mut env, "(sum-to 50000 0)"),
+            "1250025000"
+        );
+        assert_eq!(eval_str(&reader, &mut env, "(do 1 2 3)"), "3");
+    }
+
+    #[test]
+    fn test_eval_and_read() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        assert_eq!(eval_str(&reader, &mut env, "(eval '(+ 1 2))"), "3");
+        assert_eq!(eval_str(&reader, &mut env, "(read \"(+ 1 2)\")"), "(+ 1 2)");
+        assert_eq!(
+            eval_str(&reader, &mut env, "(eval (read \"(+ 1 2)\"))"),
+            "3"
+        );
+
+        // eval runs in the current environment
+        eval_str(&reader, &mut env, "(def x 10)");
+        assert_eq!(eval_str(&reader, &mut env, "(eval `(+ x ,(+ 1 1)))"), "12");
+        assert!(parse_eval(&reader, &mut env, &"(read \"(+ 1\")".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_try() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        assert_eq!(eval_str(&reader, &mut env, "(try (+ 1 2) 0)"), "3");
+        assert_eq!(
+            eval_str(&reader, &mut env, "(try (+ 1 undefined) error)"),
+            "unexpected symbol 'undefined'."
+        );
+        assert_eq!(
+            eval_str(
+                &reader,
+                &mut env,
+                "(try (bake (recipe to-base64) (dish \"hi\")) error)"
+            ),
+            "Dish(\"aGk=\")"
+        );
+        assert_eq!(
+            eval_str(
+                &reader,
+                &mut env,
+                "(try (bake (recipe from-base64) (dish \"not base64!\")) (dish \"fallback\"))"
+            ),
+            "Dish(\"fallback\")"
+        );
+        assert_eq!(
+            eval_str(
+                &reader,
+                &mut env,
+                "(try (bake (recipe from-base64) (dish \"not base64!\")) error-kind)"
+            ),
+            "decode-error"
+        );
+        assert_eq!(
+            eval_str(&reader, &mut env, "(try (+ 1 undefined) error-kind)"),
+            "nil"
+        );
+    }
+
+    #[test]
+    fn test_bake_timed() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        eval_str(
+            &reader,
+            &mut env,
+            "(def timed (bake-timed (recipe reverse to-base64) (dish \"hi\")))",
+        );
+        assert_eq!(
+            eval_str(&reader, &mut env, "(first timed)"),
+            "Dish(\"aWg=\")"
+        );
+
+        let report = eval_str(&reader, &mut env, "(last timed)");
+        let lines: Vec<&str> = report.trim_matches('"').lines().collect();
+        assert_eq!(lines.len(), 3, "{}", report);
+        assert!(lines[0].starts_with("reverse: "), "{}", report);
+        assert!(lines[1].starts_with("to-base64: "), "{}", report);
+        assert!(lines[2].starts_with("total: "), "{}", report);
+
+        // every step is still timed after one fails
+        eval_str(
+            &reader,
+            &mut env,
+            "(def timed (bake-timed (recipe from-base64 reverse) (dish \"not base64!\")))",
+        );
+        assert_eq!(
+            eval_str(&reader, &mut env, "(try (first timed) error-kind)"),
+            "decode-error"
+        );
+        assert!(eval_str(&reader, &mut env, "(last timed)").contains("reverse: "));
+
+        let res = parse_eval(
+            &reader,
+            &mut env,
+            &"(bake-timed (recipe reverse) 1)".to_string(),
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_validate_recipe() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        for recipe in [
+            "(recipe (rot13 13) reverse to-base64)",
+            "(recipe show-non-printable first)",
+            "'()",
+        ] {
+            let res = try_eval_str(&reader, &mut env, &format!("(validate-recipe {})", recipe));
+            assert_eq!(res.unwrap(), "true", "{}", recipe);
+        }
+
+        let err = try_eval_str(
+            &reader,
+            &mut env,
+            "(validate-recipe (recipe reverse rot13))",
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "step 2: operation 'rot13' needs arguments (n) but was given none"
+        );
+        // `recipe` rejects these itself, so the list is built by hand
+        let err = try_eval_str(
+            &reader,
+            &mut env,
+            "(validate-recipe (cons reverse (quote (5))))",
+        )
+        .unwrap_err();
+        assert_eq!(format!("{}", err), "step 2: expected a function. got 5");
+        assert!(try_eval_str(&reader, &mut env, "(validate-recipe reverse)").is_err());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        // the default depth needs more than a test thread's stack
+        let handle = std::thread::Builder::new()
+            .stack_size(EVAL_STACK_SIZE)
+            .spawn(|| {
+                let reader = Reader::new();
+                let mut env = default_env(&reader);
+                env.set_max_tail_calls(10000);
+                eval_str(&reader, &mut env, "(defn loop () (loop))");
+                let err = try_eval_str(&reader, &mut env, "(loop)").unwrap_err();
+                assert_eq!(format!("{}", err), "maximum recursion depth exceeded");
+
+                eval_str(&reader, &mut env, "(defn deep (n) (+ 1 (deep n)))");
+                let err = try_eval_str(&reader, &mut env, "(deep 1)").unwrap_err();
+                assert_eq!(format!("{}", err), "maximum recursion depth exceeded");
+
+                // the depth is back to zero afterwards
+                assert!(try_eval_str(&reader, &mut env, "(+ 1 2)").is_ok());
+            })
+            .unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_max_depth_default_stack() {
+        // a spawned thread gets a small stack, much smaller than the main thread's
+        let handle = std::thread::spawn(|| {
+            let reader = Reader::new();
+            let mut env = default_env(&reader);
+
+            let nested = |n| format!("{}1{}", "(+ 1 ".repeat(n), ")".repeat(n));
+
+            let deep = nested(DEFAULT_MAX_DEPTH);
+            let res = parse_eval(&reader, &mut env, &deep).unwrap();
+            assert_eq!(format!("{}", res), (DEFAULT_MAX_DEPTH + 1).to_string());
+
+            let too_deep = nested(DEFAULT_MAX_DEPTH + 1);
+            let err = parse_eval(&reader, &mut env, &too_deep).err().unwrap();
+            assert_eq!(format!("{}", err), "maximum recursion depth exceeded");
+        });
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_nil() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        assert_eq!(eval_str(&reader, &mut env, "nil"), "nil");
+        assert_eq!(eval_str(&reader, &mut env, "()"), "nil");
+        assert_eq!(eval_str(&reader, &mut env, "(first '())"), "nil");
+        assert_eq!(eval_str(&reader, &mut env, "(last '())"), "nil");
+        assert_eq!(eval_str(&reader, &mut env, "(butlast '())"), "()");
+        assert_eq!(eval_str(&reader, &mut env, "(empty? nil)"), "true");
+        assert_eq!(eval_str(&reader, &mut env, "(empty? '(1))"), "false");
+        assert_eq!(eval_str(&reader, &mut env, "(cons 1 nil)"), "(1)");
+        assert_eq!(eval_str(&reader, &mut env, "(if nil 1 2)"), "2");
+        assert_eq!(eval_str(&reader, &mut env, "(if (first '()) 1 2)"), "2");
+        assert_eq!(
+            eval_str(&reader, &mut env, "(map (fn (x) (+ x 1)) nil)"),
+            "()"
+        );
+    }
+
+    #[test]
+    fn test_empty_list_accessors() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        // `(rest '(1))` is an empty list rather than nil, so both get checked
+        for empty in ["'()", "(rest '(1))"] {
+            assert_eq!(
+                eval_str(&reader, &mut env, &format!("(first {})", empty)),
+                "nil"
+            );
+            assert_eq!(
+                eval_str(&reader, &mut env, &format!("(last {})", empty)),
+                "nil"
+            );
+            assert_eq!(
+                eval_str(&reader, &mut env, &format!("(rest {})", empty)),
+                "()"
+            );
+            assert_eq!(
+                eval_str(&reader, &mut env, &format!("(butlast {})", empty)),
+                "()"
+            );
+        }
+
+        assert_eq!(eval_str(&reader, &mut env, "(last '(1))"), "1");
+        assert_eq!(eval_str(&reader, &mut env, "(butlast '(1))"), "()");
+        assert_eq!(eval_str(&reader, &mut env, "(butlast '(1 2 3))"), "(1 2)");
+    }
+
+    #[test]
+    fn test_define() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+        let input = Expression::Dish(Rc::new(RefCell::new(Dish::from_bytes(vec![104, 105]))));
+        env.define("input", input);
+
+        let res = parse_eval(&reader, &mut env, &"input".to_string()).unwrap();
+        assert!(res.is_binary_dish());
+    }
+
+    #[test]
+    fn test_operation_aliases() {
+        let reader = Reader::new();
+        let mut env = default_env(&reader);
+
+        let expected = eval_str(&reader, &mut env, "(from-base64 (dish \"aGk=\"))");
+        assert_eq!(
+            eval_str(&reader, &mut env, "(from-b64 (dish \"aGk=\"))"),
+            expected
+        );
+        assert_eq!(
+            eval_str(&reader, &mut env, "(from-base-64 (dish \"aGk=\"))"),
+            expected
+        );
+        assert_eq!(
+            eval_str(&reader, &mut env, "(to-b64 (dish \"hi\"))"),
+            eval_str(&reader, &mut env, "(to-base64 (dish \"hi\"))")
+        );
+    }
+
     #[test]
     fn test_dish_eq() {
         let lhs = Expression::Dish(Rc::new(RefCell::new(Dish::from_string(
@@ -9,15 +9,31 @@ use crate::Dish;
 use chumsky::error::SimpleReason;
 use chumsky::prelude::*;
 use std::cell::RefCell;
-use std::hash::Hash;
+use std::ops::Range;
 use std::rc::Rc;
 
 pub struct Reader {
     parser: Box<dyn Parser<char, Expression, Error = Simple<char>>>,
 }
 
+thread_local! {
+    /// the reader handed out by `default_reader`. the parser isn't `Sync`,
+    /// so each thread builds its own, and it's dropped when the thread exits
+    static DEFAULT_READER: Rc<Reader> = Rc::new(Reader::new());
+}
+
+/// Returns the shared `Reader` for the current thread, building it the
+/// first time it's asked for. Prefer this over `Reader::new` unless you
+/// really need a separate reader
+///
+pub fn default_reader() -> Rc<Reader> {
+    DEFAULT_READER.with(Rc::clone)
+}
+
 impl Reader {
     ///! Constructs a `Reader` by building the parser and storing it
+    ///! This is expensive, so build one `Reader` and reuse it for every
+    ///! expression (or just use `default_reader`)
     ///!
     pub fn new() -> Reader {
         Reader {
@@ -28,31 +44,53 @@ impl Reader {
     ///! Attempts to parse a string `s` as an expression
     ///!
     pub fn parse(&self, s: &String) -> Result<Expression, Error> {
+        let src = s.as_str();
         self.parser
-            .parse(s.as_str().trim())
-            .map_err(convert_cheaps_to_err)
+            .parse(src)
+            .map_err(|cheaps| convert_cheaps_to_err(src, cheaps))
     }
 }
 
-/// Converts a vector of `Cheap`s into a `lisp::Error`. This is utilized by `Reader::parse`
+/// Converts a vector of `Cheap`s into a `lisp::Error`, with the position of each
+/// one in `src`. This is utilized by `Reader::parse`
 ///
-fn convert_cheaps_to_err<I: Eq + Hash, S: Clone>(cheaps: Vec<Simple<I, S>>) -> Error {
+fn convert_cheaps_to_err(src: &str, cheaps: Vec<Simple<char>>) -> Error {
     Error(
         cheaps
             .iter()
-            .map(|cheap| cheap.reason())
-            .map(|e| match e {
-                SimpleReason::Unexpected => "unexpected input".to_string(),
-                SimpleReason::Unclosed { .. } => "unclosed parenthesis".to_string(),
-                SimpleReason::Custom(s) => s.to_string(),
+            .map(|cheap| {
+                let reason = match cheap.reason() {
+                    SimpleReason::Unexpected => match cheap.found() {
+                        Some(c) => format!("unexpected '{}'", c),
+                        None => "unexpected end of input".to_string(),
+                    },
+                    SimpleReason::Unclosed { delimiter, .. } => format!("unclosed '{}'", delimiter),
+                    SimpleReason::Custom(s) => s.to_string(),
+                };
+                let (line, col) = line_col(src, cheap.span().start);
+                format!("parse error at {}:{}: {}", line, col, reason)
             })
-            .fold("".to_string(), |mut a, n| {
-                a.push_str(&n);
-                a
-            }),
+            .collect::<Vec<_>>()
+            .join("; "),
     )
 }
 
+/// helper function that turns a char offset into `src` into a 1-based line and column
+///
+fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut col = 1;
+    for c in src.chars().take(offset) {
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
 /// This implements the lisp parser!
 ///
 /// This function could be improved ***significantly*** because I don't really understand chumsky
@@ -60,23 +98,64 @@ fn convert_cheaps_to_err<I: Eq + Hash, S: Clone>(cheaps: Vec<Simple<I, S>>) -> E
 /// two `list` declarations and basically two `qlist` declarations.
 ///
 fn parser() -> impl Parser<char, Expression, Error = Simple<char>> {
+    // parses whitespace along with any `;` comments, which run to the end of the line
+    let comment = just(';')
+        .then(filter(|c: &char| *c != '\n').repeated())
+        .ignored();
+    let ws = filter(|c: &char| c.is_whitespace())
+        .ignored()
+        .or(comment)
+        .repeated()
+        .ignored();
+
     // parses a single symbol
     let symbol = filter(is_symbol_fchar)
         .repeated()
         .at_least(1)
         .chain::<char, Vec<_>, _>(filter(is_symbol_rchar).repeated())
-        .padded()
+        .padded_by(ws)
         .collect::<String>()
         .map(Expression::Symbol);
 
+    // parses a `0x` hex or `0b` binary integer
+    let radix_int = |prefix: &'static str, radix: u32| {
+        just(prefix)
+            .ignore_then(text::digits(radix))
+            .map(move |s: String| {
+                let n = s.chars().fold(0.0, |n, c| {
+                    n * radix as f64 + c.to_digit(radix).unwrap() as f64
+                });
+                Expression::Number(n)
+            })
+    };
+
+    // parses the digits of a decimal number, like `12`, `1.5`, `2.`, or `.5`
+    let mantissa = text::int(10)
+        .chain::<char, _, _>(
+            just('.')
+                .chain(filter(|c: &char| c.is_ascii_digit()).repeated())
+                .or_not()
+                .flatten(),
+        )
+        .or(just('.').chain(text::digits(10)));
+
+    // parses the exponent of a number in scientific notation, like `e9` or `E-3`
+    let exponent = just('e')
+        .or(just('E'))
+        .chain(just('+').or(just('-')).or_not())
+        .chain::<char, _, _>(text::digits(10));
+
     // parses a single number
-    let pos_number = text::int(10)
-        .chain::<char, _, _>(just('.').chain(text::digits(10)).or_not().flatten())
+    let pos_number = radix_int("0x", 16).or(radix_int("0b", 2)).or(mantissa
+        .chain::<char, _, _>(exponent.or_not().flatten())
         .collect::<String>()
         .from_str()
         .unwrapped()
-        .map(Expression::Number);
+        .map(Expression::Number));
 
+    // a number has to be followed by whitespace, a comment, `)`, or the end of the input,
+    // so things like `1.5.5` and `0x` are errors rather than two atoms
+    let number_end = filter(|_: &char| true).or_not().rewind();
     let number = filter(|c: &char| *c == '-')
         .repeated()
         .at_least(1)
@@ -85,17 +164,63 @@ fn parser() -> impl Parser<char, Expression, Error = Simple<char>> {
             Expression::Number(n) => Expression::Number(-n),
             _ => e,
         })
-        .or(pos_number);
+        .or(pos_number)
+        .then(number_end)
+        .validate(|(n, next), span: Range<usize>, emit| {
+            if let Some(c) = next.filter(|c| !(c.is_whitespace() || *c == ')' || *c == ';')) {
+                emit(Simple::custom(
+                    span,
+                    format!("expected whitespace or ')' after a number. got '{}'", c),
+                ));
+            }
+            n
+        });
+
+    // parses an escape sequence inside a string, `\xXX` gives the byte with that value.
+    // bytes up to 7f are just ascii chars, anything above that is kept as a raw byte
+    let escape = just('\\').ignore_then(choice((
+        just('\\').to(StrPiece::Char('\\')),
+        just('"').to(StrPiece::Char('"')),
+        just('n').to(StrPiece::Char('\n')),
+        just('t').to(StrPiece::Char('\t')),
+        just('r').to(StrPiece::Char('\r')),
+        just('x').ignore_then(
+            filter(char::is_ascii_hexdigit)
+                .repeated()
+                .exactly(2)
+                .collect::<String>()
+                .map(|s| match u8::from_str_radix(&s, 16).unwrap() {
+                    b if b.is_ascii() => StrPiece::Char(b as char),
+                    b => StrPiece::Byte(b),
+                }),
+        ),
+    )));
 
-    // parses a single string
-    let string = filter(|c: &char| *c != '"')
+    // parses the contents of a string, quotes included
+    let string_body = filter(|c: &char| *c != '"' && *c != '\\')
+        .map(StrPiece::Char)
+        .or(escape)
         .repeated()
-        .delimited_by(just('"'), just('"'))
-        .collect::<String>()
-        .map(Expression::String);
+        .delimited_by(just('"'), just('"'));
+
+    // parses a single string. raw bytes can't live in a `String`, so `\x80` and up are
+    // only allowed in dish literals
+    let string = string_body.validate(|pieces, span: Range<usize>, emit| {
+        let mut s = String::new();
+        for piece in pieces {
+            match piece {
+                StrPiece::Char(c) => s.push(c),
+                StrPiece::Byte(b) => emit(Simple::custom(
+                    span.clone(),
+                    format!("escape \\x{:02x} is only allowed in dish literals", b),
+                )),
+            }
+        }
+        Expression::String(s)
+    });
 
     let byte = text::int::<_, Simple<char>>(10)
-        .padded()
+        .padded_by(ws)
         .try_map(|s, span| {
             s.parse::<u8>()
                 .map_err(|e| Simple::custom(span, format!("{}", e)))
@@ -107,13 +232,25 @@ fn parser() -> impl Parser<char, Expression, Error = Simple<char>> {
         .map(|v| v.iter().map(|n| Expression::Number(*n as f64)).collect())
         .map(Expression::List);
 
-    let dish_literal_str = just('d').ignore_then(string).map(|e| {
-        if let Expression::String(s) = e {
-            let dish = Rc::new(RefCell::new(Dish::from_string(s)));
-            Expression::Dish(dish)
-        } else {
-            panic!("invalid expression passed to dish literal");
+    // a dish literal string with any raw bytes in it becomes a binary dish
+    let dish_literal_str = just('d').ignore_then(string_body).map(|pieces| {
+        let mut data = Vec::new();
+        let mut raw = false;
+        for piece in pieces {
+            match piece {
+                StrPiece::Char(c) => data.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                StrPiece::Byte(b) => {
+                    data.push(b);
+                    raw = true;
+                }
+            }
         }
+        let dish = if raw {
+            Dish::from_bytes(data)
+        } else {
+            Dish::from_string(String::from_utf8(data).unwrap())
+        };
+        Expression::Dish(Rc::new(RefCell::new(dish)))
     });
 
     let dish_literal_vec = just('d').ignore_then(vector).map(|e| {
@@ -149,7 +286,7 @@ fn parser() -> impl Parser<char, Expression, Error = Simple<char>> {
 
     // parses a single list of only atoms
     let list1 = recursive(|list| {
-        list.padded()
+        list.padded_by(ws)
             .repeated()
             .map(Expression::List)
             .delimited_by(just('('), just(')'))
@@ -160,7 +297,7 @@ fn parser() -> impl Parser<char, Expression, Error = Simple<char>> {
     // parses a quoted list
     let qlist = recursive(|qlist| {
         qlist
-            .padded()
+            .padded_by(ws)
             .repeated()
             .map(Expression::List)
             .map(|e| Expression::List(vec![Expression::Symbol("quote".to_string()), e]))
@@ -171,20 +308,35 @@ fn parser() -> impl Parser<char, Expression, Error = Simple<char>> {
     });
 
     // parses a regular list
+    // this is also where the quasiquote (`` ` ``), unquote (`,`), and unquote-splicing (`,@`)
+    // prefixes live, since they can be put in front of any expression
     let list2 = recursive(|list| {
-        list.padded()
+        let quasiquote = just('`')
+            .ignore_then(list.clone())
+            .map(|e| prefixed_form("quasiquote", e));
+        let unquote_splicing = just(",@")
+            .ignore_then(list.clone())
+            .map(|e| prefixed_form("unquote-splicing", e));
+        let unquote = just(',')
+            .ignore_then(list.clone())
+            .map(|e| prefixed_form("unquote", e));
+
+        list.padded_by(ws)
             .repeated()
             .map(Expression::List)
             .delimited_by(just('('), just(')'))
             .or(atom)
             .or(qatom)
             .or(qlist)
+            .or(quasiquote)
+            .or(unquote_splicing)
+            .or(unquote)
     });
 
     // this is basically a superposition of qlist and list
     // this begins parsing from the top and supports quoting things at the top-level
-    recursive(|expr| {
-        expr.padded()
+    let top = recursive(|expr| {
+        expr.padded_by(ws)
             .repeated()
             .map(Expression::List)
             .map(|e| Expression::List(vec![Expression::Symbol("quote".to_string()), e]))
@@ -192,8 +344,25 @@ fn parser() -> impl Parser<char, Expression, Error = Simple<char>> {
             .or(atom)
             .or(qatom)
             .or(list2)
-    })
-    .then_ignore(end())
+    });
+
+    top.padded_by(ws).then_ignore(end())
+}
+
+/// a single piece of a string literal, either a regular char or a raw byte from a `\xXX`
+/// escape above 7f
+///
+#[derive(Clone, Copy)]
+enum StrPiece {
+    Char(char),
+    Byte(u8),
+}
+
+/// helper function that turns the expression after a prefix like `` ` `` into
+/// the list `(name expr)`
+///
+fn prefixed_form(name: &str, expr: Expression) -> Expression {
+    Expression::List(vec![Expression::Symbol(name.to_string()), expr])
 }
 
 /// predicate of whether or not a character can be the first character of a symbol name
@@ -208,8 +377,8 @@ fn is_symbol_rchar(c: &char) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::lisp::{Expression, Reader};
-    use crate::Dish;
+    use crate::lisp::{default_reader, Expression, Reader};
+    use crate::{Dish, DishData};
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -225,6 +394,46 @@ mod tests {
         assert!(matches!(reader.parse(&expr2), Ok(_exp2)));
     }
 
+    #[test]
+    fn test_reader_string_escapes() {
+        let reader = Reader::new();
+        let parse = |s: &str| reader.parse(&s.to_string()).unwrap();
+
+        assert!(parse(r#""a\"b""#) == Expression::String("a\"b".to_string()));
+        assert!(parse(r#""\x41""#) == Expression::String("A".to_string()));
+        assert!(parse(r#""\n\t\r\\""#) == Expression::String("\n\t\r\\".to_string()));
+        assert!(reader.parse(&r#""\q""#.to_string()).is_err());
+        assert!(reader.parse(&r#""\x4""#.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_reader_error_position() {
+        let reader = Reader::new();
+        let err = reader.parse(&"(+ 1 2))".to_string()).err().unwrap();
+        assert_eq!(format!("{}", err), "parse error at 1:8: unexpected ')'");
+
+        let err = reader
+            .parse(&"(def a\n  \"b\\q\")".to_string())
+            .err()
+            .unwrap();
+        assert!(format!("{}", err).starts_with("parse error at 2:6:"));
+
+        // leading whitespace still counts towards the position
+        let err = reader.parse(&"\n  (+ 1 2))".to_string()).err().unwrap();
+        assert_eq!(format!("{}", err), "parse error at 2:10: unexpected ')'");
+    }
+
+    #[test]
+    fn test_reader_comments() {
+        let reader = Reader::new();
+        let src = "; a recipe with comments\n(recipe ; the first op\n  (rot13 13) ; \"(\n  d[65 ; A\n 66])\n; trailing";
+        let parsed = reader.parse(&src.to_string()).unwrap();
+        assert_eq!(format!("{}", parsed), "(recipe (rot13 13) Dish([AB]))");
+
+        let expected = Expression::String("a ; b".to_string());
+        assert!(reader.parse(&"\"a ; b\" ; c".to_string()).unwrap() == expected);
+    }
+
     #[test]
     fn test_reader_number() {
         let reader = Reader::new();
@@ -240,6 +449,56 @@ mod tests {
         assert!(matches!(reader.parse(&expr3), Ok(_exp3)));
     }
 
+    #[test]
+    fn test_reader_float_syntax() {
+        let reader = Reader::new();
+        let parse = |s: &str| reader.parse(&s.to_string()).unwrap();
+
+        assert!(parse("1e3") == Expression::Number(1000.0));
+        assert!(parse("1E3") == Expression::Number(1000.0));
+        assert!(parse("2.5e-1") == Expression::Number(0.25));
+        assert!(parse("1e+2") == Expression::Number(100.0));
+        assert!(parse(".5") == Expression::Number(0.5));
+        assert!(parse("-.5") == Expression::Number(-0.5));
+        assert!(parse("2.") == Expression::Number(2.0));
+        assert!(parse("1e9") == Expression::Number(1e9));
+
+        // a lone `.` still isn't a number, and `e3` is still a symbol
+        assert!(reader.parse(&".".to_string()).is_err());
+        assert!(parse("e3") == Expression::Symbol("e3".to_string()));
+        assert_eq!(format!("{}", parse("(+ 1e1 .5)")), "(+ 10 0.5)");
+        assert_eq!(format!("{}", parse("(+ 1 2);c")), "(+ 1 2)");
+    }
+
+    #[test]
+    fn test_reader_number_needs_delimiter() {
+        let reader = Reader::new();
+        for src in [
+            "1.5.5",
+            "1..5",
+            "0x",
+            "1e",
+            "(+ 1.5.5 0)",
+            "(+ 0x 1)",
+            "12abc",
+        ] {
+            assert!(reader.parse(&src.to_string()).is_err(), "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_reader_radix_number() {
+        let reader = Reader::new();
+        let parse = |s: &str| reader.parse(&s.to_string()).unwrap();
+
+        assert!(parse("0xFF") == Expression::Number(255.0));
+        assert!(parse("0x1f") == Expression::Number(31.0));
+        assert!(parse("0b1010") == Expression::Number(10.0));
+        assert!(parse("-0xff") == Expression::Number(-255.0));
+        assert!(parse("0") == Expression::Number(0.0));
+        assert!(reader.parse(&"0b102".to_string()).is_err());
+    }
+
     #[test]
     fn test_reader_list() {
         let reader = Reader::new();
@@ -271,6 +530,16 @@ mod tests {
         assert!(matches!(reader.parse(&expr2), Ok(_exp2)));
     }
 
+    #[test]
+    fn test_reader_quasiquote() {
+        let reader = Reader::new();
+        let parsed = reader.parse(&"`(1 ,(+ 1 1) ,@rest)".to_string()).unwrap();
+        assert_eq!(
+            format!("{}", parsed),
+            "(quasiquote (1 (unquote (+ 1 1)) (unquote-splicing rest)))"
+        );
+    }
+
     #[test]
     fn test_reader_dish_literal() {
         let reader = Reader::new();
@@ -284,4 +553,42 @@ mod tests {
         assert!(matches!(reader.parse(&expr1), Ok(_exp1)));
         assert!(matches!(reader.parse(&expr2), Ok(_exp2)));
     }
+
+    #[test]
+    fn test_reader_raw_byte_escapes() {
+        let reader = Reader::new();
+        let parse_dish = |s: &str| match reader.parse(&s.to_string()).unwrap() {
+            Expression::Dish(d) => d.borrow().data().unwrap().clone(),
+            _ => panic!("expected a dish literal"),
+        };
+
+        assert_eq!(parse_dish(r#"d"a\x41""#), DishData::Str("aA".to_string()));
+        assert_eq!(
+            parse_dish(r#"d"é\x80\xff""#),
+            DishData::Bin(vec![0xc3, 0xa9, 0x80, 0xff])
+        );
+
+        let err = reader.parse(&r#""\x80""#.to_string()).err().unwrap();
+        assert!(format!("{}", err).contains("only allowed in dish literals"));
+    }
+
+    #[test]
+    fn test_default_reader() {
+        // the same reader comes back every time on a thread
+        assert!(Rc::ptr_eq(&default_reader(), &default_reader()));
+        assert_eq!(
+            format!(
+                "{}",
+                default_reader().parse(&"(+ 1 2)".to_string()).unwrap()
+            ),
+            "(+ 1 2)"
+        );
+
+        // and each thread gets its own
+        let here = default_reader();
+        let there = std::thread::spawn(|| Rc::as_ptr(&default_reader()) as usize)
+            .join()
+            .unwrap();
+        assert_ne!(Rc::as_ptr(&here) as usize, there);
+    }
 }
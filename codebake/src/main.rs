@@ -1,6 +1,23 @@
-use codebake::lisp;
+use codebake::{lisp, ops};
 fn main() {
-    // popy
-    let mut codebake = lisp::Interpreter::default();
-    codebake.run_repl();
+    // `--dump-ops` prints the operation reference as JSON for other tools
+    if std::env::args().skip(1).any(|arg| arg == "--dump-ops") {
+        println!("{}", ops::operations_json());
+        return;
+    }
+
+    // evaluation recurses on the native stack, so the REPL gets a thread
+    // with enough stack for the default depth limit
+    let repl = std::thread::Builder::new()
+        .stack_size(lisp::EVAL_STACK_SIZE)
+        .spawn(|| {
+            // popy
+            let mut codebake = lisp::Interpreter::default();
+            codebake.run_repl();
+        })
+        .expect("failed to start the interpreter thread");
+
+    if repl.join().is_err() {
+        std::process::exit(1);
+    }
 }
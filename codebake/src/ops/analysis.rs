@@ -0,0 +1,104 @@
+use crate::{DishData, DishError, DishResult, OperationArgType, OperationArguments, OperationInfo};
+
+/// characters used to draw the entropy sparkline, from lowest to highest
+static SPARK_CHARS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// helper function that computes the shannon entropy of a byte slice in bits per byte
+/// the result is always in the range 0.0..=8.0
+///
+pub(crate) fn shannon_entropy(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for b in bytes {
+        counts[*b as usize] += 1;
+    }
+
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = *c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+pub static OPINFO_ENTROPY_MAP: OperationInfo = OperationInfo {
+    name: "entropy-map",
+    description: "draws a sparkline of the entropy of each `window`-sized block of the input",
+    authors: &["s-viour"],
+    category: "Analysis",
+    arguments: &[("window", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: entropy_map,
+};
+
+fn entropy_map(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let window = args.get_integer("window")?;
+    if window <= 0 {
+        return Err(DishError::invalid_argument("window size must be positive"));
+    }
+
+    let spark: String = dish
+        .as_bytes()
+        .chunks(window as usize)
+        .map(|chunk| {
+            // scale 0..=8 bits onto the available characters
+            let idx = (shannon_entropy(chunk) / 8.0 * (SPARK_CHARS.len() - 1) as f64).round();
+            SPARK_CHARS[idx as usize]
+        })
+        .collect();
+
+    *dish = DishData::Str(spark);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::analysis::*;
+    use crate::DishData;
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(&[]), 0.0);
+        assert_eq!(shannon_entropy(&[0; 64]), 0.0);
+        assert_eq!(shannon_entropy(&[0, 1]), 1.0);
+
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(shannon_entropy(&all), 8.0);
+    }
+
+    #[test]
+    fn test_entropy_map_step() {
+        // half zeroes, half pseudo-random bytes from a simple LCG
+        let mut data = vec![0u8; 4096];
+        let mut state: u32 = 0x1234_5678;
+        for _ in 0..4096 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            data.push((state >> 16) as u8);
+        }
+
+        let mut dish = DishData::Bin(data);
+        let mut args = OperationArguments::new();
+        args.insert("window", 512);
+        assert!(matches!(entropy_map(&args, &mut dish), Ok(())));
+
+        let spark: Vec<char> = match dish {
+            DishData::Str(s) => s.chars().collect(),
+            DishData::Bin(_) => panic!("expected a string dish"),
+        };
+        assert_eq!(spark.len(), 16);
+        assert!(spark[..8].iter().all(|c| *c == '▁'));
+        assert!(spark[8..].iter().all(|c| *c == '█' || *c == '▇'));
+
+        let mut args = OperationArguments::new();
+        args.insert("window", 0);
+        let mut dish = DishData::Bin(vec![1, 2, 3]);
+        assert!(entropy_map(&args, &mut dish).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{DishData, DishError, DishResult, OperationArguments, OperationInfo};
+
+/// helper function that reads the digits of a string dish, ignoring
+/// surrounding whitespace. anything else that isn't a digit is an error
+///
+fn digits_helper(dish: &DishData) -> Result<Vec<u32>, DishError> {
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+    if data.is_empty() {
+        return Err(DishError::decode("expected a number. got nothing"));
+    }
+
+    data.chars()
+        .map(|c| {
+            c.to_digit(10)
+                .ok_or_else(|| DishError::decode(format!("`{}` is not a digit", c)))
+        })
+        .collect()
+}
+
+/// helper function that computes the Luhn sum of `digits` modulo 10,
+/// doubling every second digit starting from the rightmost one's neighbor.
+/// a number with its check digit is valid when this is 0
+///
+fn luhn_checksum(digits: &[u32]) -> u32 {
+    digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| match i % 2 {
+            0 => *d,
+            _ => {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            }
+        })
+        .sum::<u32>()
+        % 10
+}
+
+pub static OPINFO_LUHN_VALIDATE: OperationInfo = OperationInfo {
+    name: "luhn-validate",
+    description: "checks a number (like a credit card or IMEI) with the Luhn algorithm. gives `valid` or `invalid`",
+    authors: &["s-viour"],
+    category: "Checksums",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: luhn_validate,
+};
+
+fn luhn_validate(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let digits = digits_helper(dish)?;
+    let result = match luhn_checksum(&digits) {
+        0 => "valid",
+        _ => "invalid",
+    };
+
+    *dish = DishData::Str(result.to_string());
+    Ok(())
+}
+
+pub static OPINFO_LUHN_GENERATE: OperationInfo = OperationInfo {
+    name: "luhn-generate",
+    description: "appends the Luhn check digit to a number",
+    authors: &["s-viour"],
+    category: "Checksums",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: luhn_generate,
+};
+
+fn luhn_generate(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let mut digits = digits_helper(dish)?;
+    // with a placeholder 0 on the end, the check digit is whatever brings the sum to 0
+    digits.push(0);
+    let check = (10 - luhn_checksum(&digits)) % 10;
+    digits.pop();
+    digits.push(check);
+
+    *dish = DishData::Str(digits.iter().map(|d| d.to_string()).collect());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::checksums::*;
+    use crate::{DishData, EMPTY_ARGS};
+
+    // (number without its check digit, check digit)
+    static LUHN_VECTORS: &[(&str, &str)] = &[
+        ("7992739871", "3"),
+        ("411111111111111", "1"),
+        ("37828224631000", "5"),
+        ("49015420323751", "8"),
+        ("0", "0"),
+    ];
+
+    #[test]
+    fn test_luhn_generate() {
+        for (number, check) in LUHN_VECTORS {
+            let mut data = DishData::Str(number.to_string());
+            assert!(matches!(luhn_generate(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(format!("{}{}", number, check)));
+        }
+    }
+
+    #[test]
+    fn test_luhn_validate() {
+        for (number, check) in LUHN_VECTORS {
+            let mut data = DishData::Str(format!(" {}{}\n", number, check));
+            assert!(matches!(luhn_validate(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str("valid".to_string()));
+        }
+
+        let mut data = DishData::Str("79927398710".to_string());
+        assert!(matches!(luhn_validate(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("invalid".to_string()));
+
+        for input in ["4111 1111 1111 1111", "12a4", ""] {
+            let mut data = DishData::Str(input.to_string());
+            assert!(luhn_validate(&EMPTY_ARGS, &mut data).is_err());
+            assert!(luhn_generate(&EMPTY_ARGS, &mut data).is_err());
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use crate::{DishData, DishError, DishResult, OperationArgType, OperationArguments, OperationInfo};
+
+/// helper function that appends PKCS#7 padding to `data` for the given block size
+/// a full block of padding is added when the data is already aligned
+///
+fn pkcs7_pad_helper(data: &mut Vec<u8>, block_size: usize) {
+    let n = block_size - data.len() % block_size;
+    data.resize(data.len() + n, n as u8);
+}
+
+/// helper function that validates and strips PKCS#7 padding from `data`
+///
+fn pkcs7_unpad_helper(data: &mut Vec<u8>) -> Result<(), DishError> {
+    let n = match data.last() {
+        Some(n) => *n as usize,
+        None => return Err(DishError::decode("cannot unpad empty data")),
+    };
+
+    if n == 0 || n > data.len() || data[data.len() - n..].iter().any(|b| *b as usize != n) {
+        return Err(DishError::decode("invalid PKCS#7 padding"));
+    }
+
+    data.truncate(data.len() - n);
+    Ok(())
+}
+
+pub static OPINFO_PKCS7_PAD: OperationInfo = OperationInfo {
+    name: "pkcs7-pad",
+    description: "pads the input to a multiple of `block_size` bytes using PKCS#7",
+    authors: &["s-viour"],
+    category: "Crypto",
+    arguments: &[("block_size", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: pkcs7_pad,
+};
+
+fn pkcs7_pad(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let block_size = args.get_integer("block_size")?;
+    if !(1..=255).contains(&block_size) {
+        return Err(DishError::invalid_argument(format!(
+            "block size must be between 1 and 255. got {}",
+            block_size
+        )));
+    }
+
+    let mut v = dish.as_bytes().to_vec();
+    pkcs7_pad_helper(&mut v, block_size as usize);
+    *dish = DishData::Bin(v);
+    Ok(())
+}
+
+pub static OPINFO_PKCS7_UNPAD: OperationInfo = OperationInfo {
+    name: "pkcs7-unpad",
+    description: "validates and removes PKCS#7 padding from the input",
+    authors: &["s-viour"],
+    category: "Crypto",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: pkcs7_unpad,
+};
+
+fn pkcs7_unpad(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let mut v = dish.as_bytes().to_vec();
+    pkcs7_unpad_helper(&mut v)?;
+    *dish = DishData::Bin(v);
+    Ok(())
+}
+
+/// the AES substitution box
+static SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// the inverse of `SBOX`, computed at compile time
+static INV_SBOX: [u8; 256] = invert_sbox(&SBOX);
+
+const fn invert_sbox(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut inv = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        inv[sbox[i] as usize] = i as u8;
+        i += 1;
+    }
+    inv
+}
+
+/// size of an AES block in bytes
+const AES_BLOCK_SIZE: usize = 16;
+
+/// the modes of operation supported by `aes-encrypt` and `aes-decrypt`
+static AES_MODES: &[&str] = &["cbc", "ecb", "ctr"];
+
+/// multiplies by x (i.e `{02}`) in GF(2^8)
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// multiplies two numbers in GF(2^8)
+fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut p = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            p ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    p
+}
+
+/// An expanded AES key, ready to encrypt and decrypt single blocks
+///
+struct Aes {
+    round_keys: Vec<[u8; AES_BLOCK_SIZE]>,
+}
+
+impl Aes {
+    /// Expands a 16, 24, or 32 byte key
+    fn new(key: &[u8]) -> Result<Aes, DishError> {
+        let nk = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            n => {
+                return Err(DishError::invalid_argument(format!(
+                    "invalid AES key length {}. (must be 16, 24, or 32 bytes)",
+                    n
+                )))
+            }
+        };
+        let rounds = nk + 6;
+
+        let mut words: Vec<[u8; 4]> = key.chunks(4).map(|c| [c[0], c[1], c[2], c[3]]).collect();
+        let mut rcon = 1;
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                temp = temp.map(|b| SBOX[b as usize]);
+                temp[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                temp = temp.map(|b| SBOX[b as usize]);
+            }
+            let prev = words[i - nk];
+            words.push([
+                prev[0] ^ temp[0],
+                prev[1] ^ temp[1],
+                prev[2] ^ temp[2],
+                prev[3] ^ temp[3],
+            ]);
+        }
+
+        let round_keys = words
+            .chunks(4)
+            .map(|w| {
+                let mut k = [0; AES_BLOCK_SIZE];
+                for (i, word) in w.iter().enumerate() {
+                    k[4 * i..4 * i + 4].copy_from_slice(word);
+                }
+                k
+            })
+            .collect();
+
+        Ok(Aes { round_keys })
+    }
+
+    fn add_round_key(state: &mut [u8; AES_BLOCK_SIZE], key: &[u8; AES_BLOCK_SIZE]) {
+        state.iter_mut().zip(key).for_each(|(s, k)| *s ^= k);
+    }
+
+    /// the state is stored column-major, so byte `r + 4c` is row `r` of column `c`
+    fn shift_rows(state: &mut [u8; AES_BLOCK_SIZE], inverse: bool) {
+        let old = *state;
+        for r in 1..4 {
+            for c in 0..4 {
+                let from = if inverse {
+                    (c + 4 - r) % 4
+                } else {
+                    (c + r) % 4
+                };
+                state[r + 4 * c] = old[r + 4 * from];
+            }
+        }
+    }
+
+    fn mix_columns(state: &mut [u8; AES_BLOCK_SIZE], inverse: bool) {
+        let m: [u8; 4] = if inverse {
+            [14, 11, 13, 9]
+        } else {
+            [2, 3, 1, 1]
+        };
+        for col in state.chunks_mut(4) {
+            let a = [col[0], col[1], col[2], col[3]];
+            for (r, out) in col.iter_mut().enumerate() {
+                *out = (0..4).fold(0, |acc, i| acc ^ gmul(a[(r + i) % 4], m[i]));
+            }
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8; AES_BLOCK_SIZE]) {
+        let rounds = self.round_keys.len() - 1;
+        Aes::add_round_key(block, &self.round_keys[0]);
+        for round in 1..=rounds {
+            block.iter_mut().for_each(|b| *b = SBOX[*b as usize]);
+            Aes::shift_rows(block, false);
+            if round != rounds {
+                Aes::mix_columns(block, false);
+            }
+            Aes::add_round_key(block, &self.round_keys[round]);
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; AES_BLOCK_SIZE]) {
+        let rounds = self.round_keys.len() - 1;
+        Aes::add_round_key(block, &self.round_keys[rounds]);
+        for round in (0..rounds).rev() {
+            Aes::shift_rows(block, true);
+            block.iter_mut().for_each(|b| *b = INV_SBOX[*b as usize]);
+            Aes::add_round_key(block, &self.round_keys[round]);
+            if round != 0 {
+                Aes::mix_columns(block, true);
+            }
+        }
+    }
+}
+
+/// helper function that parses the `key`, `iv`, and `mode` arguments shared by
+/// `aes-encrypt` and `aes-decrypt`. the iv is ignored in `ecb` mode
+///
+fn aes_args(args: &OperationArguments) -> Result<(Aes, [u8; AES_BLOCK_SIZE], String), DishError> {
+    let aes = Aes::new(&args.get_bytes("key")?)?;
+    let mode = args.get_string("mode")?;
+    let iv = args.get_bytes("iv")?;
+
+    let mut iv_block = [0; AES_BLOCK_SIZE];
+    if mode != "ecb" {
+        if iv.len() != AES_BLOCK_SIZE {
+            return Err(DishError::invalid_argument(format!(
+                "invalid IV length {}. ({} mode requires {} bytes)",
+                iv.len(),
+                mode,
+                AES_BLOCK_SIZE
+            )));
+        }
+        iv_block.copy_from_slice(&iv);
+    }
+
+    Ok((aes, iv_block, mode))
+}
+
+/// helper function for CTR mode, which is the same in both directions
+///
+fn aes_ctr(aes: &Aes, mut counter: [u8; AES_BLOCK_SIZE], data: &mut [u8]) {
+    for chunk in data.chunks_mut(AES_BLOCK_SIZE) {
+        let mut keystream = counter;
+        aes.encrypt_block(&mut keystream);
+        chunk.iter_mut().zip(keystream).for_each(|(b, k)| *b ^= k);
+
+        // the whole counter block is one big-endian number
+        for b in counter.iter_mut().rev() {
+            *b = b.wrapping_add(1);
+            if *b != 0 {
+                break;
+            }
+        }
+    }
+}
+
+pub static OPINFO_AES_ENCRYPT: OperationInfo = OperationInfo {
+    name: "aes-encrypt",
+    description: "encrypts the input with AES. cbc and ecb modes use PKCS#7 padding, and the iv is ignored in ecb mode",
+    authors: &["s-viour"],
+    category: "Crypto",
+    arguments: &[
+        ("key", OperationArgType::Bytes),
+        ("iv", OperationArgType::Bytes),
+        ("mode", OperationArgType::Choice(AES_MODES)),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: aes_encrypt,
+};
+
+fn aes_encrypt(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let (aes, iv, mode) = aes_args(args)?;
+    let mut data = dish.as_bytes().to_vec();
+
+    match mode.as_ref() {
+        "ctr" => aes_ctr(&aes, iv, &mut data),
+        _ => {
+            pkcs7_pad_helper(&mut data, AES_BLOCK_SIZE);
+            let mut prev = iv;
+            for chunk in data.chunks_mut(AES_BLOCK_SIZE) {
+                let mut block = [0; AES_BLOCK_SIZE];
+                block.copy_from_slice(chunk);
+                if mode == "cbc" {
+                    block.iter_mut().zip(prev).for_each(|(b, p)| *b ^= p);
+                }
+                aes.encrypt_block(&mut block);
+                chunk.copy_from_slice(&block);
+                prev = block;
+            }
+        }
+    }
+
+    *dish = DishData::Bin(data);
+    Ok(())
+}
+
+pub static OPINFO_AES_DECRYPT: OperationInfo = OperationInfo {
+    name: "aes-decrypt",
+    description: "decrypts AES encrypted input. cbc and ecb modes expect PKCS#7 padding, and the iv is ignored in ecb mode",
+    authors: &["s-viour"],
+    category: "Crypto",
+    arguments: &[
+        ("key", OperationArgType::Bytes),
+        ("iv", OperationArgType::Bytes),
+        ("mode", OperationArgType::Choice(AES_MODES)),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: aes_decrypt,
+};
+
+fn aes_decrypt(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let (aes, iv, mode) = aes_args(args)?;
+    let mut data = dish.as_bytes().to_vec();
+
+    match mode.as_ref() {
+        "ctr" => aes_ctr(&aes, iv, &mut data),
+        _ => {
+            if data.is_empty() || !data.len().is_multiple_of(AES_BLOCK_SIZE) {
+                return Err(DishError::decode(format!(
+                    "ciphertext length {} is not a multiple of the block size",
+                    data.len()
+                )));
+            }
+
+            let mut prev = iv;
+            for chunk in data.chunks_mut(AES_BLOCK_SIZE) {
+                let mut block = [0; AES_BLOCK_SIZE];
+                block.copy_from_slice(chunk);
+                let ciphertext = block;
+                aes.decrypt_block(&mut block);
+                if mode == "cbc" {
+                    block.iter_mut().zip(prev).for_each(|(b, p)| *b ^= p);
+                }
+                chunk.copy_from_slice(&block);
+                prev = ciphertext;
+            }
+            pkcs7_unpad_helper(&mut data)?;
+        }
+    }
+
+    *dish = DishData::Bin(data);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::crypto::*;
+    use crate::{DishData, EMPTY_ARGS};
+
+    fn block_args(block_size: i64) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("block_size", block_size);
+        args
+    }
+
+    #[test]
+    fn test_pkcs7_pad() {
+        let mut data = DishData::Str("YELLOW SUBMARINE".to_string());
+        assert!(matches!(pkcs7_pad(&block_args(20), &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Bin(b"YELLOW SUBMARINE\x04\x04\x04\x04".to_vec())
+        );
+
+        assert!(matches!(pkcs7_unpad(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(b"YELLOW SUBMARINE".to_vec()));
+    }
+
+    #[test]
+    fn test_pkcs7_pad_aligned() {
+        // already aligned data always gets a full block of padding
+        let mut data = DishData::Bin(vec![1; 8]);
+        assert!(matches!(pkcs7_pad(&block_args(8), &mut data), Ok(())));
+        let mut expected = vec![1; 8];
+        expected.extend([8; 8]);
+        assert_eq!(data, DishData::Bin(expected));
+
+        assert!(matches!(pkcs7_unpad(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(vec![1; 8]));
+
+        let mut data = DishData::Bin(vec![]);
+        assert!(matches!(pkcs7_pad(&block_args(4), &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(vec![4; 4]));
+    }
+
+    #[test]
+    fn test_pkcs7_invalid() {
+        for size in [0, 256, -4] {
+            let mut data = DishData::Bin(vec![1, 2, 3]);
+            assert!(pkcs7_pad(&block_args(size), &mut data).is_err());
+        }
+
+        for bad in [vec![], vec![1, 2, 0], vec![1, 3, 2], vec![5, 5, 5]] {
+            let mut data = DishData::Bin(bad);
+            assert!(pkcs7_unpad(&EMPTY_ARGS, &mut data).is_err());
+        }
+    }
+
+    fn from_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn aes_args(key: &str, iv: &str, mode: &str) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("key", from_hex(key));
+        args.insert("iv", from_hex(iv));
+        args.insert("mode", mode.to_string());
+        args
+    }
+
+    // NIST SP 800-38A test vectors
+    static NIST_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    static NIST_PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+                                   30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+    #[test]
+    fn test_aes_128_cbc_known_answer() {
+        let args = aes_args(NIST_KEY, "000102030405060708090a0b0c0d0e0f", "cbc");
+        let mut data = DishData::Bin(from_hex(NIST_PLAINTEXT));
+        assert!(matches!(aes_encrypt(&args, &mut data), Ok(())));
+
+        let expected = from_hex(
+            "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2\
+             73bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7",
+        );
+        // the final block is PKCS#7 padding
+        assert_eq!(data.as_bytes().len(), 80);
+        assert_eq!(&data.as_bytes()[..64], &expected[..]);
+
+        assert!(matches!(aes_decrypt(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(from_hex(NIST_PLAINTEXT)));
+    }
+
+    #[test]
+    fn test_aes_128_ecb_known_answer() {
+        let args = aes_args(NIST_KEY, "", "ecb");
+        let mut data = DishData::Bin(from_hex(NIST_PLAINTEXT));
+        assert!(matches!(aes_encrypt(&args, &mut data), Ok(())));
+        assert_eq!(
+            &data.as_bytes()[..16],
+            &from_hex("3ad77bb40d7a3660a89ecaf32466ef97")[..]
+        );
+
+        assert!(matches!(aes_decrypt(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(from_hex(NIST_PLAINTEXT)));
+    }
+
+    #[test]
+    fn test_aes_128_ctr_known_answer() {
+        let args = aes_args(NIST_KEY, "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff", "ctr");
+        let mut data = DishData::Bin(from_hex(NIST_PLAINTEXT));
+        assert!(matches!(aes_encrypt(&args, &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Bin(from_hex(
+                "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+                 5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee"
+            ))
+        );
+
+        assert!(matches!(aes_decrypt(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(from_hex(NIST_PLAINTEXT)));
+    }
+
+    #[test]
+    fn test_aes_192_256_blocks() {
+        // FIPS-197 appendix C
+        let plaintext = from_hex("00112233445566778899aabbccddeeff");
+        let cases = [
+            (
+                "000102030405060708090a0b0c0d0e0f1011121314151617",
+                "dda97ca4864cdfe06eaf70a0ec0d7191",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                "8ea2b7ca516745bfeafc49904b496089",
+            ),
+        ];
+
+        for (key, expected) in cases {
+            let aes = Aes::new(&from_hex(key)).unwrap();
+            let mut block = [0; AES_BLOCK_SIZE];
+            block.copy_from_slice(&plaintext);
+            aes.encrypt_block(&mut block);
+            assert_eq!(&block[..], &from_hex(expected)[..]);
+
+            aes.decrypt_block(&mut block);
+            assert_eq!(&block[..], &plaintext[..]);
+        }
+    }
+
+    #[test]
+    fn test_aes_invalid_lengths() {
+        let mut data = DishData::Str("hello".to_string());
+        let args = aes_args("2b7e1516", "000102030405060708090a0b0c0d0e0f", "cbc");
+        assert!(aes_encrypt(&args, &mut data).is_err());
+
+        let args = aes_args(NIST_KEY, "0001", "cbc");
+        assert!(aes_encrypt(&args, &mut data).is_err());
+
+        let args = aes_args(NIST_KEY, "000102030405060708090a0b0c0d0e0f", "cbc");
+        let mut data = DishData::Bin(vec![0; 15]);
+        assert!(aes_decrypt(&args, &mut data).is_err());
+    }
+}
@@ -1,58 +1,95 @@
 use crate::{
-    DishData, DishError, DishResult, OperationArgType, OperationArguments, OperationInfo,
-    EMPTY_ARGS,
+    DishData, DishError, DishResult, OperationArgDefault, OperationArgType, OperationArguments,
+    OperationInfo, EMPTY_ARGS,
 };
 use base64;
+use lazy_static::lazy_static;
 use regex::Regex;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+static BASE64_VARIANTS: &[&str] = &["standard", "url-safe", "url-safe-no-pad"];
 
 pub static OPINFO_FROMBASE64: OperationInfo = OperationInfo {
     name: "from-base64",
-    description: "converts from base64",
+    description: "converts from base64. the `url-safe` variants use `-` and `_` in place of `+` and `/`, and don't need padding",
     authors: &["s-viour"],
     category: "Data Format",
-    arguments: &[],
+    arguments: &[("variant", OperationArgType::Choice(BASE64_VARIANTS))],
+    defaults: &[("variant", OperationArgDefault::String("standard"))],
+    aliases: &["from-b64", "from-base-64"],
     op: from_base64,
 };
 
-fn from_base64(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+fn from_base64(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let variant = args.get_string("variant")?;
     let data = match dish {
         DishData::Str(s) => s.as_bytes(),
         DishData::Bin(_) => {
-            return Err(DishError(
-                "cannot convert binary data from base64".to_string(),
+            return Err(DishError::wrong_type(
+                "cannot convert binary data from base64",
             ))
         }
     };
 
-    match base64::decode(data) {
+    let res = match variant.as_ref() {
+        "standard" => base64::decode(data),
+        // padding is optional in url-safe data, so it's dropped before decoding
+        _ => {
+            let unpadded = data.strip_suffix(b"==").or_else(|| data.strip_suffix(b"="));
+            base64::decode_config(unpadded.unwrap_or(data), base64::URL_SAFE_NO_PAD)
+        }
+    };
+    match res {
         Ok(d) => {
             *dish = DishData::Bin(d);
             Ok(())
         }
-        Err(e) => Err(DishError(format!("base64 decode error: {}", e))),
+        Err(e) => Err(DishError::decode(format!("base64 decode error: {}", e))),
     }
 }
 
 pub static OPINFO_TOBASE64: OperationInfo = OperationInfo {
     name: "to-base64",
-    description: "converts to base64",
+    description: "converts to base64, in the `standard`, `url-safe`, or `url-safe-no-pad` variant. trailing `=` padding is left off when `pad` is 0",
     authors: &["s-viour"],
     category: "Data Format",
-    arguments: &[],
+    arguments: &[
+        ("variant", OperationArgType::Choice(BASE64_VARIANTS)),
+        ("pad", OperationArgType::Integer),
+    ],
+    defaults: &[
+        ("variant", OperationArgDefault::String("standard")),
+        ("pad", OperationArgDefault::Integer(1)),
+    ],
+    aliases: &["to-b64", "to-base-64"],
     op: to_base64,
 };
 
-fn to_base64(_: &OperationArguments, dish: &mut DishData) -> DishResult {
-    *dish = DishData::Str(base64::encode(dish.as_bytes()));
+fn to_base64(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let config = match args.get_string("variant")?.as_ref() {
+        "url-safe" => base64::URL_SAFE,
+        "url-safe-no-pad" => base64::URL_SAFE_NO_PAD,
+        _ => base64::STANDARD,
+    };
+    // `url-safe-no-pad` never pads, whatever `pad` is
+    let config = if args.get_integer("pad")? == 0 {
+        config.pad(false)
+    } else {
+        config
+    };
+    *dish = DishData::Str(base64::encode_config(dish.as_bytes(), config));
     Ok(())
 }
 
 pub static OPINFO_FROMDECIMAL: OperationInfo = OperationInfo {
     name: "from-decimal",
-    description: "converts a decimal-encoded string to its raw form",
+    description: "converts a decimal-encoded string to its raw form. bytes may be separated by spaces, `:`, `-`, or `,`",
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[],
+    defaults: &[],
+    aliases: &[],
     op: from_decimal,
 };
 
@@ -62,31 +99,29 @@ fn from_decimal(_: &OperationArguments, dish: &mut DishData) -> DishResult {
 
 pub static OPINFO_TODECIMAL: OperationInfo = OperationInfo {
     name: "to-decimal",
-    description: "converts data to a decimal string",
+    description: "converts data to a decimal string. bytes aren't padded, so keep a `delimiter` to read them back with `from-decimal`",
     authors: &["s-viour"],
     category: "Data Format",
-    arguments: &[],
+    arguments: &[("delimiter", OperationArgType::String)],
+    defaults: &[("delimiter", OperationArgDefault::String(" "))],
+    aliases: &[],
     op: to_decimal,
 };
 
-fn to_decimal(_: &OperationArguments, dish: &mut DishData) -> DishResult {
-    *dish = DishData::Str(
-        dish.as_bytes()
-            .iter()
-            .map(|x| x.to_string())
-            .collect::<Vec<String>>()
-            .join(" "),
-    );
-
+fn to_decimal(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = args.get_string("delimiter")?;
+    *dish = DishData::Str(to_radix_string(dish.as_bytes(), 10, false, &delimiter));
     Ok(())
 }
 
 pub static OPINFO_FROMOCTAL: OperationInfo = OperationInfo {
     name: "from-octal",
-    description: "converts an octal-encoded string to its raw form",
+    description: "converts an octal-encoded string to its raw form. bytes may be separated by spaces, `:`, `-`, or `,`",
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[],
+    defaults: &[],
+    aliases: &[],
     op: from_octal,
 };
 
@@ -96,36 +131,69 @@ fn from_octal(_: &OperationArguments, dish: &mut DishData) -> DishResult {
 
 pub static OPINFO_TOOCTAL: OperationInfo = OperationInfo {
     name: "to-octal",
-    description: "converts data to an octal string",
+    description: "converts data to an octal string. bytes aren't padded, so keep a `delimiter` to read them back with `from-octal`",
     authors: &["s-viour"],
     category: "Data Format",
-    arguments: &[],
+    arguments: &[("delimiter", OperationArgType::String)],
+    defaults: &[("delimiter", OperationArgDefault::String(" "))],
+    aliases: &[],
     op: to_octal,
 };
 
-fn to_octal(_: &OperationArguments, dish: &mut DishData) -> DishResult {
-    *dish = DishData::Str(
-        dish.as_bytes()
-            .iter()
-            .map(|x| format!("{:o}", x))
-            .collect::<Vec<String>>()
-            .join(" "),
-    );
-
+fn to_octal(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = args.get_string("delimiter")?;
+    *dish = DishData::Str(to_radix_string(dish.as_bytes(), 8, false, &delimiter));
     Ok(())
 }
 
 pub static OPINFO_FROMHEX: OperationInfo = OperationInfo {
     name: "from-hex",
-    description: "converts a hexadecimal encoded string into its raw form",
+    description: "converts a hexadecimal encoded string into its raw form. bytes may be separated by spaces, `:`, `-`, `,`, or nothing, and may start with `0x`",
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[],
+    defaults: &[],
+    aliases: &[],
     op: from_hex,
 };
 
 fn from_hex(_: &OperationArguments, dish: &mut DishData) -> DishResult {
-    from_radix_helper(16, dish)
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => {
+            return Err(DishError::wrong_type("cannot convert binary data from hex"))
+        }
+    };
+
+    let mut bytes = Vec::new();
+    for token in data.split(is_byte_separator) {
+        let digits = token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"))
+            .unwrap_or(token);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(DishError::decode(format!("`{}` is not hexadecimal", token)));
+        }
+
+        // a lone digit is a byte by itself, but longer runs are read two digits at a time
+        if digits.len() > 2 && digits.len() % 2 == 1 {
+            return Err(DishError::decode(format!(
+                "`{}` has an odd number of hex digits",
+                token
+            )));
+        }
+        for pair in digits.as_bytes().chunks(2) {
+            // the digits are all ascii, so this can't fail
+            let pair = std::str::from_utf8(pair).unwrap();
+            bytes.push(u8::from_str_radix(pair, 16).unwrap());
+        }
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(s) => *dish = DishData::Str(s),
+        Err(e) => *dish = DishData::Bin(e.into_bytes()),
+    }
+    Ok(())
 }
 
 pub static OPINFO_TOHEX: OperationInfo = OperationInfo {
@@ -133,28 +201,26 @@ pub static OPINFO_TOHEX: OperationInfo = OperationInfo {
     description: "converts data into a hexadecimal encoded string",
     authors: &["s-viour"],
     category: "Data Format",
-    arguments: &[],
+    arguments: &[("delimiter", OperationArgType::String)],
+    defaults: &[("delimiter", OperationArgDefault::String(" "))],
+    aliases: &[],
     op: to_hex,
 };
 
-fn to_hex(_: &OperationArguments, dish: &mut DishData) -> DishResult {
-    *dish = DishData::Str(
-        dish.as_bytes()
-            .iter()
-            .map(|x| format!("{:02x}", x))
-            .collect::<Vec<String>>()
-            .join(" "),
-    );
-
+fn to_hex(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = args.get_string("delimiter")?;
+    *dish = DishData::Str(to_radix_string(dish.as_bytes(), 16, true, &delimiter));
     Ok(())
 }
 
 pub static OPINFO_FROMBINARY: OperationInfo = OperationInfo {
     name: "from-binary",
-    description: "converts a binary encoded string into its raw form",
+    description: "converts a binary encoded string into its raw form. bytes may be separated by spaces, `:`, `-`, `,`, or nothing",
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[],
+    defaults: &[],
+    aliases: &[],
     op: from_binary,
 };
 
@@ -167,19 +233,15 @@ pub static OPINFO_TOBINARY: OperationInfo = OperationInfo {
     description: "converts data into a binary-encoded string",
     authors: &["s-viour"],
     category: "Data Format",
-    arguments: &[],
+    arguments: &[("delimiter", OperationArgType::String)],
+    defaults: &[("delimiter", OperationArgDefault::String(" "))],
+    aliases: &[],
     op: to_binary,
 };
 
-fn to_binary(_: &OperationArguments, dish: &mut DishData) -> DishResult {
-    *dish = DishData::Str(
-        dish.as_bytes()
-            .iter()
-            .map(|x| format!("{:08b}", x))
-            .collect::<Vec<String>>()
-            .join(" "),
-    );
-
+fn to_binary(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = args.get_string("delimiter")?;
+    *dish = DishData::Str(to_radix_string(dish.as_bytes(), 2, true, &delimiter));
     Ok(())
 }
 
@@ -189,6 +251,8 @@ pub static OPINFO_FROMRADIX: OperationInfo = OperationInfo {
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[("radix", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
     op: from_radix,
 };
 
@@ -197,7 +261,7 @@ fn from_radix(args: &OperationArguments, dish: &mut DishData) -> DishResult {
 
     match radix_res {
         Ok(r) => from_radix_helper(r, dish),
-        Err(e) => Err(DishError(format!("invalid radix. {}", e))),
+        Err(e) => Err(DishError::invalid_argument(format!("invalid radix. {}", e))),
     }
 }
 
@@ -207,6 +271,8 @@ pub static OPINFO_TORADIX: OperationInfo = OperationInfo {
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[("radix", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
     op: to_radix,
 };
 
@@ -215,85 +281,263 @@ fn to_radix(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     let radix_res = args.get_integer("radix")?.try_into();
 
     match radix_res {
-        Ok(r) => {
+        Ok(r) => match r {
+            64 => to_base64(&OPINFO_TOBASE64.default_arguments(), dish),
             // radix_fmt doesn't support anything past 36
-            if r > 36 || r == 1 {
-                return Err(DishError(format!("unsupported radix `{}`", r)));
+            0 | 1 | 37.. => Err(DishError::unsupported(format!("unsupported radix `{}`", r))),
+            _ => {
+                // binary and hex are padded, the same as `to-binary` and `to-hex`
+                let pad = r == 2 || r == 16;
+                *dish = DishData::Str(to_radix_string(dish.as_bytes(), r, pad, " "));
+                Ok(())
             }
+        },
+        Err(e) => Err(DishError::invalid_argument(format!("invalid radix. {}", e))),
+    }
+}
 
-            match r {
-                // delegate to other functions if it's a specific radix
-                2 => to_binary(&EMPTY_ARGS, dish),
-                8 => to_octal(&EMPTY_ARGS, dish),
-                10 => to_decimal(&EMPTY_ARGS, dish),
-                16 => to_hex(&EMPTY_ARGS, dish),
-                64 => to_base64(&EMPTY_ARGS, dish),
-                // otherwise use radix_fmt
-                _ => {
-                    *dish = DishData::Str(
-                        dish.as_bytes()
-                            .iter()
-                            .map(|x| format!("{}", radix_fmt::radix(*x, r)))
-                            .collect::<Vec<String>>()
-                            .join(" "),
-                    );
-                    Ok(())
-                }
-            }
+pub static OPINFO_BASE64_TO_HEX: OperationInfo = OperationInfo {
+    name: "base64-to-hex",
+    description: "converts base64 directly into a hexadecimal encoded string",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: base64_to_hex,
+};
+
+fn base64_to_hex(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    from_base64(&OPINFO_FROMBASE64.default_arguments(), dish)?;
+    *dish = DishData::Str(to_radix_string(dish.as_bytes(), 16, true, " "));
+    Ok(())
+}
+
+pub static OPINFO_HEX_TO_BASE64: OperationInfo = OperationInfo {
+    name: "hex-to-base64",
+    description: "converts a hexadecimal encoded string directly into base64",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: hex_to_base64,
+};
+
+fn hex_to_base64(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    from_hex(&EMPTY_ARGS, dish)?;
+    to_base64(&OPINFO_TOBASE64.default_arguments(), dish)
+}
+
+pub static OPINFO_TO_HEXDUMP: OperationInfo = OperationInfo {
+    name: "to-hexdump",
+    description: "renders the dish as a canonical hexdump, like `hexdump -C`",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: to_hexdump,
+};
+
+fn to_hexdump(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let bytes = dish.as_bytes();
+    let mut lines = Vec::new();
+
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = (0..16)
+            .map(|j| match chunk.get(j) {
+                Some(b) => format!("{:02x}", b),
+                None => "  ".to_string(),
+            })
+            .collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|b| match b {
+                0x20..=0x7e => *b as char,
+                _ => '.',
+            })
+            .collect();
+        lines.push(format!(
+            "{:08x}  {}  {}  |{}|",
+            i * 16,
+            hex[..8].join(" "),
+            hex[8..].join(" "),
+            ascii
+        ));
+    }
+    // the last line is the total length, so an empty dish is still one line
+    lines.push(format!("{:08x}", bytes.len()));
+
+    *dish = DishData::Str(lines.join("\n"));
+    Ok(())
+}
+
+/// the widest `width` that `side-by-side` accepts
+const MAX_SIDE_BY_SIDE_WIDTH: i64 = 1024;
+
+pub static OPINFO_SIDE_BY_SIDE: OperationInfo = OperationInfo {
+    name: "side-by-side",
+    description:
+        "shows each line of a string next to its hex, wrapping lines longer than `width` characters (at most 1024)",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[("width", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: side_by_side,
+};
+
+fn side_by_side(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let width = args.get_integer("width")?;
+    if !(1..=MAX_SIDE_BY_SIDE_WIDTH).contains(&width) {
+        return Err(DishError::invalid_argument(format!(
+            "width must be between 1 and {}. got {}",
+            MAX_SIDE_BY_SIDE_WIDTH, width
+        )));
+    }
+    let width = width as usize;
+
+    let s = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => {
+            return Err(DishError::wrong_type(
+                "side-by-side only works on strings. use to-hexdump for binary data",
+            ))
+        }
+    };
+
+    let mut rows = Vec::new();
+    for line in s.split('\n') {
+        let chars: Vec<char> = line.chars().collect();
+        // an empty line still gets a row so the line numbers stay lined up
+        let chunks: Vec<&[char]> = if chars.is_empty() {
+            vec![&[]]
+        } else {
+            chars.chunks(width).collect()
+        };
+
+        for chunk in chunks {
+            // control characters would throw off the columns, so they're dots like in a hexdump
+            let text: String = chunk
+                .iter()
+                .map(|c| if c.is_control() { '.' } else { *c })
+                .collect();
+            let bytes = chunk.iter().collect::<String>().into_bytes();
+            rows.push(format!(
+                "{}{} | {}",
+                text,
+                " ".repeat(width - chunk.len()),
+                to_radix_string(&bytes, 16, true, " ")
+            ));
         }
-        Err(e) => Err(DishError(format!("invalid radix. {}", e))),
     }
+
+    *dish = DishData::Str(rows.join("\n"));
+    Ok(())
+}
+
+/// helper function for things like `to-hex` and `to-octal`
+/// writes each byte in `radix` (2 to 36), separated by `delimiter`. with `pad`, every
+/// byte is zero-padded to the width of 255 in that radix, like `0f` and `00001010`
+///
+/// the output round-trips through `from_radix_helper` when `delimiter` is made of
+/// `is_byte_separator` chars, or with `pad` when there's no delimiter at all
+///
+fn to_radix_string(bytes: &[u8], radix: u32, pad: bool, delimiter: &str) -> String {
+    let radix = radix as u8;
+    let width = if pad {
+        radix_fmt::radix(u8::MAX, radix).to_string().len()
+    } else {
+        0
+    };
+
+    bytes
+        .iter()
+        .map(|x| format!("{:0>width$}", radix_fmt::radix(*x, radix).to_string()))
+        .collect::<Vec<String>>()
+        .join(delimiter)
+}
+
+/// predicate of whether or not `c` can separate the bytes read by `from-hex` and
+/// `from_radix_helper`
+///
+fn is_byte_separator(c: char) -> bool {
+    c.is_whitespace() || ":-,".contains(c)
 }
 
 /// helper function for things like `from-hex` and `from-octal`
 /// takes the radix and the dish and performs the entire from-radix process
 ///
+/// bytes are separated by `is_byte_separator` chars. a run of digits longer than the
+/// width of 255 in `radix` is read as fixed-width bytes back to back, which is what
+/// padded output with no delimiter looks like
+///
 fn from_radix_helper(radix: u32, dish: &mut DishData) -> DishResult {
+    if !(2..=36).contains(&radix) {
+        return Err(DishError::unsupported(format!(
+            "unsupported radix `{}`",
+            radix
+        )));
+    }
     let data = match dish {
-        DishData::Str(s) => s.split_whitespace(),
+        DishData::Str(s) => s,
         DishData::Bin(_) => {
-            return Err(DishError(format!(
+            return Err(DishError::wrong_type(format!(
                 "cannot convert binary data from radix {}",
                 radix
             )))
         }
     };
 
-    let data: Result<Vec<u8>, std::num::ParseIntError> =
-        data.map(|x| u8::from_str_radix(x, radix)).collect();
-
-    let data = match data {
-        Ok(d) => d,
-        Err(e) => return Err(DishError(format!("{}", e))),
-    };
+    let width = radix_fmt::radix(u8::MAX, radix as u8).to_string().len();
+    let mut bytes = Vec::new();
+    for token in data.split(is_byte_separator).filter(|t| !t.is_empty()) {
+        let chunk = if token.len() > width && token.len() % width == 0 {
+            width
+        } else {
+            token.len()
+        };
+        for digits in token.as_bytes().chunks(chunk) {
+            let digits = String::from_utf8_lossy(digits);
+            let byte = u8::from_str_radix(&digits, radix)
+                .map_err(|e| DishError::decode(format!("`{}`: {}", digits, e)))?;
+            bytes.push(byte);
+        }
+    }
 
-    match String::from_utf8(data.clone()) {
+    match String::from_utf8(bytes) {
         Ok(s) => *dish = DishData::Str(s),
-        Err(_) => *dish = DishData::Bin(data),
+        Err(e) => *dish = DishData::Bin(e.into_bytes()),
     }
 
     Ok(())
 }
 
+/// helper function that compiles a user-supplied regex pattern
+///
+pub(crate) fn regex_helper(pattern: &str) -> Result<Regex, DishError> {
+    Regex::new(pattern).map_err(|e| DishError::invalid_argument(format!("{}", e)))
+}
+
 pub static OPINFO_REGEXMATCH: OperationInfo = OperationInfo {
     name: "regex-match",
     description: "finds substrings that match regex",
     authors: &["Egggggg"],
     category: "Data Format",
     arguments: &[("pattern", OperationArgType::String)],
+    defaults: &[],
+    aliases: &[],
     op: regex_match,
 };
 
 fn regex_match(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     let pattern = args.get_string("pattern")?;
-    let re = match Regex::new(&pattern) {
-        Ok(r) => r,
-        Err(e) => return Err(DishError(format!("{}", e))),
-    };
+    let re = regex_helper(&pattern)?;
     let mut out = Vec::new();
     let data = match dish {
         DishData::Str(s) => s,
-        DishData::Bin(_) => return Err(DishError("dish should be string, got binary".to_string())),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
     };
 
     for m in re.find_iter(&data) {
@@ -314,19 +558,18 @@ pub static OPINFO_REGEXREPLACE: OperationInfo = OperationInfo {
         ("pattern", OperationArgType::String),
         ("replacement", OperationArgType::String),
     ],
+    defaults: &[],
+    aliases: &[],
     op: regex_replace,
 };
 
 fn regex_replace(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     let pattern = args.get_string("pattern")?;
     let replacement = args.get_string("replacement")?;
-    let re = match Regex::new(&pattern) {
-        Ok(r) => r,
-        Err(e) => return Err(DishError(format!("{}", e))),
-    };
+    let re = regex_helper(&pattern)?;
     let data = match dish {
         DishData::Str(s) => s,
-        DishData::Bin(_) => return Err(DishError("dish should be string, got binary".to_string())),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
     };
 
     *dish = DishData::Str(re.replace_all(&data, replacement).to_string());
@@ -334,12 +577,182 @@ fn regex_replace(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     Ok(())
 }
 
+pub static OPINFO_REGEX_EXTRACT: OperationInfo = OperationInfo {
+    name: "regex-extract",
+    description: "formats each regex match with a template like `$1` or `${name}`, one per line",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[
+        ("pattern", OperationArgType::String),
+        ("template", OperationArgType::String),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: regex_extract,
+};
+
+fn regex_extract(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let pattern = args.get_string("pattern")?;
+    let template = args.get_string("template")?;
+    let re = regex_helper(&pattern)?;
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    // `Captures::expand` silently replaces unknown groups with nothing,
+    // which is almost never what was meant
+    for group in template_groups_helper(&template) {
+        let exists = match group.parse::<usize>() {
+            Ok(i) => i < re.captures_len(),
+            Err(_) => re.capture_names().any(|n| n == Some(group.as_str())),
+        };
+        if !exists {
+            return Err(DishError::invalid_argument(format!(
+                "template references group `{}`, which is not in the pattern",
+                group
+            )));
+        }
+    }
+
+    let out: Vec<String> = re
+        .captures_iter(data)
+        .map(|caps| {
+            let mut line = String::new();
+            caps.expand(&template, &mut line);
+            line
+        })
+        .collect();
+
+    *dish = DishData::Str(out.join("\n"));
+    Ok(())
+}
+
+/// helper function that finds the names of the groups referenced by a replacement
+/// template, using the same rules as the regex crate: `$$` is a literal `$`, `${name}`
+/// is braced, and an unbraced `$name` takes as many word characters as it can
+///
+fn template_groups_helper(template: &str) -> Vec<String> {
+    let mut groups = Vec::new();
+    let mut rest = template;
+
+    while let Some(i) = rest.find('$') {
+        rest = &rest[i + 1..];
+        if let Some(r) = rest.strip_prefix('$') {
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix('{') {
+            if let Some(end) = r.find('}') {
+                groups.push(r[..end].to_string());
+                rest = &r[end + 1..];
+            }
+        } else {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            if len > 0 {
+                groups.push(rest[..len].to_string());
+            }
+            rest = &rest[len..];
+        }
+    }
+
+    groups
+}
+
+/// the kinds of indicator `extract` can find
+static EXTRACT_TYPES: &[&str] = &["ip", "ipv6", "email", "url", "domain", "hash"];
+
+lazy_static! {
+    // candidates for addresses are checked with std's parsers, which is far
+    // simpler than getting every case right in a regex
+    static ref IPV4_RE: Regex = Regex::new(r"\b\d{1,3}(?:\.\d{1,3}){3}\b").unwrap();
+    static ref IPV6_RE: Regex =
+        Regex::new(r"(?i)[0-9a-f]*:[0-9a-f:]*:[0-9a-f]*(?:\d{1,3}(?:\.\d{1,3}){3})?").unwrap();
+    static ref EMAIL_RE: Regex =
+        Regex::new(r"(?i)\b[a-z0-9._%+-]+@(?:[a-z0-9-]+\.)+[a-z]{2,}\b").unwrap();
+    static ref URL_RE: Regex = Regex::new(r#"(?i)\b(?:https?|ftp)://[^\s<>"']+"#).unwrap();
+    static ref DOMAIN_RE: Regex =
+        Regex::new(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z]{2,63}\b").unwrap();
+    // md5, sha1, sha256, and sha512
+    static ref HASH_RE: Regex =
+        Regex::new(r"(?i)\b(?:[0-9a-f]{128}|[0-9a-f]{64}|[0-9a-f]{40}|[0-9a-f]{32})\b").unwrap();
+}
+
+pub static OPINFO_EXTRACT: OperationInfo = OperationInfo {
+    name: "extract",
+    description: "finds every indicator of `type` (ips, emails, urls, etc.), one per line",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[
+        ("type", OperationArgType::Choice(EXTRACT_TYPES)),
+        ("unique", OperationArgType::Integer),
+    ],
+    defaults: &[("unique", OperationArgDefault::Integer(1))],
+    aliases: &[],
+    op: extract,
+};
+
+fn extract(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let typ = args.get_string("type")?;
+    let unique = args.get_integer("unique")? != 0;
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let matches: Vec<&str> = match typ.as_ref() {
+        "ip" => IPV4_RE
+            .find_iter(data)
+            .map(|m| m.as_str())
+            .filter(|m| m.parse::<std::net::Ipv4Addr>().is_ok())
+            .collect(),
+        "ipv6" => IPV6_RE
+            .find_iter(data)
+            .map(|m| m.as_str())
+            .filter(|m| m.parse::<std::net::Ipv6Addr>().is_ok())
+            .collect(),
+        "email" => EMAIL_RE.find_iter(data).map(|m| m.as_str()).collect(),
+        // punctuation right after a url is much more likely to end a sentence
+        "url" => URL_RE
+            .find_iter(data)
+            .map(|m| {
+                m.as_str()
+                    .trim_end_matches(&['.', ',', ';', ':', '!', '?', ')'][..])
+            })
+            .collect(),
+        // skip the local part of email addresses, like `first.last` in `first.last@example.com`
+        "domain" => DOMAIN_RE
+            .find_iter(data)
+            .filter(|m| {
+                let word_end = data[m.end()..]
+                    .find(char::is_whitespace)
+                    .map_or(data.len(), |i| m.end() + i);
+                !data[m.end()..word_end].contains('@')
+            })
+            .map(|m| m.as_str())
+            .collect(),
+        _ => HASH_RE.find_iter(data).map(|m| m.as_str()).collect(),
+    };
+
+    let mut out: Vec<&str> = Vec::new();
+    for m in matches {
+        if !unique || !out.contains(&m) {
+            out.push(m);
+        }
+    }
+
+    *dish = DishData::Str(out.join("\n"));
+    Ok(())
+}
+
 pub static OPINFO_URLENCODE: OperationInfo = OperationInfo {
     name: "url-encode",
     description: "URL encodes a string",
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[],
+    defaults: &[],
+    aliases: &[],
     op: url_encode,
 };
 
@@ -347,7 +760,7 @@ fn url_encode(_: &OperationArguments, dish: &mut DishData) -> DishResult {
     let s = match std::str::from_utf8(dish.as_bytes()) {
         Ok(s) => s,
         Err(e) => {
-            return Err(DishError(format!(
+            return Err(DishError::decode(format!(
                 "could not decode binary dish to utf8 for encoding: {}",
                 e
             )))
@@ -364,6 +777,8 @@ pub static OPINFO_URLDECODE: OperationInfo = OperationInfo {
     authors: &["s-viour"],
     category: "Data Format",
     arguments: &[],
+    defaults: &[],
+    aliases: &[],
     op: url_decode,
 };
 
@@ -371,7 +786,7 @@ fn url_decode(_: &OperationArguments, dish: &mut DishData) -> DishResult {
     let s = match std::str::from_utf8(dish.as_bytes()) {
         Ok(s) => s,
         Err(e) => {
-            return Err(DishError(format!(
+            return Err(DishError::decode(format!(
                 "could not decode binary dish to utf8 for decoding: {}",
                 e
             )))
@@ -381,106 +796,1639 @@ fn url_decode(_: &OperationArguments, dish: &mut DishData) -> DishResult {
     *dish = match urlencoding::decode(s) {
         Ok(s) => DishData::Str(s.to_owned().to_string()),
         Err(e) => {
-            return Err(DishError(format!("could not perform URL decode: {}", e)));
+            return Err(DishError::decode(format!(
+                "could not perform URL decode: {}",
+                e
+            )));
         }
     };
 
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::ops::data_format::*;
-    use crate::{DishData, EMPTY_ARGS};
-
-    #[test]
-    fn test_to_octal() {
-        let mut data = DishData::Bin(vec![42]);
-        let _expected = DishData::Str(String::from("52"));
-        assert!(matches!(to_octal(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
+/// helper function that URL decodes one key or value of a query string,
+/// where `+` also means a space
+///
+fn query_decode_helper(s: &str) -> Result<String, DishError> {
+    match urlencoding::decode(&s.replace('+', " ")) {
+        Ok(s) => Ok(s.into_owned()),
+        Err(e) => Err(DishError::decode(format!(
+            "could not URL decode `{}`: {}",
+            s, e
+        ))),
     }
+}
 
-    #[test]
-    fn test_from_octal() {
-        let mut data = DishData::Str("150 145 154 154 157 40 167 157 162 154 144 41".to_string());
-        let _expected = DishData::Str("hello world!".to_string());
-        assert!(matches!(from_octal(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
-    }
+pub static OPINFO_PARSE_QUERY_STRING: OperationInfo = OperationInfo {
+    name: "parse-query-string",
+    description: "lists the decoded `key = value` pairs of a URL or query string, one per line",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: parse_query_string,
+};
 
-    #[test]
-    fn test_to_hex() {
-        let mut data = DishData::Bin(vec![15]);
-        let _expected = DishData::Str(String::from("0f"));
-        assert!(matches!(to_hex(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
+fn parse_query_string(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
 
-        let mut data = DishData::Bin(vec![26]);
-        let _expected = DishData::Str(String::from("1a"));
-        assert!(matches!(to_hex(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
+    // for a whole URL, only the part between the `?` and the fragment is the query
+    let query = data.split_once('?').map_or(data, |(_, q)| q);
+    let query = query.split_once('#').map_or(query, |(q, _)| q);
+
+    let mut pairs: Vec<(String, Option<String>)> = Vec::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        pairs.push(match pair.split_once('=') {
+            Some((key, value)) => (query_decode_helper(key)?, Some(query_decode_helper(value)?)),
+            None => (query_decode_helper(pair)?, None),
+        });
     }
 
-    #[test]
-    fn test_from_hex() {
-        let mut data = DishData::Str(String::from("0f"));
-        let _expected = DishData::Str("\u{f}".to_string());
+    // keys without a value are listed on their own, without an `=`
+    let width = pairs
+        .iter()
+        .map(|(k, _)| k.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<String> = pairs
+        .iter()
+        .map(|(key, value)| match value {
+            Some(value) => format!("{:<width$} = {}", key, value),
+            None => key.clone(),
+        })
+        .collect();
+
+    *dish = DishData::Str(lines.join("\n"));
+    Ok(())
+}
 
-        assert!(matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
+pub static OPINFO_BUILD_QUERY_STRING: OperationInfo = OperationInfo {
+    name: "build-query-string",
+    description: "builds a URL encoded query string from `key = value` lines, like the ones from parse-query-string",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: build_query_string,
+};
 
-        let mut data = DishData::Str(String::from("1a"));
-        let _expected = DishData::Str("\u{1a}".to_string());
-        assert!(matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
-    }
+fn build_query_string(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
 
-    #[test]
-    fn test_from_binary() {
-        let mut data = DishData::Str("01101000 01100101 01101100 01101100 01101111".to_string());
-        let _expected = DishData::Str("hello".to_string());
+    let pairs: Vec<String> = data
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match line.split_once(" = ") {
+            Some((key, value)) => format!(
+                "{}={}",
+                urlencoding::encode(key.trim_end()),
+                urlencoding::encode(value)
+            ),
+            None => urlencoding::encode(line.trim_end()).into_owned(),
+        })
+        .collect();
+
+    *dish = DishData::Str(pairs.join("&"));
+    Ok(())
+}
 
-        assert!(matches!(from_binary(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
-    }
+pub static OPINFO_JWT_DECODE: OperationInfo = OperationInfo {
+    name: "jwt-decode",
+    description:
+        "decodes the header and payload of a JSON web token. the signature is not verified",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: jwt_decode,
+};
 
-    #[test]
-    fn test_to_binary() {
-        let mut data = DishData::Str("hello world!".to_string());
-        let _expected = DishData::Str("01101000 01100101 01101100 01101100 01101111 00100000 01110111 01101111 01110010 01101100 01100100 00100001".to_string());
+fn jwt_decode(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let token = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
 
-        assert!(matches!(to_binary(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(DishError::decode(format!(
+            "expected a JWT with 3 dot-separated parts. got {}",
+            parts.len()
+        )));
     }
 
-    #[test]
-    fn test_from_decimal() {
-        let mut data = DishData::Str("104 101 108 108 111 32 119 111 114 108 100 33".to_string());
-        let _expected = DishData::Str("hello world!".to_string());
+    let decoded = serde_json::json!({
+        "header": jwt_segment_helper("header", parts[0])?,
+        "payload": jwt_segment_helper("payload", parts[1])?,
+        "signature": parts[2],
+    });
 
-        assert!(matches!(from_decimal(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
-    }
+    // serializing a `Value` can't fail
+    *dish = DishData::Str(serde_json::to_string_pretty(&decoded).unwrap());
+    Ok(())
+}
 
-    #[test]
-    fn test_to_decimal() {
-        let mut data = DishData::Str("hello world!".to_string());
-        let _expected = DishData::Str("104 101 108 108 111 32 119 111 114 108 100 33".to_string());
+/// helper function that decodes a single base64url JSON segment of a JWT
+///
+fn jwt_segment_helper(name: &str, segment: &str) -> Result<serde_json::Value, DishError> {
+    let bytes = base64::decode_config(segment.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+        .map_err(|e| DishError::decode(format!("JWT {} is not valid base64url: {}", name, e)))?;
 
-        assert!(matches!(to_decimal(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
-    }
+    serde_json::from_slice(&bytes)
+        .map_err(|e| DishError::decode(format!("JWT {} is not valid JSON: {}", name, e)))
+}
 
-    #[test]
-    fn test_url_encode() {
-        let mut data = DishData::Str("abcdefghijklmnopqrstuvwxyz!@#$%^&*()[]".to_string());
-        let _expected = DishData::Str(
-            "abcdefghijklmnopqrstuvwxyz%21%40%23%24%25%5E%26%2A%28%29%5B%5D".to_string(),
-        );
+/// the widest `indent` that `json-beautify` and `xml-beautify` accept
+const MAX_INDENT: i64 = 16;
 
-        assert!(matches!(url_encode(&EMPTY_ARGS, &mut data), Ok(())));
-        assert_eq!(data, _expected);
+/// helper function that gets the `indent` argument of a beautify operation
+///
+fn beautify_indent(args: &OperationArguments) -> Result<usize, DishError> {
+    match args.get_integer("indent")? {
+        n if n < 0 => Err(DishError::invalid_argument("indent must not be negative")),
+        n if n > MAX_INDENT => Err(DishError::invalid_argument(format!(
+            "indent must be at most {}. got {}",
+            MAX_INDENT, n
+        ))),
+        n => Ok(n as usize),
+    }
+}
+
+pub static OPINFO_JSON_BEAUTIFY: OperationInfo = OperationInfo {
+    name: "json-beautify",
+    description: "reformats JSON with each level indented by `indent` spaces, up to 16",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[("indent", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: json_beautify,
+};
+
+fn json_beautify(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let indent = beautify_indent(args)?;
+
+    let value = parse_json_helper(dish)?;
+    let indent = " ".repeat(indent);
+    let mut out = Vec::new();
+    let mut ser = serde_json::Serializer::with_formatter(
+        &mut out,
+        serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes()),
+    );
+    value
+        .serialize(&mut ser)
+        .map_err(|e| DishError::decode(format!("could not serialize JSON: {}", e)))?;
+
+    // serde_json only ever writes valid utf8
+    *dish = DishData::Str(String::from_utf8(out).unwrap());
+    Ok(())
+}
+
+pub static OPINFO_JSON_MINIFY: OperationInfo = OperationInfo {
+    name: "json-minify",
+    description: "strips all insignificant whitespace from JSON",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: json_minify,
+};
+
+fn json_minify(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let value = parse_json_helper(dish)?;
+    *dish = DishData::Str(value.to_string());
+    Ok(())
+}
+
+/// helper function that parses a string dish as JSON. object keys keep their order
+///
+fn parse_json_helper(dish: &DishData) -> Result<serde_json::Value, DishError> {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    // serde_json's error message already includes the line and column
+    serde_json::from_str(data).map_err(|e| DishError::decode(format!("invalid JSON: {}", e)))
+}
+
+/// HTML elements that never have a closing tag
+static HTML_VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// A single piece of markup found by `tokenize_markup`
+///
+#[derive(Debug, PartialEq)]
+enum MarkupToken<'a> {
+    /// an opening tag and its name, like `<a href="...">`
+    Open(&'a str, &'a str),
+    /// a closing tag and its name, like `</a>`
+    Close(&'a str, &'a str),
+    /// anything that doesn't affect nesting, like `<br/>`, comments, or `<?xml ... ?>`
+    Leaf(&'a str),
+    /// non-whitespace text between tags, already trimmed
+    Text(&'a str),
+}
+
+/// helper function that splits XML or HTML into tags and text
+///
+fn tokenize_markup(s: &str) -> Result<Vec<MarkupToken<'_>>, DishError> {
+    let mut tokens = Vec::new();
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = rest[..end].trim();
+            if !text.is_empty() {
+                tokens.push(MarkupToken::Text(text));
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        // comments, CDATA, and declarations end with a fixed terminator
+        let terminator = [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")]
+            .iter()
+            .find(|(start, _)| rest.starts_with(start))
+            .map(|(_, end)| *end);
+        if let Some(end) = terminator {
+            let len = rest.find(end).ok_or_else(|| {
+                DishError::decode(format!("unterminated markup, expected `{}`", end))
+            })?;
+            tokens.push(MarkupToken::Leaf(&rest[..len + end.len()]));
+            rest = &rest[len + end.len()..];
+            continue;
+        }
+
+        // find the end of the tag, skipping over any quoted attribute values
+        let mut quote = None;
+        let len = rest
+            .char_indices()
+            .find(|(_, c)| match quote {
+                Some(q) => {
+                    if *c == q {
+                        quote = None;
+                    }
+                    false
+                }
+                None => {
+                    if *c == '"' || *c == '\'' {
+                        quote = Some(*c);
+                    }
+                    *c == '>'
+                }
+            })
+            .map(|(i, _)| i + 1)
+            .ok_or_else(|| DishError::decode("unterminated tag"))?;
+        let tag = &rest[..len];
+        rest = &rest[len..];
+
+        let name_start = if tag.starts_with("</") { 2 } else { 1 };
+        let name = tag[name_start..]
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .next()
+            .unwrap_or("");
+        if name.is_empty() {
+            return Err(DishError::decode(format!("tag `{}` has no name", tag)));
+        }
+
+        if name_start == 2 {
+            tokens.push(MarkupToken::Close(tag, name));
+        } else if tag.starts_with("<!")
+            || tag.ends_with("/>")
+            || HTML_VOID_ELEMENTS
+                .iter()
+                .any(|v| v.eq_ignore_ascii_case(name))
+        {
+            tokens.push(MarkupToken::Leaf(tag));
+        } else {
+            tokens.push(MarkupToken::Open(tag, name));
+
+            // the contents of these are raw text and never contain tags
+            if ["script", "style"]
+                .iter()
+                .any(|r| r.eq_ignore_ascii_case(name))
+            {
+                let close = format!("</{}", name.to_ascii_lowercase());
+                let end = rest
+                    .to_ascii_lowercase()
+                    .find(&close)
+                    .ok_or_else(|| DishError::decode(format!("unclosed tag `{}`", tag)))?;
+                let text = rest[..end].trim();
+                if !text.is_empty() {
+                    tokens.push(MarkupToken::Text(text));
+                }
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+pub static OPINFO_XML_BEAUTIFY: OperationInfo = OperationInfo {
+    name: "xml-beautify",
+    description: "reindents XML or HTML with each level indented by `indent` spaces, up to 16",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[("indent", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: xml_beautify,
+};
+
+fn xml_beautify(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let indent = beautify_indent(args)?;
+
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let tokens = tokenize_markup(data)?;
+    let mut stack: Vec<&str> = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let pad = " ".repeat(stack.len() * indent);
+        match tokens[i] {
+            MarkupToken::Open(tag, name) => {
+                // keep elements with no children, or only text, on one line
+                let closes = |t: &MarkupToken| matches!(t, MarkupToken::Close(_, n) if n.eq_ignore_ascii_case(name));
+                match (tokens.get(i + 1), tokens.get(i + 2)) {
+                    (Some(close @ MarkupToken::Close(end, _)), _) if closes(close) => {
+                        lines.push(format!("{}{}{}", pad, tag, end));
+                        i += 2;
+                        continue;
+                    }
+                    (Some(MarkupToken::Text(text)), Some(close @ MarkupToken::Close(end, _)))
+                        if closes(close) =>
+                    {
+                        lines.push(format!("{}{}{}{}", pad, tag, text, end));
+                        i += 3;
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                lines.push(format!("{}{}", pad, tag));
+                stack.push(name);
+            }
+            MarkupToken::Close(tag, name) => match stack.pop() {
+                Some(open) if open.eq_ignore_ascii_case(name) => {
+                    let pad = " ".repeat(stack.len() * indent);
+                    lines.push(format!("{}{}", pad, tag));
+                }
+                Some(open) => {
+                    return Err(DishError::decode(format!(
+                        "unbalanced tags: `{}` closes `<{}>`",
+                        tag, open
+                    )))
+                }
+                None => {
+                    return Err(DishError::decode(format!(
+                        "unbalanced tags: `{}` has no opening tag",
+                        tag
+                    )))
+                }
+            },
+            MarkupToken::Leaf(s) | MarkupToken::Text(s) => lines.push(format!("{}{}", pad, s)),
+        }
+        i += 1;
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(DishError::decode(format!(
+            "unbalanced tags: `<{}>` is never closed",
+            open
+        )));
+    }
+
+    *dish = DishData::Str(lines.join("\n"));
+    Ok(())
+}
+
+/// helper function that parses a single-character delimiter argument
+///
+fn delimiter_helper(args: &OperationArguments) -> Result<char, DishError> {
+    let delimiter = args.get_string("delimiter")?;
+    let mut chars = delimiter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '"' && c != '\n' && c != '\r' => Ok(c),
+        _ => Err(DishError::invalid_argument(format!(
+            "delimiter must be a single character other than a quote or newline. got `{}`",
+            delimiter
+        ))),
+    }
+}
+
+/// helper function that parses delimited text like CSV into rows of fields
+/// fields may be quoted to contain the delimiter, newlines, or `""` (an escaped quote)
+///
+fn parse_delimited_helper(s: &str, delimiter: char) -> Result<Vec<Vec<String>>, DishError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut chars = s.chars().peekable();
+
+    // whether the current row has anything in it, so a trailing newline isn't a row
+    let mut in_row = false;
+    while let Some(c) = chars.next() {
+        in_row = true;
+        match c {
+            '"' if field.is_empty() => loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err(DishError::decode("unterminated quoted field")),
+                }
+            },
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                in_row = false;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_row {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// helper function that quotes a field for delimited output if it needs it
+///
+fn quote_field_helper(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub static OPINFO_CSV_TO_JSON: OperationInfo = OperationInfo {
+    name: "csv-to-json",
+    description:
+        "converts delimited text to a JSON array. rows become objects when `header` is nonzero",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[
+        ("delimiter", OperationArgType::String),
+        ("header", OperationArgType::Integer),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: csv_to_json,
+};
+
+fn csv_to_json(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = delimiter_helper(args)?;
+    let header = args.get_integer("header")? != 0;
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let mut rows = parse_delimited_helper(data, delimiter)?.into_iter();
+    let value = if header {
+        let names = rows.next().unwrap_or_default();
+        let objects = rows
+            .enumerate()
+            .map(|(i, row)| {
+                if row.len() != names.len() {
+                    return Err(DishError::decode(format!(
+                        "row {} has {} fields, but the header has {}",
+                        i + 2,
+                        row.len(),
+                        names.len()
+                    )));
+                }
+                Ok(names
+                    .iter()
+                    .cloned()
+                    .zip(row.into_iter().map(serde_json::Value::String))
+                    .collect())
+            })
+            .collect::<Result<Vec<serde_json::Map<_, _>>, DishError>>()?;
+        serde_json::json!(objects)
+    } else {
+        serde_json::json!(rows.collect::<Vec<_>>())
+    };
+
+    *dish = DishData::Str(value.to_string());
+    Ok(())
+}
+
+pub static OPINFO_JSON_TO_CSV: OperationInfo = OperationInfo {
+    name: "json-to-csv",
+    description: "converts a JSON array of flat objects to delimited text with a header row",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[("delimiter", OperationArgType::String)],
+    defaults: &[],
+    aliases: &[],
+    op: json_to_csv,
+};
+
+fn json_to_csv(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = delimiter_helper(args)?;
+    let value = parse_json_helper(dish)?;
+    let objects = match &value {
+        serde_json::Value::Array(a) => a,
+        _ => return Err(DishError::decode("expected a JSON array of objects")),
+    };
+
+    // columns are every key, in the order they're first seen
+    let mut columns: Vec<&String> = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        let object = object
+            .as_object()
+            .ok_or_else(|| DishError::decode(format!("element {} is not an object", i)))?;
+        for (key, value) in object {
+            if value.is_object() || value.is_array() {
+                return Err(DishError::decode(format!(
+                    "element {} is not flat. (field `{}` is nested)",
+                    i, key
+                )));
+            }
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+
+    let mut lines = vec![columns
+        .iter()
+        .map(|c| quote_field_helper(c, delimiter))
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())];
+    for object in objects {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|c| match object.get(c.as_str()) {
+                Some(serde_json::Value::String(s)) => quote_field_helper(s, delimiter),
+                Some(serde_json::Value::Null) | None => String::new(),
+                Some(v) => v.to_string(),
+            })
+            .collect();
+        lines.push(fields.join(&delimiter.to_string()));
+    }
+
+    *dish = DishData::Str(lines.join("\n"));
+    Ok(())
+}
+
+static TABLE_FORMATS: &[&str] = &["ascii", "markdown"];
+
+pub static OPINFO_TO_TABLE: OperationInfo = OperationInfo {
+    name: "to-table",
+    description: "renders delimited text as an aligned `ascii` or `markdown` table",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[
+        ("delimiter", OperationArgType::String),
+        ("format", OperationArgType::Choice(TABLE_FORMATS)),
+        ("header", OperationArgType::Integer),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: to_table,
+};
+
+fn to_table(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = delimiter_helper(args)?;
+    let format = args.get_string("format")?;
+    let header = args.get_integer("header")? != 0;
+
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let mut rows: Vec<Vec<String>> = parse_delimited_helper(data, delimiter)?
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|field| {
+                    let field = field.replace(['\r', '\n'], " ");
+                    if format == "markdown" {
+                        field.replace('|', "\\|")
+                    } else {
+                        field
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    // markdown tables always need a header, so give them an empty one
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    if format == "markdown" && !header {
+        rows.insert(0, vec![String::new(); columns]);
+    }
+    let header = header || format == "markdown";
+
+    let mut widths = vec![0; columns];
+    for row in &rows {
+        for (i, field) in row.iter().enumerate() {
+            widths[i] = widths[i].max(field.chars().count());
+        }
+    }
+    // markdown needs at least three dashes in the separator
+    if format == "markdown" {
+        widths.iter_mut().for_each(|w| *w = (*w).max(3));
+    }
+
+    let render_row = |row: &Vec<String>| -> String {
+        let fields: Vec<String> = (0..columns)
+            .map(|i| {
+                let field = row.get(i).map(String::as_str).unwrap_or("");
+                format!("{:<width$}", field, width = widths[i])
+            })
+            .collect();
+        format!("| {} |", fields.join(" | "))
+    };
+    let rule = |fill: &str, edge: &str| -> String {
+        let parts: Vec<String> = widths.iter().map(|w| fill.repeat(w + 2)).collect();
+        format!("{}{}{}", edge, parts.join(edge), edge)
+    };
+
+    let mut lines = Vec::new();
+    if format == "ascii" {
+        lines.push(rule("-", "+"));
+    }
+    for (i, row) in rows.iter().enumerate() {
+        lines.push(render_row(row));
+        if i == 0 && header {
+            lines.push(match format.as_ref() {
+                "ascii" => rule("=", "+"),
+                _ => rule("-", "|"),
+            });
+        }
+    }
+    if format == "ascii" {
+        lines.push(rule("-", "+"));
+    }
+
+    *dish = DishData::Str(lines.join("\n"));
+    Ok(())
+}
+
+lazy_static! {
+    static ref FLAGS_VALUE_RE: Regex = Regex::new(r"(?i)\b(?:0x([0-9a-f]+)|([0-9]+))\b").unwrap();
+}
+
+pub static OPINFO_DECODE_FLAGS: OperationInfo = OperationInfo {
+    name: "decode-flags",
+    description: "lists which bits of the first decimal or `0x` hex number in the input are set, one per line. `names` is a comma-separated list of bit names, lowest bit first",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[("names", OperationArgType::String)],
+    defaults: &[],
+    aliases: &[],
+    op: decode_flags,
+};
+
+fn decode_flags(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let names = args.get_string("names")?;
+    let names: Vec<&str> = names.split(',').map(str::trim).collect();
+    if names.len() > u64::BITS as usize {
+        return Err(DishError::invalid_argument(format!(
+            "got {} names, but values only have {} bits",
+            names.len(),
+            u64::BITS
+        )));
+    }
+
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+    let caps = FLAGS_VALUE_RE
+        .captures(data)
+        .ok_or_else(|| DishError::decode("no number found in the input"))?;
+    let value = match (caps.get(1), caps.get(2)) {
+        (Some(hex), _) => u64::from_str_radix(hex.as_str(), 16),
+        (_, Some(decimal)) => decimal.as_str().parse(),
+        _ => unreachable!("the flags regex always captures a number"),
+    }
+    .map_err(|e| DishError::decode(format!("invalid flags value `{}`. {}", &caps[0], e)))?;
+
+    // set bits without a name (or with an empty one) are still listed by number
+    let set: Vec<String> = (0..u64::BITS as usize)
+        .filter(|bit| value & (1 << bit) != 0)
+        .map(|bit| match names.get(bit) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => format!("bit {}", bit),
+        })
+        .collect();
+
+    *dish = DishData::Str(set.join("\n"));
+    Ok(())
+}
+
+pub static OPINFO_IP_TO_INT: OperationInfo = OperationInfo {
+    name: "ip-to-int",
+    description: "converts an IPv4 or IPv6 address to the number it represents",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: ip_to_int,
+};
+
+fn ip_to_int(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let n = match data.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => u32::from(ip) as u128,
+        Ok(IpAddr::V6(ip)) => u128::from(ip),
+        Err(e) => {
+            return Err(DishError::decode(format!(
+                "`{}` is not an ip address. {}",
+                data, e
+            )))
+        }
+    };
+
+    *dish = DishData::Str(n.to_string());
+    Ok(())
+}
+
+pub static OPINFO_INT_TO_IP: OperationInfo = OperationInfo {
+    name: "int-to-ip",
+    description: "converts a number to an IPv4 or IPv6 address, depending on `version` (4 or 6)",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[("version", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: int_to_ip,
+};
+
+fn int_to_ip(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let version = args.get_integer("version")?;
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let ip = match version {
+        4 => data.parse::<u32>().map(|n| Ipv4Addr::from(n).to_string()),
+        6 => data.parse::<u128>().map(|n| Ipv6Addr::from(n).to_string()),
+        _ => {
+            return Err(DishError::invalid_argument(format!(
+                "version must be 4 or 6. got {}",
+                version
+            )))
+        }
+    }
+    .map_err(|e| {
+        DishError::decode(format!(
+            "`{}` is not an IPv{} address number. {}",
+            data, version, e
+        ))
+    })?;
+
+    *dish = DishData::Str(ip);
+    Ok(())
+}
+
+/// the most addresses `cidr-range` will list
+const CIDR_RANGE_LIMIT: u128 = 1 << 16;
+
+pub static OPINFO_CIDR_RANGE: OperationInfo = OperationInfo {
+    name: "cidr-range",
+    description: "lists every address in a CIDR block like `192.168.0.0/30`, one per line",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: cidr_range,
+};
+
+fn cidr_range(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let (ip, prefix) = data.split_once('/').ok_or_else(|| {
+        DishError::decode(format!(
+            "`{}` is not a CIDR block. expected address/prefix",
+            data
+        ))
+    })?;
+    let ip: IpAddr = ip
+        .parse()
+        .map_err(|e| DishError::decode(format!("`{}` is not an ip address. {}", ip, e)))?;
+    let (n, bits) = match ip {
+        IpAddr::V4(ip) => (u32::from(ip) as u128, 32),
+        IpAddr::V6(ip) => (u128::from(ip), 128),
+    };
+    let prefix: u32 = match prefix.parse() {
+        Ok(p) if p <= bits => p,
+        _ => {
+            return Err(DishError::decode(format!(
+                "prefix must be between 0 and {}. got `{}`",
+                bits, prefix
+            )))
+        }
+    };
+
+    let host_bits = bits - prefix;
+    if host_bits > CIDR_RANGE_LIMIT.trailing_zeros() {
+        return Err(DishError::unsupported(format!(
+            "/{} has too many addresses to list. (the most is {})",
+            prefix, CIDR_RANGE_LIMIT
+        )));
+    }
+    // host bits set in the address itself are ignored, like most tools do
+    let size = 1u128 << host_bits;
+    let network = n & !(size - 1);
+
+    // inclusive, since `network + size` overflows for a block at the top of the address space
+    let addresses: Vec<String> = (network..=network + (size - 1))
+        .map(|n| match ip {
+            IpAddr::V4(_) => Ipv4Addr::from(n as u32).to_string(),
+            IpAddr::V6(_) => Ipv6Addr::from(n).to_string(),
+        })
+        .collect();
+
+    *dish = DishData::Str(addresses.join("\n"));
+    Ok(())
+}
+
+static ENDIANNESS: &[&str] = &["big", "little"];
+
+pub static OPINFO_READ_INT: OperationInfo = OperationInfo {
+    name: "read-int",
+    description: "reads the first `width` (1, 2, 4, or 8) bytes of the dish as a `big` or `little` endian integer, two's complement when `signed` is nonzero, and gives it in decimal",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[
+        ("width", OperationArgType::Integer),
+        ("endian", OperationArgType::Choice(ENDIANNESS)),
+        ("signed", OperationArgType::Integer),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: read_int,
+};
+
+fn read_int(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let width = int_width(args)?;
+    let little = args.get_string("endian")? == "little";
+    let signed = args.get_integer("signed")? != 0;
+
+    let bytes = dish.as_bytes();
+    if bytes.len() < width {
+        return Err(DishError::decode(format!(
+            "a {} byte integer needs {} bytes, but the dish only has {}",
+            width,
+            width,
+            bytes.len()
+        )));
+    }
+
+    // widen to 8 big endian bytes so every width is read the same way
+    let mut buf = [0u8; 8];
+    buf[8 - width..].copy_from_slice(&bytes[..width]);
+    if little {
+        buf[8 - width..].reverse();
+    }
+    let n = u64::from_be_bytes(buf);
+
+    *dish = DishData::Str(if signed {
+        // shifting back down as an i64 sign-extends the top bit of the width
+        let shift = 64 - 8 * width as u32;
+        (((n << shift) as i64) >> shift).to_string()
+    } else {
+        n.to_string()
+    });
+    Ok(())
+}
+
+pub static OPINFO_WRITE_INT: OperationInfo = OperationInfo {
+    name: "write-int",
+    description: "writes the decimal integer in the dish as `width` (1, 2, 4, or 8) `big` or `little` endian bytes, two's complement when `signed` is nonzero. the inverse of `read-int`",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[
+        ("width", OperationArgType::Integer),
+        ("endian", OperationArgType::Choice(ENDIANNESS)),
+        ("signed", OperationArgType::Integer),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: write_int,
+};
+
+fn write_int(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let width = int_width(args)?;
+    let little = args.get_string("endian")? == "little";
+    let signed = args.get_integer("signed")? != 0;
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let n: i128 = data
+        .parse()
+        .map_err(|e| DishError::decode(format!("`{}` is not an integer. {}", data, e)))?;
+    let bits = 8 * width as u32;
+    let (min, max) = if signed {
+        (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+    } else {
+        (0, (1i128 << bits) - 1)
+    };
+    if n < min || n > max {
+        return Err(DishError::decode(format!(
+            "{} doesn't fit in a {} byte {} integer",
+            n,
+            width,
+            if signed { "signed" } else { "unsigned" }
+        )));
+    }
+
+    // two's complement of a negative number is just its low bits
+    let mut bytes = (n as u64).to_be_bytes()[8 - width..].to_vec();
+    if little {
+        bytes.reverse();
+    }
+
+    *dish = DishData::Bin(bytes);
+    Ok(())
+}
+
+/// helper function for `read-int` and `write-int` that checks `width` is
+/// the size of an integer type
+///
+fn int_width(args: &OperationArguments) -> Result<usize, DishError> {
+    match args.get_integer("width")? {
+        w @ (1 | 2 | 4 | 8) => Ok(w as usize),
+        w => Err(DishError::invalid_argument(format!(
+            "width must be 1, 2, 4, or 8. got {}",
+            w
+        ))),
+    }
+}
+
+pub static OPINFO_TRANSPOSE: OperationInfo = OperationInfo {
+    name: "transpose",
+    description: "swaps the rows (lines) and columns (split on `delimiter`) of the input. short rows are padded with empty cells, unless `strict` is 1, which makes them an error",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[
+        ("delimiter", OperationArgType::String),
+        ("strict", OperationArgType::Integer),
+    ],
+    defaults: &[("strict", OperationArgDefault::Integer(0))],
+    aliases: &[],
+    op: transpose,
+};
+
+fn transpose(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let delimiter = args.get_string("delimiter")?;
+    let strict = args.get_integer("strict")? != 0;
+    if delimiter.is_empty() {
+        return Err(DishError::invalid_argument("delimiter can't be empty"));
+    }
+
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+    let (body, trailing) = match data.strip_suffix('\n') {
+        Some(body) => (body, "\n"),
+        None => (data.as_str(), ""),
+    };
+
+    let rows: Vec<Vec<&str>> = body
+        .split('\n')
+        .map(|row| row.split(delimiter.as_str()).collect())
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if strict {
+        if let Some(i) = rows.iter().position(|row| row.len() != columns) {
+            return Err(DishError::decode(format!(
+                "row {} has {} columns, but the widest row has {}",
+                i + 1,
+                rows[i].len(),
+                columns
+            )));
+        }
+    }
+
+    let transposed: Vec<String> = (0..columns)
+        .map(|column| {
+            let cells: Vec<&str> = rows
+                .iter()
+                .map(|row| row.get(column).copied().unwrap_or(""))
+                .collect();
+            cells.join(&delimiter)
+        })
+        .collect();
+
+    *dish = DishData::Str(transposed.join("\n") + trailing);
+    Ok(())
+}
+
+/// A color as its red, green, blue, and optional alpha channels
+///
+type Rgba = ([u8; 3], Option<u8>);
+
+/// helper function that parses `#RGB`, `#RGBA`, `#RRGGBB`, or `#RRGGBBAA`
+/// (the `#` is optional)
+///
+fn parse_hex_color(s: &str) -> Result<Rgba, DishError> {
+    let hex = s.trim().trim_start_matches('#');
+    let invalid = || DishError::decode(format!("`{}` is not a hex color", s.trim()));
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    // shorthand colors repeat each digit, so `#f80` is `#ff8800`
+    let channels: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16).unwrap())
+            .collect(),
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect(),
+        _ => return Err(invalid()),
+    };
+
+    Ok((
+        [channels[0], channels[1], channels[2]],
+        channels.get(3).copied(),
+    ))
+}
+
+/// helper function that writes a color as `#rrggbb`, or `#rrggbbaa` if it has an alpha
+///
+fn format_hex_color((rgb, alpha): Rgba) -> String {
+    let mut hex = format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]);
+    if let Some(a) = alpha {
+        hex.push_str(&format!("{:02x}", a));
+    }
+    hex
+}
+
+/// helper function that gets the arguments out of a css color function like
+/// `rgb(1, 2, 3)` or `hsla(1, 2%, 3%, 0.5)`, with `name` being `rgb` or `hsl`
+///
+fn parse_color_function<'a>(s: &'a str, name: &str) -> Result<Vec<&'a str>, DishError> {
+    let s = s.trim();
+    let invalid = || DishError::decode(format!("`{}` is not an {}() color", s, name));
+
+    let rest = s.strip_prefix(name).ok_or_else(invalid)?;
+    let rest = rest.strip_prefix('a').unwrap_or(rest);
+    let args = rest
+        .trim_start()
+        .strip_prefix('(')
+        .and_then(|r| r.strip_suffix(')'))
+        .ok_or_else(invalid)?;
+
+    let args: Vec<&str> = args.split(',').map(str::trim).collect();
+    match args.len() {
+        3 | 4 => Ok(args),
+        _ => Err(invalid()),
+    }
+}
+
+/// helper function that parses a color channel, which has to be between 0 and `max`.
+/// channels can end with a `%` when `percent` is set
+///
+fn parse_color_channel(s: &str, max: f64, percent: bool) -> Result<f64, DishError> {
+    let number = match (percent, s.strip_suffix('%')) {
+        (true, Some(n)) => n,
+        _ => s,
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if (0.0..=max).contains(&n) => Ok(n),
+        _ => Err(DishError::decode(format!(
+            "color component `{}` must be a number between 0 and {}",
+            s, max
+        ))),
+    }
+}
+
+/// helper function that parses the css alpha component (0 to 1) into a byte
+///
+fn parse_alpha(s: &str) -> Result<u8, DishError> {
+    Ok((parse_color_channel(s, 1.0, false)? * 255.0).round() as u8)
+}
+
+/// helper function that writes an alpha byte the way css expects it, from 0 to 1
+///
+fn format_alpha(a: u8) -> String {
+    let alpha = (a as f64 / 255.0 * 1000.0).round() / 1000.0;
+    alpha.to_string()
+}
+
+/// helper function that converts a color to hue (in degrees), saturation, and lightness
+/// (both from 0 to 1)
+///
+fn rgb_to_hsl(rgb: [u8; 3]) -> (f64, f64, f64) {
+    let [r, g, b] = rgb.map(|c| c as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    (h * 60.0, s, l)
+}
+
+/// helper function that converts hue (in degrees), saturation, and lightness back to a color
+///
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> [u8; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    [r, g, b].map(|v| ((v + m) * 255.0).round() as u8)
+}
+
+pub static OPINFO_HEX_TO_RGB: OperationInfo = OperationInfo {
+    name: "hex-to-rgb",
+    description: "converts a hex color (`#f80`, `#ff8800`, or `#ff880080`) to `rgb(255, 136, 0)` or `rgba(...)`",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: hex_to_rgb,
+};
+
+fn hex_to_rgb(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let ([r, g, b], alpha) = parse_hex_color(data)?;
+    *dish = DishData::Str(match alpha {
+        Some(a) => format!("rgba({}, {}, {}, {})", r, g, b, format_alpha(a)),
+        None => format!("rgb({}, {}, {})", r, g, b),
+    });
+    Ok(())
+}
+
+pub static OPINFO_RGB_TO_HEX: OperationInfo = OperationInfo {
+    name: "rgb-to-hex",
+    description: "converts an `rgb(255, 136, 0)` or `rgba(...)` color to hex",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: rgb_to_hex,
+};
+
+fn rgb_to_hex(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let args = parse_color_function(data, "rgb")?;
+    let mut rgb = [0; 3];
+    for (channel, arg) in rgb.iter_mut().zip(&args) {
+        let n = parse_color_channel(arg, 255.0, false)?;
+        if n.fract() != 0.0 {
+            return Err(DishError::decode(format!(
+                "color component `{}` must be an integer",
+                arg
+            )));
+        }
+        *channel = n as u8;
+    }
+    let alpha = args.get(3).map(|a| parse_alpha(a)).transpose()?;
+
+    *dish = DishData::Str(format_hex_color((rgb, alpha)));
+    Ok(())
+}
+
+pub static OPINFO_HEX_TO_HSL: OperationInfo = OperationInfo {
+    name: "hex-to-hsl",
+    description: "converts a hex color to `hsl(33, 100%, 50%)` or `hsla(...)`",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: hex_to_hsl,
+};
+
+fn hex_to_hsl(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let (rgb, alpha) = parse_hex_color(data)?;
+    let (h, s, l) = rgb_to_hsl(rgb);
+    let (h, s, l) = (h.round() % 360.0, (s * 100.0).round(), (l * 100.0).round());
+    *dish = DishData::Str(match alpha {
+        Some(a) => format!("hsla({}, {}%, {}%, {})", h, s, l, format_alpha(a)),
+        None => format!("hsl({}, {}%, {}%)", h, s, l),
+    });
+    Ok(())
+}
+
+pub static OPINFO_HSL_TO_HEX: OperationInfo = OperationInfo {
+    name: "hsl-to-hex",
+    description: "converts an `hsl(33, 100%, 50%)` or `hsla(...)` color to hex",
+    authors: &["s-viour"],
+    category: "Data Format",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: hsl_to_hex,
+};
+
+fn hsl_to_hex(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let args = parse_color_function(data, "hsl")?;
+    let h = parse_color_channel(args[0].trim_end_matches("deg"), 360.0, false)?;
+    let s = parse_color_channel(args[1], 100.0, true)? / 100.0;
+    let l = parse_color_channel(args[2], 100.0, true)? / 100.0;
+    let alpha = args.get(3).map(|a| parse_alpha(a)).transpose()?;
+
+    *dish = DishData::Str(format_hex_color((hsl_to_rgb(h, s, l), alpha)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::data_format::*;
+    use crate::{DishData, Operation, EMPTY_ARGS};
+
+    #[test]
+    fn test_base64_padding() {
+        let cases = [
+            ("hi", "standard", 1, "aGk="),
+            ("hi", "standard", 0, "aGk"),
+            ("h", "standard", 0, "aA"),
+            ("hey", "standard", 0, "aGV5"),
+            ("hi", "url-safe", 0, "aGk"),
+            ("hi", "url-safe-no-pad", 1, "aGk"),
+        ];
+
+        for (input, variant, pad, expected) in cases {
+            let mut args = OperationArguments::new();
+            args.insert("variant", variant.to_string());
+            args.insert("pad", pad);
+            let mut data = DishData::Str(input.to_string());
+            assert!(matches!(to_base64(&args, &mut data), Ok(())));
+            assert_eq!(
+                data,
+                DishData::Str(expected.to_string()),
+                "{} {}",
+                variant,
+                pad
+            );
+
+            // decoding doesn't care whether there was padding
+            let mut args = OperationArguments::new();
+            args.insert("variant", variant.to_string());
+            assert!(
+                matches!(from_base64(&args, &mut data), Ok(())),
+                "{}",
+                expected
+            );
+            assert_eq!(data.as_bytes(), input.as_bytes(), "{}", expected);
+        }
+    }
+
+    #[test]
+    fn test_base64_variants() {
+        // 0xfb 0xff encodes to `+` and `/` in the standard alphabet
+        let cases = [
+            ("standard", "+/8="),
+            ("url-safe", "-_8="),
+            ("url-safe-no-pad", "-_8"),
+        ];
+
+        for (variant, expected) in cases {
+            let mut args = OperationArguments::new();
+            args.insert("variant", variant.to_string());
+            args.insert("pad", 1);
+            let mut data = DishData::Bin(vec![0xfb, 0xff]);
+            assert!(matches!(to_base64(&args, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()), "{}", variant);
+
+            assert!(
+                matches!(from_base64(&args, &mut data), Ok(())),
+                "{}",
+                variant
+            );
+            assert_eq!(data, DishData::Bin(vec![0xfb, 0xff]), "{}", variant);
+        }
+
+        // either url-safe variant decodes with or without padding
+        for (variant, input) in [("url-safe", "-_8"), ("url-safe-no-pad", "-_8=")] {
+            let mut args = OperationArguments::new();
+            args.insert("variant", variant.to_string());
+            let mut data = DishData::Str(input.to_string());
+            assert!(matches!(from_base64(&args, &mut data), Ok(())), "{}", input);
+            assert_eq!(data, DishData::Bin(vec![0xfb, 0xff]));
+        }
+
+        // and the standard alphabet doesn't accept url-safe characters
+        let mut data = DishData::Str("-_8=".to_string());
+        let args = OPINFO_FROMBASE64.default_arguments();
+        assert!(from_base64(&args, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_to_octal() {
+        let mut data = DishData::Bin(vec![42]);
+        let _expected = DishData::Str(String::from("52"));
+        assert!(matches!(
+            to_octal(&OPINFO_TOOCTAL.default_arguments(), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_from_octal() {
+        let mut data = DishData::Str("150 145 154 154 157 40 167 157 162 154 144 41".to_string());
+        let _expected = DishData::Str("hello world!".to_string());
+        assert!(matches!(from_octal(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_to_hex() {
+        let mut data = DishData::Bin(vec![15]);
+        let _expected = DishData::Str(String::from("0f"));
+        assert!(matches!(
+            to_hex(&OPINFO_TOHEX.default_arguments(), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, _expected);
+
+        let mut data = DishData::Bin(vec![26]);
+        let _expected = DishData::Str(String::from("1a"));
+        assert!(matches!(
+            to_hex(&OPINFO_TOHEX.default_arguments(), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_from_hex() {
+        let mut data = DishData::Str(String::from("0f"));
+        let _expected = DishData::Str("\u{f}".to_string());
+
+        assert!(matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, _expected);
+
+        let mut data = DishData::Str(String::from("1a"));
+        let _expected = DishData::Str("\u{1a}".to_string());
+        assert!(matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_from_hex_formats() {
+        let hello = DishData::Str("hello".to_string());
+        for input in [
+            "68 65 6c 6c 6f",
+            "68656c6c6f",
+            "0x68656c6c6f",
+            "0x68 0x65 0x6C 0x6c 0x6F",
+            "68:65:6c:6c:6f",
+            "68-65-6c-6c-6f",
+            "  68656c\n6c6f\n",
+        ] {
+            let mut data = DishData::Str(input.to_string());
+            assert!(
+                matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())),
+                "{}",
+                input
+            );
+            assert_eq!(data, hello, "{}", input);
+        }
+
+        // single digits are still bytes on their own
+        let mut data = DishData::Str("f 1a".to_string());
+        assert!(matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("\u{f}\u{1a}".to_string()));
+
+        let mut data = DishData::Str("00ff".to_string());
+        assert!(matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(vec![0, 255]));
+
+        let mut data = DishData::Str("".to_string());
+        assert!(matches!(from_hex(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("".to_string()));
+
+        for input in ["68656", "0x686", "xyz", "68 6g"] {
+            let mut data = DishData::Str(input.to_string());
+            assert!(from_hex(&EMPTY_ARGS, &mut data).is_err(), "{}", input);
+        }
+        let mut data = DishData::Bin(vec![0x68]);
+        assert!(from_hex(&EMPTY_ARGS, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_from_binary() {
+        let mut data = DishData::Str("01101000 01100101 01101100 01101100 01101111".to_string());
+        let _expected = DishData::Str("hello".to_string());
+
+        assert!(matches!(from_binary(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_to_binary() {
+        let mut data = DishData::Str("hello world!".to_string());
+        let _expected = DishData::Str("01101000 01100101 01101100 01101100 01101111 00100000 01110111 01101111 01110010 01101100 01100100 00100001".to_string());
+
+        assert!(matches!(
+            to_binary(&OPINFO_TOBINARY.default_arguments(), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_from_decimal() {
+        let mut data = DishData::Str("104 101 108 108 111 32 119 111 114 108 100 33".to_string());
+        let _expected = DishData::Str("hello world!".to_string());
+
+        assert!(matches!(from_decimal(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_to_decimal() {
+        let mut data = DishData::Str("hello world!".to_string());
+        let _expected = DishData::Str("104 101 108 108 111 32 119 111 114 108 100 33".to_string());
+
+        assert!(matches!(
+            to_decimal(&OPINFO_TODECIMAL.default_arguments(), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, _expected);
+    }
+
+    #[test]
+    fn test_to_radix_string_round_trip() {
+        let every_byte: Vec<u8> = (0..=u8::MAX).collect();
+
+        for radix in 2..=36 {
+            for pad in [false, true] {
+                let mut data = DishData::Str(to_radix_string(&every_byte, radix, pad, " "));
+                assert!(
+                    matches!(from_radix_helper(radix, &mut data), Ok(())),
+                    "radix {} (pad {}) failed to decode",
+                    radix,
+                    pad
+                );
+                assert_eq!(data.as_bytes(), every_byte, "radix {} (pad {})", radix, pad);
+            }
+        }
+
+        assert_eq!(to_radix_string(&[1, 255], 16, true, " "), "01 ff");
+        assert_eq!(to_radix_string(&[1, 255], 3, true, " "), "000001 100110");
+        assert_eq!(to_radix_string(&[1, 255], 3, false, " "), "1 100110");
+
+        for radix in [0, 1, 37] {
+            let mut data = DishData::Str("1".to_string());
+            assert!(from_radix_helper(radix, &mut data).is_err());
+        }
+    }
+
+    #[test]
+    fn test_radix_delimiters() {
+        let cases = [
+            (to_hex as Operation, "", "68656c6c6f"),
+            (to_hex, ":", "68:65:6c:6c:6f"),
+            (to_hex, ", ", "68, 65, 6c, 6c, 6f"),
+            (to_binary, "", "0110100001100101011011000110110001101111"),
+            (to_octal, "-", "150-145-154-154-157"),
+            (to_decimal, ",", "104,101,108,108,111"),
+        ];
+
+        for (op, delimiter, expected) in cases {
+            let mut args = OperationArguments::new();
+            args.insert("delimiter", delimiter.to_string());
+            let mut data = DishData::Str("hello".to_string());
+            assert!(matches!(op(&args, &mut data), Ok(())), "{:?}", delimiter);
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+
+        // the separators the `from-` operations understand all round-trip
+        let pairs = [
+            (to_hex as Operation, from_hex as Operation, true),
+            (to_binary, from_binary, true),
+            (to_octal, from_octal, false),
+            (to_decimal, from_decimal, false),
+        ];
+        for (to, from, padded) in pairs {
+            for delimiter in ["", " ", ":", "-", ", "] {
+                // unpadded bytes can't be told apart without a delimiter
+                if delimiter.is_empty() && !padded {
+                    continue;
+                }
+                let mut args = OperationArguments::new();
+                args.insert("delimiter", delimiter.to_string());
+                let mut data = DishData::Bin(vec![0, 15, 255, 16]);
+                assert!(matches!(to(&args, &mut data), Ok(())));
+                assert!(
+                    matches!(from(&EMPTY_ARGS, &mut data), Ok(())),
+                    "{:?}",
+                    delimiter
+                );
+                assert_eq!(data, DishData::Bin(vec![0, 15, 255, 16]), "{:?}", delimiter);
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_radix() {
+        let mut args = OperationArguments::new();
+        args.insert("radix", 64);
+        let mut data = DishData::Str("hello".to_string());
+        assert!(matches!(to_radix(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("aGVsbG8=".to_string()));
+
+        for radix in [0, 1, 37] {
+            let mut args = OperationArguments::new();
+            args.insert("radix", radix);
+            assert!(to_radix(&args, &mut DishData::Str("hello".to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_url_encode() {
+        let mut data = DishData::Str("abcdefghijklmnopqrstuvwxyz!@#$%^&*()[]".to_string());
+        let _expected = DishData::Str(
+            "abcdefghijklmnopqrstuvwxyz%21%40%23%24%25%5E%26%2A%28%29%5B%5D".to_string(),
+        );
+
+        assert!(matches!(url_encode(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, _expected);
     }
 
     #[test]
@@ -491,4 +2439,732 @@ mod tests {
         assert!(matches!(url_decode(&EMPTY_ARGS, &mut data), Ok(())));
         assert_eq!(data, _expected);
     }
+
+    #[test]
+    fn test_hex_base64_round_trip() {
+        let mut data = DishData::Str("48 65 6C 6c 6F 00 ff".to_string());
+        assert!(matches!(hex_to_base64(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("SGVsbG8A/w==".to_string()));
+
+        assert!(matches!(base64_to_hex(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("48 65 6c 6c 6f 00 ff".to_string()));
+    }
+
+    #[test]
+    fn test_hex_base64_invalid() {
+        let mut data = DishData::Str("not hex".to_string());
+        assert!(hex_to_base64(&EMPTY_ARGS, &mut data).is_err());
+
+        let mut data = DishData::Str("!!!!".to_string());
+        assert!(base64_to_hex(&EMPTY_ARGS, &mut data).is_err());
+    }
+
+    // the example token from jwt.io
+    static JWT: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+                        eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+                        SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+
+    #[test]
+    fn test_jwt_decode() {
+        let mut data = DishData::Str(JWT.to_string());
+        assert!(matches!(jwt_decode(&EMPTY_ARGS, &mut data), Ok(())));
+
+        let decoded: serde_json::Value = match &data {
+            DishData::Str(s) => serde_json::from_str(s).unwrap(),
+            DishData::Bin(_) => panic!("expected a string dish"),
+        };
+        assert_eq!(decoded["header"]["alg"], "HS256");
+        assert_eq!(decoded["payload"]["name"], "John Doe");
+        assert_eq!(decoded["payload"]["iat"], 1516239022);
+        assert_eq!(
+            decoded["signature"],
+            "SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c"
+        );
+    }
+
+    #[test]
+    fn test_jwt_decode_invalid() {
+        let (header, rest) = JWT.split_once('.').unwrap();
+        let tokens = [
+            // too few parts
+            header.to_string(),
+            // not base64url
+            format!("{}.!!!.sig", header),
+            // valid base64url, but not JSON ("hello")
+            format!("{}.aGVsbG8.sig", header),
+            format!("{}.{}.extra", header, rest),
+        ];
+
+        for token in tokens {
+            let mut data = DishData::Str(token);
+            assert!(jwt_decode(&EMPTY_ARGS, &mut data).is_err());
+        }
+    }
+
+    static UGLY_JSON: &str = "{ \"b\": [1, 2.5, {\"c\": null}], \"a\" : \"x y\", \"e\": {} }";
+
+    #[test]
+    fn test_json_beautify() {
+        let mut args = OperationArguments::new();
+        args.insert("indent", 2);
+        let mut data = DishData::Str(UGLY_JSON.to_string());
+        assert!(matches!(json_beautify(&args, &mut data), Ok(())));
+
+        // keys stay in their original order
+        let expected = "{\n  \"b\": [\n    1,\n    2.5,\n    {\n      \"c\": null\n    }\n  ],\n  \"a\": \"x y\",\n  \"e\": {}\n}";
+        assert_eq!(data, DishData::Str(expected.to_string()));
+    }
+
+    #[test]
+    fn test_json_minify() {
+        let mut data = DishData::Str(UGLY_JSON.to_string());
+        assert!(matches!(json_minify(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Str("{\"b\":[1,2.5,{\"c\":null}],\"a\":\"x y\",\"e\":{}}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        let mut data = DishData::Str("{\"a\": [1, 2}".to_string());
+        match json_minify(&EMPTY_ARGS, &mut data) {
+            Err(e) => assert!(e.to_string().contains("line 1 column 12")),
+            Ok(()) => panic!("expected invalid JSON to fail"),
+        }
+
+        let mut args = OperationArguments::new();
+        args.insert("indent", 4);
+        let mut data = DishData::Str("".to_string());
+        assert!(json_beautify(&args, &mut data).is_err());
+    }
+
+    fn xml_beautify_str(s: &str, indent: i64) -> Result<String, DishError> {
+        let mut args = OperationArguments::new();
+        args.insert("indent", indent);
+        let mut data = DishData::Str(s.to_string());
+        xml_beautify(&args, &mut data)?;
+        match data {
+            DishData::Str(s) => Ok(s),
+            DishData::Bin(_) => panic!("expected a string dish"),
+        }
+    }
+
+    #[test]
+    fn test_xml_beautify() {
+        let xml = "<?xml version=\"1.0\"?><root><item id=\"a>b\">one</item><empty/>\
+                   <!-- note --><list>  <x></x>text<y/></list></root>";
+        let expected = "<?xml version=\"1.0\"?>\n\
+                        <root>\n\
+                        \x20 <item id=\"a>b\">one</item>\n\
+                        \x20 <empty/>\n\
+                        \x20 <!-- note -->\n\
+                        \x20 <list>\n\
+                        \x20   <x></x>\n\
+                        \x20   text\n\
+                        \x20   <y/>\n\
+                        \x20 </list>\n\
+                        </root>";
+        assert_eq!(xml_beautify_str(xml, 2).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_xml_beautify_html() {
+        let html = "<html><head><meta charset=\"utf-8\"><script>if (a < b) {}</script></head>\
+                    <body><p>hi<br>there</p></body></html>";
+        let expected = "<html>\n\
+                        \x20<head>\n\
+                        \x20 <meta charset=\"utf-8\">\n\
+                        \x20 <script>if (a < b) {}</script>\n\
+                        \x20</head>\n\
+                        \x20<body>\n\
+                        \x20 <p>\n\
+                        \x20  hi\n\
+                        \x20  <br>\n\
+                        \x20  there\n\
+                        \x20 </p>\n\
+                        \x20</body>\n\
+                        </html>";
+        assert_eq!(xml_beautify_str(html, 1).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_xml_beautify_unbalanced() {
+        for s in [
+            "<a><b></a></b>",
+            "<a>",
+            "</a>",
+            "<a><b></b>",
+            "<a attr=\"unterminated>",
+        ] {
+            assert!(xml_beautify_str(s, 2).is_err());
+        }
+    }
+
+    #[test]
+    fn test_beautify_indent_limit() {
+        assert!(xml_beautify_str("<a></a>", MAX_INDENT).is_ok());
+        for indent in [-1, MAX_INDENT + 1, i64::MAX] {
+            assert!(xml_beautify_str("<a></a>", indent).is_err());
+
+            let mut args = OperationArguments::new();
+            args.insert("indent", indent);
+            let mut data = DishData::Str(UGLY_JSON.to_string());
+            assert!(json_beautify(&args, &mut data).is_err());
+        }
+    }
+
+    fn csv_args(delimiter: &str, header: i64) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("delimiter", delimiter.to_string());
+        args.insert("header", header);
+        args
+    }
+
+    static CSV: &str = "name,quote\nada,\"hello, world\"\nbob,\"line one\nline \"\"two\"\"\"\n";
+
+    #[test]
+    fn test_csv_to_json() {
+        let mut data = DishData::Str(CSV.to_string());
+        assert!(matches!(csv_to_json(&csv_args(",", 1), &mut data), Ok(())));
+        let expected = r#"[{"name":"ada","quote":"hello, world"},{"name":"bob","quote":"line one\nline \"two\""}]"#;
+        assert_eq!(data, DishData::Str(expected.to_string()));
+
+        let mut data = DishData::Str("a;b\r\nc;d".to_string());
+        assert!(matches!(csv_to_json(&csv_args(";", 0), &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(r#"[["a","b"],["c","d"]]"#.to_string()));
+
+        let mut data = DishData::Str("a,b\nc".to_string());
+        assert!(csv_to_json(&csv_args(",", 1), &mut data).is_err());
+        let mut data = DishData::Str("\"a,b".to_string());
+        assert!(csv_to_json(&csv_args(",", 0), &mut data).is_err());
+    }
+
+    #[test]
+    fn test_json_to_csv_round_trip() {
+        let mut data = DishData::Str(CSV.to_string());
+        assert!(matches!(csv_to_json(&csv_args(",", 1), &mut data), Ok(())));
+        assert!(matches!(json_to_csv(&csv_args(",", 1), &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(CSV.trim_end().to_string()));
+
+        let mut data = DishData::Str(r#"[{"a": 1, "b": null}, {"c": true}]"#.to_string());
+        assert!(matches!(json_to_csv(&csv_args("\t", 1), &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("a\tb\tc\n1\t\t\n\t\ttrue".to_string()));
+    }
+
+    #[test]
+    fn test_json_to_csv_invalid() {
+        for s in [
+            r#"{"a": 1}"#,
+            r#"[1, 2]"#,
+            r#"[{"a": [1]}]"#,
+            r#"[{"a": {"b": 1}}]"#,
+        ] {
+            let mut data = DishData::Str(s.to_string());
+            assert!(json_to_csv(&csv_args(",", 1), &mut data).is_err());
+        }
+
+        let mut data = DishData::Str("[]".to_string());
+        assert!(json_to_csv(&csv_args(",,", 1), &mut data).is_err());
+    }
+
+    fn table(s: &str, format: &str, header: i64) -> Result<String, DishError> {
+        let mut args = csv_args(",", header);
+        args.insert("format", format.to_string());
+        let mut data = DishData::Str(s.to_string());
+        to_table(&args, &mut data)?;
+        match data {
+            DishData::Str(s) => Ok(s),
+            DishData::Bin(_) => panic!("expected a string dish"),
+        }
+    }
+
+    #[test]
+    fn test_to_table_ascii() {
+        let expected = "+------+-----+\n\
+                        | name | age |\n\
+                        +======+=====+\n\
+                        | ada  | 36  |\n\
+                        | bob  |     |\n\
+                        +------+-----+";
+        assert_eq!(
+            table("name,age\nada,36\nbob", "ascii", 1).unwrap(),
+            expected
+        );
+
+        let expected = "+---+---+\n| 1 | 2 |\n| 3 | 4 |\n+---+---+";
+        assert_eq!(table("1,2\n3,4", "ascii", 0).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_table_markdown() {
+        let expected = "| name | a\\|b |\n\
+                        |------|------|\n\
+                        | ada  | x    |";
+        assert_eq!(table("name,a|b\nada,x", "markdown", 1).unwrap(), expected);
+
+        let expected = "|     |     |\n|-----|-----|\n| 1   | 2   |";
+        assert_eq!(table("1,2", "markdown", 0).unwrap(), expected);
+    }
+
+    fn extract_args(pattern: &str, template: &str) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("pattern", pattern.to_string());
+        args.insert("template", template.to_string());
+        args
+    }
+
+    static LOG: &str = "10.0.0.1 GET /index.html 200\n\
+                        10.0.0.2 POST /login 401\n\
+                        not a request line";
+
+    #[test]
+    fn test_regex_extract() {
+        let args = extract_args(r"(?P<ip>[\d.]+) (\w+) (\S+) (\d+)", "$2 ${ip} -> $4 ($$)");
+        let mut data = DishData::Str(LOG.to_string());
+        assert!(matches!(regex_extract(&args, &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Str("GET 10.0.0.1 -> 200 ($)\nPOST 10.0.0.2 -> 401 ($)".to_string())
+        );
+
+        let mut data = DishData::Str("no matches here".to_string());
+        assert!(matches!(regex_extract(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(String::new()));
+    }
+
+    #[test]
+    fn test_regex_extract_invalid() {
+        let cases = [
+            ("(unclosed", "$1"),
+            (r"(\d+)", "$2"),
+            (r"(?P<n>\d+)", "${name}"),
+            (r"(\d+)", "$1x"),
+        ];
+        for (pattern, template) in cases {
+            let mut data = DishData::Str(LOG.to_string());
+            assert!(regex_extract(&extract_args(pattern, template), &mut data).is_err());
+        }
+    }
+
+    static INDICATORS: &str = "beacon to 192.168.1.20 and 10.0.0.300 from fe80::1 and 2001:db8::8a2e:370:7334, \
+        mail admin@example.com or Bob.Smith+tag@mail.example.org. see https://example.com/path?q=1. \
+        again 192.168.1.20! md5 d41d8cd98f00b204e9800998ecf8427e sha1 da39a3ee5e6b4b0d3255bfef95601890afd80709";
+
+    fn extract_str(typ: &str, unique: i64) -> String {
+        let mut args = OperationArguments::new();
+        args.insert("type", typ.to_string());
+        args.insert("unique", unique);
+        let mut data = DishData::Str(INDICATORS.to_string());
+        assert!(matches!(extract(&args, &mut data), Ok(())));
+        match data {
+            DishData::Str(s) => s,
+            DishData::Bin(_) => panic!("expected a string dish"),
+        }
+    }
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(extract_str("ip", 1), "192.168.1.20");
+        assert_eq!(extract_str("ip", 0), "192.168.1.20\n192.168.1.20");
+        assert_eq!(extract_str("ipv6", 1), "fe80::1\n2001:db8::8a2e:370:7334");
+        assert_eq!(
+            extract_str("email", 1),
+            "admin@example.com\nBob.Smith+tag@mail.example.org"
+        );
+        assert_eq!(extract_str("url", 1), "https://example.com/path?q=1");
+        assert_eq!(extract_str("domain", 1), "example.com\nmail.example.org");
+        assert_eq!(
+            extract_str("hash", 1),
+            "d41d8cd98f00b204e9800998ecf8427e\nda39a3ee5e6b4b0d3255bfef95601890afd80709"
+        );
+    }
+
+    #[test]
+    fn test_to_hexdump() {
+        let mut data = DishData::Str("hello world!\n\x00\x7f codebake".to_string());
+        assert!(matches!(to_hexdump(&EMPTY_ARGS, &mut data), Ok(())));
+        let expected =
+            "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 21 0a 00 7f 20  |hello world!... |\n\
+                        00000010  63 6f 64 65 62 61 6b 65                           |codebake|\n\
+                        00000018";
+        assert_eq!(data, DishData::Str(expected.to_string()));
+
+        let mut data = DishData::Bin(vec![]);
+        assert!(matches!(to_hexdump(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("00000000".to_string()));
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let mut args = OperationArguments::new();
+        args.insert("width", 4);
+
+        let mut data = DishData::Str("hi\tyou\r\n\nné".to_string());
+        assert!(matches!(side_by_side(&args, &mut data), Ok(())));
+        let expected = "hi.y | 68 69 09 79\n\
+                        ou.  | 6f 75 0d\n     | \n\
+                        né   | 6e c3 a9";
+        assert_eq!(data, DishData::Str(expected.to_string()));
+
+        assert!(side_by_side(&args, &mut DishData::Bin(vec![0, 1])).is_err());
+        for width in [0, MAX_SIDE_BY_SIDE_WIDTH + 1, i64::MAX] {
+            let mut args = OperationArguments::new();
+            args.insert("width", width);
+            assert!(side_by_side(&args, &mut DishData::Str("hi".to_string())).is_err());
+        }
+    }
+
+    fn flags_args(names: &str) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("names", names.to_string());
+        args
+    }
+
+    #[test]
+    fn test_decode_flags() {
+        let names = "read, write, exec";
+        let cases = [
+            ("5", "read\nexec"),
+            ("mask: 0x6 (other bits ignored)", "write\nexec"),
+            ("0", ""),
+            ("0X11", "read\nbit 4"),
+        ];
+
+        for (input, expected) in cases {
+            let mut data = DishData::Str(input.to_string());
+            assert!(matches!(
+                decode_flags(&flags_args(names), &mut data),
+                Ok(())
+            ));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+
+        let mut data = DishData::Str("3".to_string());
+        assert!(matches!(
+            decode_flags(&flags_args(",second"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str("bit 0\nsecond".to_string()));
+
+        let too_many = vec!["x"; 65].join(",");
+        for (input, names) in [
+            ("1", too_many.as_str()),
+            ("no flags", names),
+            ("99999999999999999999", names),
+        ] {
+            let mut data = DishData::Str(input.to_string());
+            assert!(decode_flags(&flags_args(names), &mut data).is_err());
+        }
+        assert!(decode_flags(&flags_args(names), &mut DishData::Bin(vec![1])).is_err());
+    }
+
+    #[test]
+    fn test_ip_to_int_round_trip() {
+        let cases = [
+            ("192.168.0.1", "3232235521", 4),
+            ("0.0.0.0", "0", 4),
+            ("255.255.255.255", "4294967295", 4),
+            ("::1", "1", 6),
+            (
+                "2001:db8::ff00:42:8329",
+                "42540766411282592856904265327123268393",
+                6,
+            ),
+        ];
+
+        for (ip, n, version) in cases {
+            let mut data = DishData::Str(ip.to_string());
+            assert!(matches!(ip_to_int(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(n.to_string()));
+
+            let mut args = OperationArguments::new();
+            args.insert("version", version);
+            assert!(matches!(int_to_ip(&args, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(ip.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_ip_invalid() {
+        for ip in ["256.0.0.1", "1.2.3", "hello", "::g"] {
+            assert!(ip_to_int(&EMPTY_ARGS, &mut DishData::Str(ip.to_string())).is_err());
+        }
+
+        for (n, version) in [("4294967296", 4), ("-1", 6), ("1", 5)] {
+            let mut args = OperationArguments::new();
+            args.insert("version", version);
+            assert!(int_to_ip(&args, &mut DishData::Str(n.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_cidr_range() {
+        let cases = [
+            (
+                "192.168.0.0/30",
+                "192.168.0.0\n192.168.0.1\n192.168.0.2\n192.168.0.3",
+            ),
+            ("10.0.0.7/31", "10.0.0.6\n10.0.0.7"),
+            ("10.0.0.7/32", "10.0.0.7"),
+            ("2001:db8::/127", "2001:db8::\n2001:db8::1"),
+            // blocks at the very top of the address space
+            ("255.255.255.254/31", "255.255.255.254\n255.255.255.255"),
+            (
+                "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/127",
+                "ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe\nffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+            ),
+        ];
+
+        for (cidr, expected) in cases {
+            let mut data = DishData::Str(cidr.to_string());
+            assert!(matches!(cidr_range(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+
+        let mut data = DishData::Str("10.0.0.0/16".to_string());
+        assert!(matches!(cidr_range(&EMPTY_ARGS, &mut data), Ok(())));
+
+        for cidr in [
+            "10.0.0.0",
+            "10.0.0.0/33",
+            "10.0.0.0/15",
+            "::/0",
+            "10.0.0/24",
+        ] {
+            assert!(cidr_range(&EMPTY_ARGS, &mut DishData::Str(cidr.to_string())).is_err());
+        }
+    }
+
+    fn int_args(width: i64, endian: &str, signed: i64) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("width", width);
+        args.insert("endian", endian.to_string());
+        args.insert("signed", signed);
+        args
+    }
+
+    #[test]
+    fn test_read_int() {
+        let cases = [
+            (vec![0x2a], 1, "big", 0, "42"),
+            (vec![0xff], 1, "big", 0, "255"),
+            (vec![0xff], 1, "big", 1, "-1"),
+            (vec![0x12, 0x34, 0xff], 2, "big", 0, "4660"),
+            (vec![0x12, 0x34], 2, "little", 0, "13330"),
+            (vec![0x00, 0x80], 2, "little", 1, "-32768"),
+            (vec![0xde, 0xad, 0xbe, 0xef], 4, "big", 0, "3735928559"),
+            (vec![0xff; 8], 8, "little", 0, "18446744073709551615"),
+            (vec![0xff; 8], 8, "big", 1, "-1"),
+        ];
+
+        for (bytes, width, endian, signed, expected) in cases {
+            let mut data = DishData::Bin(bytes.clone());
+            let args = int_args(width, endian, signed);
+            assert!(matches!(read_int(&args, &mut data), Ok(())), "{:?}", bytes);
+            assert_eq!(data, DishData::Str(expected.to_string()), "{:?}", bytes);
+
+            // and writing it back gives the bytes that were read
+            assert!(
+                matches!(write_int(&args, &mut data), Ok(())),
+                "{}",
+                expected
+            );
+            assert_eq!(data.as_bytes(), &bytes[..width as usize], "{}", expected);
+        }
+
+        assert!(read_int(&int_args(4, "big", 0), &mut DishData::Bin(vec![1, 2, 3])).is_err());
+        assert!(read_int(&int_args(3, "big", 0), &mut DishData::Bin(vec![1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn test_write_int() {
+        let mut data = DishData::Str("258".to_string());
+        assert!(matches!(
+            write_int(&int_args(4, "little", 0), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Bin(vec![2, 1, 0, 0]));
+
+        let mut data = DishData::Str(" -2\n".to_string());
+        assert!(matches!(
+            write_int(&int_args(2, "big", 1), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Bin(vec![0xff, 0xfe]));
+
+        for (input, width, signed) in [("256", 1, 0), ("128", 1, 1), ("-1", 2, 0), ("lots", 8, 0)] {
+            let mut data = DishData::Str(input.to_string());
+            let res = write_int(&int_args(width, "big", signed), &mut data);
+            assert!(res.is_err(), "{}", input);
+        }
+    }
+
+    fn convert_color(op: Operation, input: &str) -> DishData {
+        let mut data = DishData::Str(input.to_string());
+        if let Err(e) = op(&EMPTY_ARGS, &mut data) {
+            panic!("failed to convert {}: {}", input, e);
+        }
+        data
+    }
+
+    #[test]
+    fn test_rgb_round_trip() {
+        let cases = [
+            ("#ff8800", "rgb(255, 136, 0)"),
+            ("#000000", "rgb(0, 0, 0)"),
+            ("#1e90ff", "rgb(30, 144, 255)"),
+            ("#ff000080", "rgba(255, 0, 0, 0.502)"),
+            ("#ffffff00", "rgba(255, 255, 255, 0)"),
+        ];
+
+        for (hex, rgb) in cases {
+            assert_eq!(
+                convert_color(hex_to_rgb, hex),
+                DishData::Str(rgb.to_string())
+            );
+            assert_eq!(
+                convert_color(rgb_to_hex, rgb),
+                DishData::Str(hex.to_string())
+            );
+        }
+
+        // shorthand is expanded and the # is optional
+        let rgb = DishData::Str("rgb(255, 136, 0)".to_string());
+        assert_eq!(convert_color(hex_to_rgb, "#f80"), rgb);
+        assert_eq!(convert_color(hex_to_rgb, "FF8800"), rgb);
+        assert_eq!(
+            convert_color(hex_to_rgb, "#f808"),
+            DishData::Str("rgba(255, 136, 0, 0.533)".to_string())
+        );
+        assert_eq!(
+            convert_color(rgb_to_hex, "rgb(255,136,0)"),
+            DishData::Str("#ff8800".to_string())
+        );
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        let cases = [
+            ("#ff0000", "hsl(0, 100%, 50%)"),
+            ("#00ff00", "hsl(120, 100%, 50%)"),
+            ("#0000ff", "hsl(240, 100%, 50%)"),
+            ("#ffffff", "hsl(0, 0%, 100%)"),
+            ("#808080", "hsl(0, 0%, 50%)"),
+            ("#ff00ff80", "hsla(300, 100%, 50%, 0.502)"),
+        ];
+
+        for (hex, hsl) in cases {
+            assert_eq!(
+                convert_color(hex_to_hsl, hex),
+                DishData::Str(hsl.to_string())
+            );
+            assert_eq!(
+                convert_color(hsl_to_hex, hsl),
+                DishData::Str(hex.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_color_invalid() {
+        for hex in ["#ff8800a", "#ggg", "#12345", ""] {
+            assert!(hex_to_rgb(&EMPTY_ARGS, &mut DishData::Str(hex.to_string())).is_err());
+        }
+        for rgb in [
+            "rgb(256, 0, 0)",
+            "rgb(1, 2)",
+            "rgb(1.5, 2, 3)",
+            "rgba(1, 2, 3, 2)",
+            "hsl(1, 2, 3)",
+        ] {
+            assert!(rgb_to_hex(&EMPTY_ARGS, &mut DishData::Str(rgb.to_string())).is_err());
+        }
+        for hsl in ["hsl(400, 50%, 50%)", "hsl(10, 150%, 50%)", "rgb(1, 2, 3)"] {
+            assert!(hsl_to_hex(&EMPTY_ARGS, &mut DishData::Str(hsl.to_string())).is_err());
+        }
+    }
+
+    fn transpose_args(delimiter: &str, strict: i64) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("delimiter", delimiter.to_string());
+        args.insert("strict", strict);
+        args
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut data = DishData::Str("name,age\nalice,30\nbob,25\n".to_string());
+        assert!(matches!(
+            transpose(&transpose_args(",", 1), &mut data),
+            Ok(())
+        ));
+        assert_eq!(
+            data,
+            DishData::Str("name,alice,bob\nage,30,25\n".to_string())
+        );
+
+        // transposing twice gives back the original
+        assert!(matches!(
+            transpose(&transpose_args(",", 1), &mut data),
+            Ok(())
+        ));
+        assert_eq!(
+            data,
+            DishData::Str("name,age\nalice,30\nbob,25\n".to_string())
+        );
+
+        let mut data = DishData::Str("a | b | c\nd".to_string());
+        assert!(matches!(
+            transpose(&transpose_args(" | ", 0), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str("a | d\nb | \nc | ".to_string()));
+
+        let mut data = DishData::Str("a,b,c\nd".to_string());
+        assert!(transpose(&transpose_args(",", 1), &mut data).is_err());
+        assert!(transpose(&transpose_args("", 0), &mut data).is_err());
+    }
+
+    #[test]
+    fn test_query_string_round_trip() {
+        let query = "a=1&long_key=hello%20world&long_key=2&flag&empty=";
+        let listing = "a        = 1\n\
+                       long_key = hello world\n\
+                       long_key = 2\n\
+                       flag\n\
+                       empty    = ";
+
+        let mut data = DishData::Str(query.to_string());
+        assert!(matches!(parse_query_string(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(listing.to_string()));
+
+        assert!(matches!(build_query_string(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(query.to_string()));
+    }
+
+    #[test]
+    fn test_parse_query_string() {
+        let mut data = DishData::Str(
+            "https://example.com/search?q=rust+lang&x=%26%3D&&lang=en#top".to_string(),
+        );
+        assert!(matches!(parse_query_string(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Str("q    = rust lang\nx    = &=\nlang = en".to_string())
+        );
+
+        assert!(matches!(build_query_string(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Str("q=rust%20lang&x=%26%3D&lang=en".to_string())
+        );
+
+        let mut data = DishData::Str(String::new());
+        assert!(matches!(parse_query_string(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(String::new()));
+
+        let mut data = DishData::Str("a=%ff".to_string());
+        assert!(parse_query_string(&EMPTY_ARGS, &mut data).is_err());
+    }
 }
@@ -0,0 +1,602 @@
+use crate::{DishData, DishError, DishResult, OperationArgType, OperationArguments, OperationInfo};
+
+static MONTH_NAMES: &[&str] = &[
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// weekday names, starting from sunday
+static WEEKDAY_NAMES: &[&str] = &[
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// A broken-down date and time, along with the UTC offset it was written in
+///
+#[derive(Debug, PartialEq)]
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    millis: u32,
+    /// offset from UTC in seconds, e.g `-18000` for `-0500`
+    offset: i64,
+}
+
+/// helper function that converts a date to the number of days since 1970-01-01
+/// this is the `days_from_civil` algorithm from http://howardhinnant.github.io/date_algorithms.html
+///
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// helper function that converts a number of days since 1970-01-01 to a date
+/// the inverse of `days_from_civil`
+///
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl DateTime {
+    /// Creates a UTC `DateTime` from milliseconds since the epoch
+    fn from_millis(millis: i64) -> DateTime {
+        let secs = millis.div_euclid(1000);
+        let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+        let time = secs.rem_euclid(86400);
+
+        DateTime {
+            year,
+            month,
+            day,
+            hour: (time / 3600) as u32,
+            minute: (time / 60 % 60) as u32,
+            second: (time % 60) as u32,
+            millis: millis.rem_euclid(1000) as u32,
+            offset: 0,
+        }
+    }
+
+    /// Milliseconds since the epoch, taking the offset into account,
+    /// or `None` if that doesn't fit in an `i64`
+    fn to_millis(&self) -> Option<i64> {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let time =
+            self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64 - self.offset;
+        days.checked_mul(86400)?
+            .checked_add(time)?
+            .checked_mul(1000)?
+            .checked_add(self.millis as i64)
+    }
+
+    /// 0 is sunday
+    fn weekday(&self) -> usize {
+        (days_from_civil(self.year, self.month, self.day) + 4).rem_euclid(7) as usize
+    }
+
+    fn day_of_year(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1
+    }
+
+    fn hour12(&self) -> u32 {
+        match self.hour % 12 {
+            0 => 12,
+            h => h,
+        }
+    }
+}
+
+/// helper function that expands strftime shorthands like `%F` into
+/// the specifiers they stand for
+///
+fn expand_format(format: &str) -> String {
+    format
+        .replace("%F", "%Y-%m-%d")
+        .replace("%T", "%H:%M:%S")
+        .replace("%D", "%m/%d/%y")
+        .replace("%R", "%H:%M")
+        .replace("%h", "%b")
+}
+
+/// helper function that renders a `DateTime` with a strftime-style format string
+///
+fn format_datetime(dt: &DateTime, format: &str) -> Result<String, DishError> {
+    let format = expand_format(format);
+    let mut out = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+
+        let spec = match chars.next() {
+            Some('3') if chars.next() == Some('f') => "3f".to_string(),
+            Some(s) => s.to_string(),
+            None => {
+                return Err(DishError::invalid_argument(
+                    "format string ends with a lone `%`",
+                ))
+            }
+        };
+
+        out += &match spec.as_ref() {
+            "Y" => format!("{:04}", dt.year),
+            "C" => format!("{:02}", dt.year.div_euclid(100)),
+            "y" => format!("{:02}", dt.year.rem_euclid(100)),
+            "m" => format!("{:02}", dt.month),
+            "d" => format!("{:02}", dt.day),
+            "e" => format!("{:>2}", dt.day),
+            "j" => format!("{:03}", dt.day_of_year()),
+            "H" => format!("{:02}", dt.hour),
+            "I" => format!("{:02}", dt.hour12()),
+            "M" => format!("{:02}", dt.minute),
+            "S" => format!("{:02}", dt.second),
+            "3f" => format!("{:03}", dt.millis),
+            "f" => format!("{:09}", dt.millis * 1_000_000),
+            "p" => (if dt.hour < 12 { "AM" } else { "PM" }).to_string(),
+            "B" => MONTH_NAMES[dt.month as usize - 1].to_string(),
+            "b" => MONTH_NAMES[dt.month as usize - 1][..3].to_string(),
+            "A" => WEEKDAY_NAMES[dt.weekday()].to_string(),
+            "a" => WEEKDAY_NAMES[dt.weekday()][..3].to_string(),
+            "u" => match dt.weekday() {
+                0 => "7".to_string(),
+                d => d.to_string(),
+            },
+            "w" => dt.weekday().to_string(),
+            "s" => match dt.to_millis() {
+                Some(millis) => millis.div_euclid(1000).to_string(),
+                None => return Err(DishError::decode("the date is out of range")),
+            },
+            "z" => "+0000".to_string(),
+            "Z" => "UTC".to_string(),
+            "n" => "\n".to_string(),
+            "t" => "\t".to_string(),
+            "%" => "%".to_string(),
+            other => {
+                return Err(DishError::unsupported(format!(
+                    "unsupported format specifier `%{}`",
+                    other
+                )))
+            }
+        };
+    }
+
+    Ok(out)
+}
+
+/// helper function that consumes between `min` and `max` ascii digits from the
+/// front of `s` and returns their value along with the rest of the string
+///
+fn take_number(s: &str, min: usize, max: usize) -> Option<(i64, &str)> {
+    let len = s
+        .bytes()
+        .take(max)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if len < min {
+        return None;
+    }
+    Some((s[..len].parse().ok()?, &s[len..]))
+}
+
+/// helper function that consumes a (case-insensitive) name from `names` from the
+/// front of `s`. abbreviated names are the first three letters of the full name
+///
+fn take_name<'a>(s: &'a str, names: &[&str], abbreviated: bool) -> Option<(usize, &'a str)> {
+    names.iter().enumerate().find_map(|(i, name)| {
+        let name = if abbreviated { &name[..3] } else { name };
+        match s.get(..name.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(name) => Some((i, &s[name.len()..])),
+            _ => None,
+        }
+    })
+}
+
+/// helper function that parses a date string according to a strftime-style format string
+/// fields missing from the format default to the epoch, i.e `1970-01-01 00:00:00 UTC`
+///
+fn parse_datetime(s: &str, format: &str) -> Result<DateTime, DishError> {
+    let format = expand_format(format);
+    let invalid = || DishError::decode(format!("`{}` does not match the format `{}`", s, format));
+
+    let mut dt = DateTime::from_millis(0);
+    let mut pm = None;
+    let mut timestamp = None;
+    let mut rest = s;
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            rest = rest.trim_start();
+            continue;
+        }
+        if c != '%' {
+            rest = rest.strip_prefix(c).ok_or_else(invalid)?;
+            continue;
+        }
+
+        let spec = match chars.next() {
+            Some('3') if chars.next() == Some('f') => '3',
+            Some(spec) => spec,
+            None => {
+                return Err(DishError::invalid_argument(
+                    "format string ends with a lone `%`",
+                ))
+            }
+        };
+
+        rest = match spec {
+            'Y' => {
+                let (sign, r) = match rest.strip_prefix('-') {
+                    Some(r) => (-1, r),
+                    None => (1, rest),
+                };
+                let (year, r) = take_number(r, 1, 9).ok_or_else(invalid)?;
+                dt.year = sign * year;
+                r
+            }
+            'y' => {
+                // the same pivot as POSIX strptime, 69-99 are 1900s
+                let (year, r) = take_number(rest, 2, 2).ok_or_else(invalid)?;
+                dt.year = if year < 69 { 2000 + year } else { 1900 + year };
+                r
+            }
+            'm' | 'd' | 'e' | 'H' | 'I' | 'M' | 'S' => {
+                let (n, r) = take_number(rest.trim_start(), 1, 2).ok_or_else(invalid)?;
+                match spec {
+                    'm' => dt.month = n as u32,
+                    'd' | 'e' => dt.day = n as u32,
+                    'H' | 'I' => dt.hour = n as u32,
+                    'M' => dt.minute = n as u32,
+                    _ => dt.second = n as u32,
+                }
+                r
+            }
+            '3' => {
+                let (n, r) = take_number(rest, 3, 3).ok_or_else(invalid)?;
+                dt.millis = n as u32;
+                r
+            }
+            'f' => {
+                let (n, r) = take_number(rest, 9, 9).ok_or_else(invalid)?;
+                dt.millis = (n / 1_000_000) as u32;
+                r
+            }
+            'p' => match rest.get(..2).map(|p| p.to_ascii_uppercase()) {
+                Some(p) if p == "AM" || p == "PM" => {
+                    pm = Some(p == "PM");
+                    &rest[2..]
+                }
+                _ => return Err(invalid()),
+            },
+            'B' | 'b' => {
+                let (month, r) = take_name(rest, MONTH_NAMES, spec == 'b').ok_or_else(invalid)?;
+                dt.month = month as u32 + 1;
+                r
+            }
+            'A' | 'a' => {
+                take_name(rest, WEEKDAY_NAMES, spec == 'a')
+                    .ok_or_else(invalid)?
+                    .1
+            }
+            's' => {
+                let (sign, r) = match rest.strip_prefix('-') {
+                    Some(r) => (-1, r),
+                    None => (1, rest),
+                };
+                let (secs, r) = take_number(r, 1, 18).ok_or_else(invalid)?;
+                timestamp = Some(sign * secs);
+                r
+            }
+            'z' => {
+                if let Some(r) = rest.strip_prefix('Z') {
+                    dt.offset = 0;
+                    r
+                } else {
+                    let (sign, r) = match rest.chars().next() {
+                        Some('+') => (1, &rest[1..]),
+                        Some('-') => (-1, &rest[1..]),
+                        _ => return Err(invalid()),
+                    };
+                    let (hours, r) = take_number(r, 2, 2).ok_or_else(invalid)?;
+                    let r = r.strip_prefix(':').unwrap_or(r);
+                    let (minutes, r) = take_number(r, 2, 2).ok_or_else(invalid)?;
+                    if hours > 23 || minutes > 59 {
+                        return Err(DishError::decode(format!(
+                            "`{}` is not a valid UTC offset",
+                            &rest[..rest.len() - r.len()]
+                        )));
+                    }
+                    dt.offset = sign * (hours * 3600 + minutes * 60);
+                    r
+                }
+            }
+            'Z' => ["UTC", "GMT", "Z"]
+                .iter()
+                .find_map(|z| rest.strip_prefix(z))
+                .ok_or_else(invalid)?,
+            'n' | 't' => rest.trim_start(),
+            '%' => rest.strip_prefix('%').ok_or_else(invalid)?,
+            other => {
+                return Err(DishError::unsupported(format!(
+                    "unsupported format specifier `%{}`",
+                    other
+                )))
+            }
+        };
+    }
+
+    if !rest.is_empty() {
+        return Err(DishError::decode(format!(
+            "unexpected trailing input `{}` after the date",
+            rest
+        )));
+    }
+
+    if let Some(secs) = timestamp {
+        let millis = secs
+            .checked_mul(1000)
+            .ok_or_else(|| DishError::decode(format!("timestamp `{}` is out of range", secs)))?;
+        return Ok(DateTime::from_millis(millis));
+    }
+
+    if let Some(pm) = pm {
+        if dt.hour == 0 || dt.hour > 12 {
+            return Err(DishError::decode(format!(
+                "hour {} is invalid with AM/PM",
+                dt.hour
+            )));
+        }
+        dt.hour = dt.hour % 12 + if pm { 12 } else { 0 };
+    }
+
+    if !(1..=12).contains(&dt.month)
+        || dt.day < 1
+        || dt.day > days_in_month(dt.year, dt.month)
+        || dt.hour > 23
+        || dt.minute > 59
+        || dt.second > 59
+    {
+        return Err(DishError::decode(format!("`{}` is not a valid date", s)));
+    }
+
+    Ok(dt)
+}
+
+/// helper function that parses the `unit` argument into milliseconds per unit
+///
+fn unit_helper(args: &OperationArguments) -> Result<i64, DishError> {
+    match args.get_string("unit")?.as_ref() {
+        "s" => Ok(1000),
+        "ms" => Ok(1),
+        other => Err(DishError::invalid_argument(format!(
+            "unit must be either `s` or `ms`. got `{}`",
+            other
+        ))),
+    }
+}
+
+pub static OPINFO_FROM_UNIX_TIMESTAMP: OperationInfo = OperationInfo {
+    name: "from-unix-timestamp",
+    description:
+        "renders a unix timestamp in `unit`s (`s` or `ms`) as a UTC date with a strftime `format`",
+    authors: &["s-viour"],
+    category: "Datetime",
+    arguments: &[
+        ("unit", OperationArgType::String),
+        ("format", OperationArgType::String),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: from_unix_timestamp,
+};
+
+fn from_unix_timestamp(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let unit = unit_helper(args)?;
+    let format = args.get_string("format")?;
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let timestamp: i64 = data
+        .parse()
+        .map_err(|_| DishError::decode(format!("`{}` is not a valid timestamp", data)))?;
+    let millis = timestamp
+        .checked_mul(unit)
+        .ok_or_else(|| DishError::decode(format!("timestamp `{}` is out of range", data)))?;
+
+    *dish = DishData::Str(format_datetime(&DateTime::from_millis(millis), &format)?);
+    Ok(())
+}
+
+pub static OPINFO_TO_UNIX_TIMESTAMP: OperationInfo = OperationInfo {
+    name: "to-unix-timestamp",
+    description:
+        "parses a date with a strftime `format` into a unix timestamp in `unit`s (`s` or `ms`)",
+    authors: &["s-viour"],
+    category: "Datetime",
+    arguments: &[
+        ("unit", OperationArgType::String),
+        ("format", OperationArgType::String),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: to_unix_timestamp,
+};
+
+fn to_unix_timestamp(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let unit = unit_helper(args)?;
+    let format = args.get_string("format")?;
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let millis = parse_datetime(data, &format)?
+        .to_millis()
+        .ok_or_else(|| DishError::decode(format!("`{}` is out of range", data)))?;
+    *dish = DishData::Str(millis.div_euclid(unit).to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::datetime::*;
+
+    fn timestamp_args(unit: &str, format: &str) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("unit", unit.to_string());
+        args.insert("format", format.to_string());
+        args
+    }
+
+    #[test]
+    fn test_civil_days_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in (-800_000..800_000).step_by(997) {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn test_from_unix_timestamp() {
+        let mut data = DishData::Str("1700000000".to_string());
+        let args = timestamp_args("s", "%Y-%m-%d %H:%M:%S %Z");
+        assert!(matches!(from_unix_timestamp(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("2023-11-14 22:13:20 UTC".to_string()));
+
+        let mut data = DishData::Str("951782400123".to_string());
+        let args = timestamp_args("ms", "%a, %e %b %Y %I:%M:%S.%3f %p (day %j)");
+        assert!(matches!(from_unix_timestamp(&args, &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Str("Tue, 29 Feb 2000 12:00:00.123 AM (day 060)".to_string())
+        );
+
+        let mut data = DishData::Str("-1".to_string());
+        let args = timestamp_args("s", "%F %T");
+        assert!(matches!(from_unix_timestamp(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("1969-12-31 23:59:59".to_string()));
+    }
+
+    #[test]
+    fn test_to_unix_timestamp() {
+        let mut data = DishData::Str("2023-11-14 22:13:20".to_string());
+        let args = timestamp_args("s", "%F %T");
+        assert!(matches!(to_unix_timestamp(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("1700000000".to_string()));
+
+        // `%3f` wants exactly three digits of fractional seconds
+        let mut data = DishData::Str("14/Nov/2023:17:13:20.5 -0500".to_string());
+        let args = timestamp_args("ms", "%d/%b/%Y:%H:%M:%S.%3f %z");
+        assert!(to_unix_timestamp(&args, &mut data).is_err());
+
+        // offsets are converted back to UTC
+        let mut data = DishData::Str("14/Nov/2023:05:13:20.500 PM -05:00".to_string());
+        let args = timestamp_args("ms", "%d/%b/%Y:%I:%M:%S.%3f %p %z");
+        assert!(matches!(to_unix_timestamp(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("1700000000500".to_string()));
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let format = "%A %B %d %Y %T";
+        for ts in ["0", "-86401", "253402300799", "1234567890"] {
+            let mut data = DishData::Str(ts.to_string());
+            let args = timestamp_args("s", format);
+            assert!(matches!(from_unix_timestamp(&args, &mut data), Ok(())));
+            assert!(matches!(to_unix_timestamp(&args, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(ts.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_timestamp_invalid() {
+        let args = timestamp_args("s", "%F");
+        for s in ["2023-02-29", "2023-13-01", "2023-01-01x", "yesterday"] {
+            let mut data = DishData::Str(s.to_string());
+            assert!(to_unix_timestamp(&args, &mut data).is_err());
+        }
+
+        let mut data = DishData::Str("12ab".to_string());
+        assert!(from_unix_timestamp(&args, &mut data).is_err());
+
+        let mut data = DishData::Str("0".to_string());
+        assert!(from_unix_timestamp(&timestamp_args("us", "%F"), &mut data).is_err());
+        assert!(from_unix_timestamp(&timestamp_args("s", "%Q"), &mut data).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_out_of_range() {
+        // these all match their format, but don't fit in milliseconds
+        for (input, format) in [
+            ("999999999999999999", "%s"),
+            ("-999999999999999999", "%s"),
+            ("999999999-01-01", "%F"),
+        ] {
+            let mut data = DishData::Str(input.to_string());
+            let res = to_unix_timestamp(&timestamp_args("s", format), &mut data);
+            assert!(res.is_err(), "{}", input);
+        }
+
+        let args = timestamp_args("s", "%F %H:%M %z");
+        for offset in ["+9999", "+2400", "-0060", "+23:60"] {
+            let mut data = DishData::Str(format!("2023-01-01 00:00 {}", offset));
+            assert!(to_unix_timestamp(&args, &mut data).is_err(), "{}", offset);
+        }
+        let mut data = DishData::Str("2023-01-01 00:00 -23:59".to_string());
+        assert!(matches!(to_unix_timestamp(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("1672617540".to_string()));
+    }
+}
@@ -15,29 +15,66 @@
 //!      as possible. Only return an error if it's completely unreasonable to
 //!      return success. An example of when an operation would be expected to
 //!      fail is when trying to decompress data that does not have correct headers.
+//!      Make errors with the `DishError` constructor for their kind, like
+//!      `DishError::wrong_type` or `DishError::decode`.
 //!   
 //!   2. Create the OperationInfo struct for your operation. The `arguments` field
 //!      is a list of tuples of the form ("argument name", OperationArgType::ArgumentType).
 //!      This lets you declaratively specify what arguments your operation takes
-//!      and in what order. *There are no optional/default arguments.* All arguments
-//!      you specify are required.
+//!      and in what order. All arguments you specify are required, unless they're
+//!      trailing arguments listed in `defaults` with a default value.
 //!
 //!   3. Add your OperationInfo declaration to the list below!
 //!
 
+mod analysis;
+mod checksums;
+mod crypto;
 mod data_format;
+mod datetime;
+mod structured;
 mod textual;
 mod utility;
 
-use crate::OperationInfo;
+use crate::{
+    DishData, OperationArg, OperationArgDefault, OperationArgType, OperationArguments,
+    OperationInfo,
+};
+pub(crate) use analysis::shannon_entropy;
+use analysis::*;
+use checksums::*;
+use crypto::*;
+pub(crate) use data_format::regex_helper;
 use data_format::*;
+use datetime::*;
+use structured::*;
 use textual::*;
 use utility::*;
 
+use serde::Serialize;
+
 /// This is the list of ALL OperationInfo structures
 pub static OPERATIONS: &[&OperationInfo] = &[
     &OPINFO_ROT13,
+    &OPINFO_ROT_BRUTE_FORCE,
     &OPINFO_REVERSE,
+    &OPINFO_REVERSE_LINES,
+    &OPINFO_REVERSE_WORDS,
+    &OPINFO_TO_CAMEL_CASE,
+    &OPINFO_TO_SNAKE_CASE,
+    &OPINFO_TO_KEBAB_CASE,
+    &OPINFO_TO_TITLE_CASE,
+    &OPINFO_SUBSTITUTE,
+    &OPINFO_KEYWORD_CIPHER_ENCODE,
+    &OPINFO_KEYWORD_CIPHER_DECODE,
+    &OPINFO_TO_ROMAN,
+    &OPINFO_FROM_ROMAN,
+    &OPINFO_NUMBER_TO_WORDS,
+    &OPINFO_TO_NATO,
+    &OPINFO_FROM_NATO,
+    &OPINFO_REMOVE_NON_PRINTABLE,
+    &OPINFO_SHOW_NON_PRINTABLE,
+    &OPINFO_CANONICALIZE,
     &OPINFO_FROMBASE64,
     &OPINFO_TOBASE64,
     &OPINFO_FROMDECIMAL,
@@ -46,14 +83,428 @@ pub static OPERATIONS: &[&OperationInfo] = &[
     &OPINFO_TOOCTAL,
     &OPINFO_TOHEX,
     &OPINFO_FROMHEX,
+    &OPINFO_BASE64_TO_HEX,
+    &OPINFO_HEX_TO_BASE64,
+    &OPINFO_TO_HEXDUMP,
+    &OPINFO_SIDE_BY_SIDE,
     &OPINFO_FROMBINARY,
     &OPINFO_TOBINARY,
     &OPINFO_FROMRADIX,
     &OPINFO_TORADIX,
     &OPINFO_REGEXMATCH,
     &OPINFO_REGEXREPLACE,
+    &OPINFO_REGEX_EXTRACT,
+    &OPINFO_EXTRACT,
+    &OPINFO_DECODE_FLAGS,
+    &OPINFO_IP_TO_INT,
+    &OPINFO_INT_TO_IP,
+    &OPINFO_CIDR_RANGE,
+    &OPINFO_READ_INT,
+    &OPINFO_WRITE_INT,
+    &OPINFO_HEX_TO_RGB,
+    &OPINFO_RGB_TO_HEX,
+    &OPINFO_HEX_TO_HSL,
+    &OPINFO_HSL_TO_HEX,
     &OPINFO_URLENCODE,
     &OPINFO_URLDECODE,
+    &OPINFO_PARSE_QUERY_STRING,
+    &OPINFO_BUILD_QUERY_STRING,
+    &OPINFO_JWT_DECODE,
+    &OPINFO_JSON_BEAUTIFY,
+    &OPINFO_JSON_MINIFY,
+    &OPINFO_XML_BEAUTIFY,
+    &OPINFO_CSV_TO_JSON,
+    &OPINFO_JSON_TO_CSV,
+    &OPINFO_TO_TABLE,
+    &OPINFO_TRANSPOSE,
     &OPINFO_TAKE_BYTES,
     &OPINFO_DROP_BYTES,
+    &OPINFO_SLICE,
+    &OPINFO_PAD_BYTES,
+    &OPINFO_REPEAT,
+    &OPINFO_LENGTH,
+    &OPINFO_SHUFFLE_BYTES,
+    &OPINFO_UNSHUFFLE_BYTES,
+    &OPINFO_STRINGS,
+    &OPINFO_DETECT_FORMAT,
+    &OPINFO_ENTROPY_MAP,
+    &OPINFO_PARSE_FILE_MODE,
+    &OPINFO_LUHN_VALIDATE,
+    &OPINFO_LUHN_GENERATE,
+    &OPINFO_PKCS7_PAD,
+    &OPINFO_PKCS7_UNPAD,
+    &OPINFO_AES_ENCRYPT,
+    &OPINFO_AES_DECRYPT,
+    &OPINFO_FROM_UNIX_TIMESTAMP,
+    &OPINFO_TO_UNIX_TIMESTAMP,
+];
+
+/// Two operations where `inverse` undoes `forward`, along with what
+/// `self_test` round-trips through them
+///
+pub struct ReversiblePair {
+    pub forward: &'static OperationInfo,
+    pub inverse: &'static OperationInfo,
+    /// arguments given to whichever of the two operations takes them,
+    /// on top of their defaults
+    pub args: &'static [(&'static str, OperationArgDefault)],
+    /// the input to round-trip, `SELF_TEST_INPUT` if there isn't one
+    pub input: Option<&'static str>,
+}
+
+impl ReversiblePair {
+    /// a pair that round-trips `SELF_TEST_INPUT` with default arguments
+    const fn new(forward: &'static OperationInfo, inverse: &'static OperationInfo) -> Self {
+        ReversiblePair {
+            forward,
+            inverse,
+            args: &[],
+            input: None,
+        }
+    }
+
+    /// Builds the arguments `oi` gets in the self test
+    ///
+    fn arguments_for(&self, oi: &OperationInfo) -> OperationArguments {
+        let mut args = oi.default_arguments();
+        for (name, value) in self.args {
+            if oi.arguments.iter().any(|(arg, _)| arg == name) {
+                args.insert(name, OperationArg::from(value));
+            }
+        }
+        args
+    }
+}
+
+/// Pairs of operations where the second undoes the first. These are
+/// checked by `self_test` to make sure they always round-trip
+pub static REVERSIBLE_PAIRS: &[ReversiblePair] = &[
+    ReversiblePair::new(&OPINFO_TOBASE64, &OPINFO_FROMBASE64),
+    ReversiblePair {
+        args: &[("variant", OperationArgDefault::String("url-safe"))],
+        ..ReversiblePair::new(&OPINFO_TOBASE64, &OPINFO_FROMBASE64)
+    },
+    ReversiblePair {
+        args: &[("variant", OperationArgDefault::String("url-safe-no-pad"))],
+        ..ReversiblePair::new(&OPINFO_TOBASE64, &OPINFO_FROMBASE64)
+    },
+    ReversiblePair {
+        args: &[("pad", OperationArgDefault::Integer(0))],
+        ..ReversiblePair::new(&OPINFO_TOBASE64, &OPINFO_FROMBASE64)
+    },
+    ReversiblePair {
+        input: Some("63 6f 64 65 62 61 6b 65 00 ff"),
+        ..ReversiblePair::new(&OPINFO_HEX_TO_BASE64, &OPINFO_BASE64_TO_HEX)
+    },
+    ReversiblePair::new(&OPINFO_TODECIMAL, &OPINFO_FROMDECIMAL),
+    ReversiblePair::new(&OPINFO_TOOCTAL, &OPINFO_FROMOCTAL),
+    ReversiblePair::new(&OPINFO_TOHEX, &OPINFO_FROMHEX),
+    ReversiblePair::new(&OPINFO_TOBINARY, &OPINFO_FROMBINARY),
+    ReversiblePair::new(&OPINFO_URLENCODE, &OPINFO_URLDECODE),
+    ReversiblePair::new(&OPINFO_REVERSE, &OPINFO_REVERSE),
+    ReversiblePair::new(&OPINFO_REVERSE_LINES, &OPINFO_REVERSE_LINES),
+    ReversiblePair {
+        args: &[("keyword", OperationArgDefault::String("codebake"))],
+        ..ReversiblePair::new(&OPINFO_KEYWORD_CIPHER_ENCODE, &OPINFO_KEYWORD_CIPHER_DECODE)
+    },
+    ReversiblePair {
+        input: Some("1994"),
+        ..ReversiblePair::new(&OPINFO_TO_ROMAN, &OPINFO_FROM_ROMAN)
+    },
+    ReversiblePair {
+        input: Some("HELLOWORLD42"),
+        ..ReversiblePair::new(&OPINFO_TO_NATO, &OPINFO_FROM_NATO)
+    },
+    ReversiblePair {
+        args: &[("block_size", OperationArgDefault::Integer(16))],
+        ..ReversiblePair::new(&OPINFO_PKCS7_PAD, &OPINFO_PKCS7_UNPAD)
+    },
+    ReversiblePair {
+        args: &[("seed", OperationArgDefault::Integer(42))],
+        ..ReversiblePair::new(&OPINFO_SHUFFLE_BYTES, &OPINFO_UNSHUFFLE_BYTES)
+    },
 ];
+
+/// Known input used by `self_test`. It contains control characters and
+/// multi-byte characters to catch padding and encoding bugs
+static SELF_TEST_INPUT: &str = "codebake\u{0}\u{f}\n\t 👾 hello world!";
+
+/// Runs every reversible pair in `REVERSIBLE_PAIRS` on a small known input
+/// and verifies that applying both operations gives back the original data.
+///
+/// Returns an error describing every operation/direction that failed
+///
+pub fn self_test() -> Result<(), String> {
+    let mut failures: Vec<String> = Vec::new();
+
+    for pair in REVERSIBLE_PAIRS {
+        let (forward, inverse) = (pair.forward, pair.inverse);
+        for (op, name) in [(forward, "forward"), (inverse, "inverse")] {
+            if !OPERATIONS.contains(&op) {
+                failures.push(format!(
+                    "{} operation '{}' is not registered",
+                    name, op.name
+                ));
+            }
+        }
+
+        let original = DishData::Str(pair.input.unwrap_or(SELF_TEST_INPUT).to_string());
+        let mut data = original.clone();
+        if let Err(e) = (forward.op)(&pair.arguments_for(forward), &mut data) {
+            failures.push(format!("'{}' failed: {}", forward.name, e));
+            continue;
+        }
+        if let Err(e) = (inverse.op)(&pair.arguments_for(inverse), &mut data) {
+            failures.push(format!(
+                "'{}' failed undoing '{}': {}",
+                inverse.name, forward.name, e
+            ));
+            continue;
+        }
+
+        if data.as_bytes() != original.as_bytes() {
+            failures.push(format!(
+                "'{}' followed by '{}' did not round-trip. got {}",
+                forward.name, inverse.name, data
+            ));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// Every category an operation may belong to
+pub static CATEGORIES: &[&str] = &[
+    "Textual",
+    "Data Format",
+    "Utility",
+    "Analysis",
+    "Structured",
+    "Crypto",
+    "Datetime",
+    "Checksums",
+];
+
+/// Checks every entry in `OPERATIONS` for a `lowercase-with-dashes` name,
+/// a category from `CATEGORIES`, and a name no other operation has.
+/// Aliases are held to the same naming rules and must not clash with any
+/// other name or alias.
+///
+/// Returns a description of every offending entry
+///
+pub fn validate_operations() -> Result<(), Vec<String>> {
+    let mut problems: Vec<String> = Vec::new();
+
+    for (i, oi) in OPERATIONS.iter().enumerate() {
+        if !is_valid_name(oi.name) {
+            problems.push(format!(
+                "operation '{}' is not named `lowercase-with-dashes`",
+                oi.name
+            ));
+        }
+        if !CATEGORIES.contains(&oi.category) {
+            problems.push(format!(
+                "operation '{}' has unknown category '{}'",
+                oi.name, oi.category
+            ));
+        }
+        if OPERATIONS[..i].iter().any(|other| other.name == oi.name) {
+            problems.push(format!(
+                "operation '{}' is registered more than once",
+                oi.name
+            ));
+        }
+        for alias in oi.aliases {
+            if !is_valid_name(alias) {
+                problems.push(format!(
+                    "alias '{}' of operation '{}' is not named `lowercase-with-dashes`",
+                    alias, oi.name
+                ));
+            }
+            if OPERATIONS.iter().any(|other| other.name == *alias)
+                || OPERATIONS[..i]
+                    .iter()
+                    .any(|other| other.aliases.contains(alias))
+            {
+                problems.push(format!(
+                    "alias '{}' of operation '{}' is already taken",
+                    alias, oi.name
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// An entry in the JSON dump of `OPERATIONS`
+///
+#[derive(Serialize)]
+struct OperationEntry {
+    name: &'static str,
+    description: &'static str,
+    category: &'static str,
+    authors: &'static [&'static str],
+    aliases: &'static [&'static str],
+    arguments: Vec<ArgumentEntry>,
+}
+
+#[derive(Serialize)]
+struct ArgumentEntry {
+    name: &'static str,
+    #[serde(rename = "type")]
+    arg_type: &'static OperationArgType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<&'static OperationArgDefault>,
+}
+
+/// Serializes every entry in `OPERATIONS` as a JSON array, in order.
+/// This is the machine-readable counterpart to the wiki's operation reference,
+/// for editors and the web interface to build completions from
+///
+pub fn operations_json() -> String {
+    let entries: Vec<OperationEntry> = OPERATIONS
+        .iter()
+        .map(|oi| OperationEntry {
+            name: oi.name,
+            description: oi.description,
+            category: oi.category,
+            authors: oi.authors,
+            aliases: oi.aliases,
+            arguments: oi
+                .arguments
+                .iter()
+                .map(|(name, arg_type)| ArgumentEntry {
+                    name,
+                    arg_type,
+                    default: oi.default_for(name),
+                })
+                .collect(),
+        })
+        .collect();
+
+    // nothing in an entry can fail to serialize
+    serde_json::to_string_pretty(&entries).unwrap()
+}
+
+/// helper function that checks a name is words of lowercase letters
+/// and digits, separated by single dashes
+///
+fn is_valid_name(name: &str) -> bool {
+    name.split('-').all(|word| {
+        !word.is_empty()
+            && word
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    })
+}
+
+/// Inputs longer than this many bytes are split across threads by `map_bytes_parallel`
+#[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+pub(crate) const PARALLEL_THRESHOLD: usize = 1 << 20;
+
+/// Replaces every byte of `bytes` with `f` of that byte
+///
+/// When codebake is built with the `parallel` feature and there are more
+/// than `PARALLEL_THRESHOLD` bytes, they're split into one chunk per thread.
+/// Smaller inputs aren't worth the cost of spawning threads
+///
+pub(crate) fn map_bytes_parallel<F>(bytes: &mut [u8], f: F)
+where
+    F: Fn(u8) -> u8 + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if bytes.len() > PARALLEL_THRESHOLD {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = bytes.len().div_ceil(threads);
+        let f = &f;
+
+        std::thread::scope(|scope| {
+            for chunk in bytes.chunks_mut(chunk_size) {
+                scope.spawn(move || chunk.iter_mut().for_each(|b| *b = f(*b)));
+            }
+        });
+        return;
+    }
+
+    bytes.iter_mut().for_each(|b| *b = f(*b));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::*;
+
+    #[test]
+    fn test_map_bytes_parallel() {
+        // big enough to be split up, and not a multiple of the thread count
+        let input: Vec<u8> = (0..PARALLEL_THRESHOLD * 3 + 7)
+            .map(|i| (i * 31) as u8)
+            .collect();
+        let f = |b: u8| b.rotate_left(3) ^ 0x5a;
+
+        let sequential: Vec<u8> = input.iter().map(|b| f(*b)).collect();
+        let mut parallel = input.clone();
+        map_bytes_parallel(&mut parallel, f);
+        assert_eq!(parallel, sequential);
+
+        let mut small = vec![1, 2, 3];
+        map_bytes_parallel(&mut small, f);
+        assert_eq!(small, vec![f(1), f(2), f(3)]);
+    }
+
+    #[test]
+    fn test_validate_operations() {
+        if let Err(problems) = validate_operations() {
+            panic!("invalid operations:\n{}", problems.join("\n"));
+        }
+
+        assert!(is_valid_name("to-base64"));
+        assert!(is_valid_name("rot13"));
+        assert!(!is_valid_name("to_base64"));
+        assert!(!is_valid_name("ToBase64"));
+        assert!(!is_valid_name("to--base64"));
+        assert!(!is_valid_name("-reverse"));
+        assert!(!is_valid_name(""));
+    }
+
+    #[test]
+    fn test_operations_json() {
+        let json: serde_json::Value = serde_json::from_str(&operations_json()).unwrap();
+        let ops = json.as_array().unwrap();
+        assert_eq!(ops.len(), OPERATIONS.len());
+
+        let to_hex = ops.iter().find(|op| op["name"] == "to-hex").unwrap();
+        assert_eq!(to_hex["category"], "Data Format");
+        assert_eq!(
+            to_hex["arguments"],
+            serde_json::json!([{"name": "delimiter", "type": "string", "default": " "}])
+        );
+
+        let show = ops
+            .iter()
+            .find(|op| op["name"] == "show-non-printable")
+            .unwrap();
+        assert_eq!(
+            show["arguments"][0]["type"],
+            serde_json::json!({"choice": ["hex", "caret", "unicode"]})
+        );
+
+        let from_base64 = ops.iter().find(|op| op["name"] == "from-base64").unwrap();
+        assert_eq!(from_base64["aliases"][0], "from-b64");
+    }
+
+    #[test]
+    fn test_self_test() {
+        if let Err(e) = self_test() {
+            panic!("operation self-test failed:\n{}", e);
+        }
+    }
+}
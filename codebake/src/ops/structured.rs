@@ -0,0 +1,183 @@
+use crate::{DishData, DishError, DishResult, OperationArguments, OperationInfo};
+
+/// names of the permission classes, in the order they appear in a mode
+static MODE_CLASSES: &[&str] = &["owner", "group", "others"];
+
+pub static OPINFO_PARSE_FILE_MODE: OperationInfo = OperationInfo {
+    name: "parse-file-mode",
+    description: "describes an octal (`0755`) or symbolic (`rwxr-xr-x`) unix file mode",
+    authors: &["s-viour"],
+    category: "Structured",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: parse_file_mode,
+};
+
+fn parse_file_mode(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let mode = if data.chars().all(|c| c.is_ascii_digit()) {
+        parse_octal_mode(data)?
+    } else {
+        parse_symbolic_mode(data)?
+    };
+
+    let mut out = vec![
+        format!("octal:    {:04o}", mode),
+        format!("symbolic: {}", symbolic_mode(mode)),
+    ];
+
+    for (i, class) in MODE_CLASSES.iter().enumerate() {
+        let bits = (mode >> (6 - 3 * i)) & 0o7;
+        let perms: Vec<&str> = [(0o4, "read"), (0o2, "write"), (0o1, "execute")]
+            .iter()
+            .filter(|(bit, _)| bits & bit != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        let perms = if perms.is_empty() {
+            "none".to_string()
+        } else {
+            perms.join(", ")
+        };
+        out.push(format!("{:<9} {}", format!("{}:", class), perms));
+    }
+
+    let special: Vec<&str> = [(0o4000, "setuid"), (0o2000, "setgid"), (0o1000, "sticky")]
+        .iter()
+        .filter(|(bit, _)| mode & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if !special.is_empty() {
+        out.push(format!("special:  {}", special.join(", ")));
+    }
+
+    *dish = DishData::Str(out.join("\n"));
+    Ok(())
+}
+
+/// helper function that parses an octal mode like `644`, `0755`, or `4755`
+///
+fn parse_octal_mode(s: &str) -> Result<u32, DishError> {
+    if s.is_empty() || s.len() > 4 {
+        return Err(DishError::decode(format!(
+            "invalid octal file mode `{}`",
+            s
+        )));
+    }
+
+    u32::from_str_radix(s, 8)
+        .map_err(|_| DishError::decode(format!("invalid octal file mode `{}`", s)))
+}
+
+/// helper function that parses a symbolic mode like `rwxr-xr-x`
+/// an optional leading file type character (as printed by `ls -l`) is ignored
+///
+fn parse_symbolic_mode(s: &str) -> Result<u32, DishError> {
+    let chars: Vec<char> = s.chars().collect();
+    let chars = match chars.len() {
+        9 => &chars[..],
+        10 => &chars[1..],
+        _ => {
+            return Err(DishError::decode(format!(
+                "invalid symbolic file mode `{}`",
+                s
+            )))
+        }
+    };
+
+    let mut mode = 0;
+    for (i, c) in chars.iter().enumerate() {
+        let class = i / 3;
+        let bit = 1 << (8 - i);
+        // the special bits live in the execute position of each class
+        let special = 0o4000 >> class;
+        let expected = ['r', 'w', 'x'][i % 3];
+
+        mode |= match (i % 3, *c) {
+            (_, '-') => 0,
+            (_, c) if c == expected => bit,
+            (2, 's') if class < 2 => bit | special,
+            (2, 'S') if class < 2 => special,
+            (2, 't') if class == 2 => bit | special,
+            (2, 'T') if class == 2 => special,
+            _ => {
+                return Err(DishError::decode(format!(
+                    "invalid symbolic file mode `{}`",
+                    s
+                )))
+            }
+        };
+    }
+
+    Ok(mode)
+}
+
+/// helper function that renders a mode as its symbolic `rwxr-xr-x` form
+///
+fn symbolic_mode(mode: u32) -> String {
+    let mut out = String::new();
+    for class in 0..3 {
+        let bits = (mode >> (6 - 3 * class)) & 0o7;
+        let special = mode & (0o4000 >> class) != 0;
+
+        out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        out.push(match (bits & 0o1 != 0, special, class == 2) {
+            (true, false, _) => 'x',
+            (false, false, _) => '-',
+            (true, true, false) => 's',
+            (false, true, false) => 'S',
+            (true, true, true) => 't',
+            (false, true, true) => 'T',
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::structured::*;
+    use crate::{DishData, EMPTY_ARGS};
+
+    fn parse(s: &str) -> String {
+        let mut data = DishData::Str(s.to_string());
+        assert!(matches!(parse_file_mode(&EMPTY_ARGS, &mut data), Ok(())));
+        match data {
+            DishData::Str(s) => s,
+            DishData::Bin(_) => panic!("expected a string dish"),
+        }
+    }
+
+    #[test]
+    fn test_parse_file_mode_round_trip() {
+        let out = parse("0644");
+        assert!(out.contains("symbolic: rw-r--r--"));
+        assert!(out.contains("owner:    read, write"));
+        assert!(out.contains("others:   read"));
+
+        let out = parse("rw-r--r--");
+        assert!(out.contains("octal:    0644"));
+    }
+
+    #[test]
+    fn test_parse_file_mode_special() {
+        let out = parse("4755");
+        assert!(out.contains("symbolic: rwsr-xr-x"));
+        assert!(out.contains("special:  setuid"));
+
+        let out = parse("drwxrwxrwT");
+        assert!(out.contains("octal:    1776"));
+    }
+
+    #[test]
+    fn test_parse_file_mode_invalid() {
+        for s in ["0899", "12345", "rwxrwxrwz", "rw-", "rwsrwxrws"] {
+            let mut data = DishData::Str(s.to_string());
+            assert!(parse_file_mode(&EMPTY_ARGS, &mut data).is_err());
+        }
+    }
+}
@@ -1,45 +1,73 @@
-use crate::{DishData, DishResult, OperationArgType, OperationArguments, OperationInfo};
+use crate::ops::map_bytes_parallel;
+use crate::{
+    DishData, DishError, DishResult, OperationArgDefault, OperationArgType, OperationArguments,
+    OperationInfo,
+};
 
 fn rot13_helper_bin(n: i64, s: &mut [u8]) {
-    s.iter_mut().for_each(|c| {
-        let cx = *c as i64;
-        *c = if *c >= 65 && *c <= 90 {
+    map_bytes_parallel(s, |c| {
+        let cx = c as i64;
+        if (65..=90).contains(&c) {
             (((cx + n - 65) % 26) + 65) as u8
-        } else if *c >= 97 && *c <= 122 {
+        } else if (97..=122).contains(&c) {
             (((cx + n - 97) % 26) + 97) as u8
         } else {
-            *c
+            c
         }
     });
 }
 
-fn rot13_helper_str(n: i64, s: &mut str) {
-    unsafe {
-        rot13_helper_bin(n, s.as_bytes_mut());
-    }
-}
-
 pub static OPINFO_ROT13: OperationInfo = OperationInfo {
     name: "rot13",
     description: "rotates characters in the input by the specified amount",
     authors: &["s-viour"],
     category: "Textual",
     arguments: &[("n", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
     op: rot13,
 };
 
 fn rot13(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     let n = args.get_integer("n")?;
-    match dish {
-        DishData::Str(s) => {
-            rot13_helper_str(n, s);
-            Ok(())
-        }
-        DishData::Bin(b) => {
-            rot13_helper_bin(n, b);
-            Ok(())
+    // only ASCII letters are touched, so a string dish stays valid UTF-8
+    unsafe {
+        rot13_helper_bin(n, dish.as_bytes_mut());
+    }
+    Ok(())
+}
+
+pub static OPINFO_ROT_BRUTE_FORCE: OperationInfo = OperationInfo {
+    name: "rot-brute-force",
+    description: "lists every rotation from 1 to 25 of the input, one per line. with a `crib`, only rotations containing it (ignoring case) are listed",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[("crib", OperationArgType::String)],
+    defaults: &[("crib", OperationArgDefault::String(""))],
+    aliases: &[],
+    op: rot_brute_force,
+};
+
+fn rot_brute_force(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let crib = args.get_string("crib")?.to_lowercase();
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let mut rotations = Vec::new();
+    for n in 1..26 {
+        let mut rotated = data.clone().into_bytes();
+        rot13_helper_bin(n, &mut rotated);
+        // only ASCII letters were rotated, so it's still valid UTF-8
+        let rotated = String::from_utf8(rotated).unwrap();
+        if rotated.to_lowercase().contains(&crib) {
+            rotations.push(format!("rot {}: {}", n, rotated));
         }
     }
+
+    *dish = DishData::Str(rotations.join("\n"));
+    Ok(())
 }
 
 pub static OPINFO_REVERSE: OperationInfo = OperationInfo {
@@ -48,26 +76,878 @@ pub static OPINFO_REVERSE: OperationInfo = OperationInfo {
     authors: &["s-viour"],
     category: "Textual",
     arguments: &[],
+    defaults: &[],
+    aliases: &[],
     op: reverse,
 };
 
 fn reverse(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let is_str = matches!(dish, DishData::Str(_));
+    // reversing all the bytes leaves each multi-byte character backwards,
+    // so those get flipped back around to keep a string dish valid UTF-8
+    unsafe {
+        let bytes = dish.as_bytes_mut();
+        bytes.reverse();
+        if is_str {
+            reverse_utf8_chars(bytes);
+        }
+    }
+    Ok(())
+}
+
+/// helper function that reverses the bytes of every character in `bytes`,
+/// where `bytes` is UTF-8 that has itself been reversed. after a reverse each
+/// character starts with its continuation bytes and ends with the leading byte
+///
+fn reverse_utf8_chars(bytes: &mut [u8]) {
+    let mut start = 0;
+    while start < bytes.len() {
+        let mut end = start;
+        while end + 1 < bytes.len() && bytes[end] & 0xc0 == 0x80 {
+            end += 1;
+        }
+        bytes[start..=end].reverse();
+        start = end + 1;
+    }
+}
+
+/// helper function that splits a string dish into its lines, calls `f` on them,
+/// and joins them back together. a trailing newline stays at the end
+///
+fn map_lines(dish: &mut DishData, f: impl FnOnce(Vec<&str>) -> Vec<String>) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let (body, trailing) = match data.strip_suffix('\n') {
+        Some(body) => (body, "\n"),
+        None => (data.as_str(), ""),
+    };
+    let lines = f(body.split('\n').collect());
+
+    *dish = DishData::Str(lines.join("\n") + trailing);
+    Ok(())
+}
+
+pub static OPINFO_REVERSE_LINES: OperationInfo = OperationInfo {
+    name: "reverse-lines",
+    description: "reverses the order of the lines in the input, but not the lines themselves",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: reverse_lines,
+};
+
+fn reverse_lines(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    map_lines(dish, |lines| {
+        lines.into_iter().rev().map(String::from).collect()
+    })
+}
+
+pub static OPINFO_REVERSE_WORDS: OperationInfo = OperationInfo {
+    name: "reverse-words",
+    description: "reverses the order of the words on each line of the input",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: reverse_words,
+};
+
+fn reverse_words(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    map_lines(dish, |lines| {
+        lines
+            .into_iter()
+            .map(|line| {
+                // keep windows line endings where they were
+                let (line, ending) = match line.strip_suffix('\r') {
+                    Some(line) => (line, "\r"),
+                    None => (line, ""),
+                };
+                let words: Vec<&str> = line.split_whitespace().rev().collect();
+                words.join(" ") + ending
+            })
+            .collect()
+    })
+}
+
+/// helper function that splits an identifier-ish string into its words
+/// words are separated by whitespace, underscores, hyphens, and case boundaries
+/// (so `helloWorld` and `HTTPServer` split into `hello World` and `HTTP Server`)
+///
+fn split_identifier_words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = s.chars().collect();
+
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_whitespace() || *c == '_' || *c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // split on `aB`, and on the last capital of an acronym like `HTTPServer`
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+
+        current.push(*c);
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// helper function that uppercases the first character of a word and lowercases the rest
+///
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => c
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// helper function that performs the shared work of all the case conversion operations
+///
+fn convert_case(dish: &mut DishData, convert: fn(Vec<String>) -> String) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    *dish = DishData::Str(convert(split_identifier_words(data)));
+    Ok(())
+}
+
+pub static OPINFO_TO_CAMEL_CASE: OperationInfo = OperationInfo {
+    name: "to-camel-case",
+    description: "converts the input to camelCase",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: to_camel_case,
+};
+
+fn to_camel_case(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    convert_case(dish, |words| {
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                if i == 0 {
+                    w.to_lowercase()
+                } else {
+                    capitalize(w)
+                }
+            })
+            .collect()
+    })
+}
+
+pub static OPINFO_TO_SNAKE_CASE: OperationInfo = OperationInfo {
+    name: "to-snake-case",
+    description: "converts the input to snake_case",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: to_snake_case,
+};
+
+fn to_snake_case(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    convert_case(dish, |words| {
+        words
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("_")
+    })
+}
+
+pub static OPINFO_TO_KEBAB_CASE: OperationInfo = OperationInfo {
+    name: "to-kebab-case",
+    description: "converts the input to kebab-case",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: to_kebab_case,
+};
+
+fn to_kebab_case(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    convert_case(dish, |words| {
+        words
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("-")
+    })
+}
+
+pub static OPINFO_TO_TITLE_CASE: OperationInfo = OperationInfo {
+    name: "to-title-case",
+    description: "converts the input to Title Case",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: to_title_case,
+};
+
+fn to_title_case(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    convert_case(dish, |words| {
+        words
+            .iter()
+            .map(|w| capitalize(w))
+            .collect::<Vec<String>>()
+            .join(" ")
+    })
+}
+
+pub static OPINFO_SUBSTITUTE: OperationInfo = OperationInfo {
+    name: "substitute",
+    description:
+        "replaces each character in `from` with the character at the same position in `to`",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[
+        ("from", OperationArgType::String),
+        ("to", OperationArgType::String),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: substitute,
+};
+
+fn substitute(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let from: Vec<char> = args.get_string("from")?.chars().collect();
+    let to: Vec<char> = args.get_string("to")?.chars().collect();
+    if to.len() < from.len() {
+        return Err(DishError::invalid_argument(format!(
+            "substitution alphabet is too short. expected {} characters, got {}",
+            from.len(),
+            to.len()
+        )));
+    }
+
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    *dish = DishData::Str(
+        data.chars()
+            .map(|c| match from.iter().position(|f| *f == c) {
+                Some(i) => to[i],
+                None => c,
+            })
+            .collect(),
+    );
+    Ok(())
+}
+
+/// helper function that builds the substitution alphabet for a keyword cipher:
+/// the keyword's letters (without repeats) followed by the rest of the alphabet
+///
+fn keyword_alphabet(keyword: &str) -> Result<Vec<u8>, DishError> {
+    if let Some(c) = keyword.chars().find(|c| !c.is_ascii_alphabetic()) {
+        return Err(DishError::invalid_argument(format!(
+            "keyword can only contain letters. got `{}`",
+            c
+        )));
+    }
+
+    let mut alphabet: Vec<u8> = Vec::with_capacity(26);
+    for c in keyword
+        .bytes()
+        .map(|c| c.to_ascii_uppercase())
+        .chain(b'A'..=b'Z')
+    {
+        if !alphabet.contains(&c) {
+            alphabet.push(c);
+        }
+    }
+    Ok(alphabet)
+}
+
+/// helper function that substitutes every letter of a string dish with the one at
+/// the same position in the keyword alphabet, or the other way around for `decode`
+///
+fn keyword_cipher_helper(
+    args: &OperationArguments,
+    dish: &mut DishData,
+    decode: bool,
+) -> DishResult {
+    let alphabet = keyword_alphabet(&args.get_string("keyword")?)?;
+    let plain: Vec<u8> = (b'A'..=b'Z').collect();
+    let (from, to) = if decode {
+        (&alphabet, &plain)
+    } else {
+        (&plain, &alphabet)
+    };
+
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    *dish = DishData::Str(
+        data.chars()
+            .map(|c| {
+                if !c.is_ascii_alphabetic() {
+                    return c;
+                }
+                let i = from
+                    .iter()
+                    .position(|f| *f == c.to_ascii_uppercase() as u8)
+                    .unwrap();
+                if c.is_ascii_lowercase() {
+                    to[i].to_ascii_lowercase() as char
+                } else {
+                    to[i] as char
+                }
+            })
+            .collect(),
+    );
+    Ok(())
+}
+
+pub static OPINFO_KEYWORD_CIPHER_ENCODE: OperationInfo = OperationInfo {
+    name: "keyword-cipher-encode",
+    description:
+        "substitutes letters using an alphabet that starts with `keyword`, like `KEYWORDABCF...`",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[("keyword", OperationArgType::String)],
+    defaults: &[],
+    aliases: &[],
+    op: keyword_cipher_encode,
+};
+
+fn keyword_cipher_encode(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    keyword_cipher_helper(args, dish, false)
+}
+
+pub static OPINFO_KEYWORD_CIPHER_DECODE: OperationInfo = OperationInfo {
+    name: "keyword-cipher-decode",
+    description: "undoes `keyword-cipher-encode` with the same keyword",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[("keyword", OperationArgType::String)],
+    defaults: &[],
+    aliases: &[],
+    op: keyword_cipher_decode,
+};
+
+fn keyword_cipher_decode(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    keyword_cipher_helper(args, dish, true)
+}
+
+/// roman numeral symbols, including the subtractive forms, from largest to smallest
+static ROMAN_NUMERALS: &[(u32, &str)] = &[
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// helper function that converts a number in 1..=3999 to its roman numeral
+///
+fn roman_helper(mut n: u32) -> String {
+    let mut out = String::new();
+    for (value, symbol) in ROMAN_NUMERALS {
+        while n >= *value {
+            out.push_str(symbol);
+            n -= value;
+        }
+    }
+    out
+}
+
+pub static OPINFO_TO_ROMAN: OperationInfo = OperationInfo {
+    name: "to-roman",
+    description: "converts a number between 1 and 3999 into a roman numeral",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: to_roman,
+};
+
+fn to_roman(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let n: u32 = match data.parse() {
+        Ok(n) if (1..=3999).contains(&n) => n,
+        _ => {
+            return Err(DishError::unsupported(format!(
+                "`{}` cannot be written as a roman numeral. (must be 1-3999)",
+                data
+            )))
+        }
+    };
+
+    *dish = DishData::Str(roman_helper(n));
+    Ok(())
+}
+
+pub static OPINFO_FROM_ROMAN: OperationInfo = OperationInfo {
+    name: "from-roman",
+    description: "converts a roman numeral into a number",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: from_roman,
+};
+
+fn from_roman(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s.trim().to_uppercase(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let mut rest = data.as_str();
+    let mut n = 0;
+    for (value, symbol) in ROMAN_NUMERALS {
+        while let Some(r) = rest.strip_prefix(symbol) {
+            n += value;
+            rest = r;
+        }
+    }
+
+    // a well-formed numeral is exactly the canonical form of its value
+    if !rest.is_empty() || !(1..=3999).contains(&n) || roman_helper(n) != data {
+        return Err(DishError::decode(format!(
+            "`{}` is not a valid roman numeral",
+            data
+        )));
+    }
+
+    *dish = DishData::Str(n.to_string());
+    Ok(())
+}
+
+static SMALL_NUMBER_WORDS: &[&str] = &[
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+static TENS_WORDS: &[&str] = &[
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+static SCALE_WORDS: &[&str] = &[
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+];
+
+/// helper function that spells out a number below 1000
+///
+fn hundreds_to_words(n: u64) -> String {
+    let mut words = Vec::new();
+    if n >= 100 {
+        words.push(format!(
+            "{} hundred",
+            SMALL_NUMBER_WORDS[(n / 100) as usize]
+        ));
+    }
+
+    let rest = (n % 100) as usize;
+    if rest >= 20 {
+        let tens = TENS_WORDS[rest / 10];
+        if rest.is_multiple_of(10) {
+            words.push(tens.to_string());
+        } else {
+            words.push(format!("{}-{}", tens, SMALL_NUMBER_WORDS[rest % 10]));
+        }
+    } else if rest > 0 {
+        words.push(SMALL_NUMBER_WORDS[rest].to_string());
+    }
+
+    words.join(" ")
+}
+
+/// helper function that spells out any `i64` in english
+///
+fn number_to_words_helper(n: i64) -> String {
+    if n == 0 {
+        return SMALL_NUMBER_WORDS[0].to_string();
+    }
+
+    let mut magnitude = n.unsigned_abs();
+    let mut groups = Vec::new();
+    let mut scale = 0;
+    while magnitude > 0 {
+        let group = magnitude % 1000;
+        if group > 0 {
+            let words = hundreds_to_words(group);
+            groups.push(match SCALE_WORDS[scale] {
+                "" => words,
+                scale_word => format!("{} {}", words, scale_word),
+            });
+        }
+        magnitude /= 1000;
+        scale += 1;
+    }
+    groups.reverse();
+
+    let words = groups.join(" ");
+    if n < 0 {
+        format!("negative {}", words)
+    } else {
+        words
+    }
+}
+
+pub static OPINFO_NUMBER_TO_WORDS: OperationInfo = OperationInfo {
+    name: "number-to-words",
+    description: "spells out a number in english words",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: number_to_words,
+};
+
+fn number_to_words(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s.trim(),
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let n: i64 = match data.parse() {
+        Ok(n) => n,
+        Err(e) => {
+            return Err(DishError::decode(format!(
+                "`{}` is not an integer. ({})",
+                data, e
+            )))
+        }
+    };
+
+    *dish = DishData::Str(number_to_words_helper(n));
+    Ok(())
+}
+
+static NATO_ALPHABET: &[(char, &str)] = &[
+    ('A', "Alpha"),
+    ('B', "Bravo"),
+    ('C', "Charlie"),
+    ('D', "Delta"),
+    ('E', "Echo"),
+    ('F', "Foxtrot"),
+    ('G', "Golf"),
+    ('H', "Hotel"),
+    ('I', "India"),
+    ('J', "Juliet"),
+    ('K', "Kilo"),
+    ('L', "Lima"),
+    ('M', "Mike"),
+    ('N', "November"),
+    ('O', "Oscar"),
+    ('P', "Papa"),
+    ('Q', "Quebec"),
+    ('R', "Romeo"),
+    ('S', "Sierra"),
+    ('T', "Tango"),
+    ('U', "Uniform"),
+    ('V', "Victor"),
+    ('W', "Whiskey"),
+    ('X', "X-ray"),
+    ('Y', "Yankee"),
+    ('Z', "Zulu"),
+    ('0', "Zero"),
+    ('1', "One"),
+    ('2', "Two"),
+    ('3', "Three"),
+    ('4', "Four"),
+    ('5', "Five"),
+    ('6', "Six"),
+    ('7', "Seven"),
+    ('8', "Eight"),
+    ('9', "Nine"),
+];
+
+/// other spellings `from-nato` understands, like the official ICAO ones
+static NATO_ALIASES: &[(char, &str)] = &[
+    ('A', "Alfa"),
+    ('J', "Juliett"),
+    ('X', "Xray"),
+    ('9', "Niner"),
+];
+
+pub static OPINFO_TO_NATO: OperationInfo = OperationInfo {
+    name: "to-nato",
+    description: "spells out letters and digits with the NATO phonetic alphabet",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: to_nato,
+};
+
+fn to_nato(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let words: Vec<String> = data
+        .chars()
+        .map(|c| {
+            let upper = c.to_ascii_uppercase();
+            match NATO_ALPHABET.iter().find(|(letter, _)| *letter == upper) {
+                Some((_, word)) => word.to_string(),
+                None => c.to_string(),
+            }
+        })
+        .collect();
+
+    *dish = DishData::Str(words.join(" "));
+    Ok(())
+}
+
+pub static OPINFO_FROM_NATO: OperationInfo = OperationInfo {
+    name: "from-nato",
+    description:
+        "converts space-separated NATO phonetic alphabet words back into letters and digits",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: from_nato,
+};
+
+fn from_nato(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let letters = data
+        .split_whitespace()
+        .map(|word| {
+            NATO_ALPHABET
+                .iter()
+                .chain(NATO_ALIASES)
+                .find(|(_, w)| w.eq_ignore_ascii_case(word))
+                .map(|(letter, _)| *letter)
+                .ok_or_else(|| {
+                    DishError::decode(format!("`{}` is not a NATO phonetic alphabet word", word))
+                })
+        })
+        .collect::<Result<String, DishError>>()?;
+
+    *dish = DishData::Str(letters);
+    Ok(())
+}
+
+/// characters that take up no space when printed, like zero-width spaces,
+/// joiners, byte order marks and bidirectional text controls
+///
+static INVISIBLE_CHARS: &[char] = &[
+    '\u{ad}', '\u{200b}', '\u{200c}', '\u{200d}', '\u{200e}', '\u{200f}', '\u{202a}', '\u{202b}',
+    '\u{202c}', '\u{202d}', '\u{202e}', '\u{2060}', '\u{2066}', '\u{2067}', '\u{2068}', '\u{2069}',
+    '\u{feff}',
+];
+
+/// predicate of whether or not a character wouldn't show up when printed.
+/// that's control characters other than tabs and line endings, including
+/// the C1 controls from U+0080 to U+009F, and the invisible characters above.
+/// any other non-ASCII character, like `é`, is printable
+///
+fn is_non_printable(c: char) -> bool {
+    (c.is_control() && !matches!(c, '\t' | '\n' | '\r')) || INVISIBLE_CHARS.contains(&c)
+}
+
+pub static OPINFO_REMOVE_NON_PRINTABLE: OperationInfo = OperationInfo {
+    name: "remove-non-printable",
+    description: "removes control characters other than tabs and line endings, including Unicode ones like U+0085, and invisible characters like zero-width spaces. other non-ASCII text is kept",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: remove_non_printable,
+};
+
+fn remove_non_printable(_: &OperationArguments, dish: &mut DishData) -> DishResult {
     match dish {
-        DishData::Str(d) => {
-            *dish = DishData::Str(d.chars().rev().collect());
+        DishData::Str(s) => {
+            s.retain(|c| !is_non_printable(c));
             Ok(())
         }
-        DishData::Bin(d) => {
-            d.reverse();
-            Ok(())
+        DishData::Bin(_) => Err(DishError::wrong_type("dish should be string, got binary")),
+    }
+}
+
+static NON_PRINTABLE_STYLES: &[&str] = &["hex", "caret", "unicode"];
+
+pub static OPINFO_SHOW_NON_PRINTABLE: OperationInfo = OperationInfo {
+    name: "show-non-printable",
+    description: "replaces the characters `remove-non-printable` would remove with a visible `style`: `\\x00`, `^@`, or `␀`. non-ASCII ones are shown as `\\u{200b}` in every style",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[("style", OperationArgType::Choice(NON_PRINTABLE_STYLES))],
+    defaults: &[("style", OperationArgDefault::String("hex"))],
+    aliases: &[],
+    op: show_non_printable,
+};
+
+fn show_non_printable(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let style = args.get_string("style")?;
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Err(DishError::wrong_type("dish should be string, got binary")),
+    };
+
+    let mut shown = String::with_capacity(data.len());
+    for c in data.chars() {
+        if !is_non_printable(c) {
+            shown.push(c);
+            continue;
         }
+
+        let n = c as u32;
+        if !c.is_ascii() {
+            shown.push_str(&format!("\\u{{{:x}}}", n));
+            continue;
+        }
+        match style.as_ref() {
+            "caret" => {
+                // caret notation flips the 7th bit, so NUL is ^@ and DEL is ^?
+                shown.push('^');
+                shown.push(char::from_u32(n ^ 0x40).unwrap());
+            }
+            // control pictures start at U+2400, with DEL off on its own at U+2421
+            "unicode" => shown.push(match n {
+                0x7f => '\u{2421}',
+                _ => char::from_u32(0x2400 + n).unwrap(),
+            }),
+            _ => shown.push_str(&format!("\\x{:02x}", n)),
+        }
+    }
+
+    *dish = DishData::Str(shown);
+    Ok(())
+}
+
+pub static OPINFO_CANONICALIZE: OperationInfo = OperationInfo {
+    name: "canonicalize",
+    description: "makes a string stable to compare: `\\r\\n` and `\\r` line endings become `\\n` unless `line_endings` is 0, a trailing newline is removed, and lines are sorted when `sort` is nonzero. binary dishes are left alone",
+    authors: &["s-viour"],
+    category: "Textual",
+    arguments: &[
+        ("line_endings", OperationArgType::Integer),
+        ("sort", OperationArgType::Integer),
+    ],
+    defaults: &[
+        ("line_endings", OperationArgDefault::Integer(1)),
+        ("sort", OperationArgDefault::Integer(0)),
+    ],
+    aliases: &[],
+    op: canonicalize,
+};
+
+fn canonicalize(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let line_endings = args.get_integer("line_endings")? != 0;
+    let sort = args.get_integer("sort")? != 0;
+    let data = match dish {
+        DishData::Str(s) => s,
+        DishData::Bin(_) => return Ok(()),
+    };
+
+    let mut text = if line_endings {
+        data.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        data.clone()
+    };
+    let trailing = if text.ends_with("\r\n") {
+        2
+    } else if text.ends_with('\n') {
+        1
+    } else {
+        0
+    };
+    text.truncate(text.len() - trailing);
+
+    if sort {
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        lines.sort_unstable();
+        text = lines.join("\n");
     }
+
+    *dish = DishData::Str(text);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::ops::textual::*;
-    use crate::{DishData, EMPTY_ARGS};
+    use crate::{DishData, Operation, EMPTY_ARGS};
 
     static ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 
@@ -98,6 +978,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rot_brute_force() {
+        let mut args = OperationArguments::new();
+        args.insert("crib", "".to_string());
+        let mut data = DishData::Str("Uryyb!".to_string());
+        assert!(matches!(rot_brute_force(&args, &mut data), Ok(())));
+        let lines = match &data {
+            DishData::Str(s) => s.lines().map(String::from).collect::<Vec<_>>(),
+            DishData::Bin(_) => panic!("rot-brute-force should return a string"),
+        };
+        assert_eq!(lines.len(), 25);
+        assert_eq!(lines[0], "rot 1: Vszzc!");
+        assert_eq!(lines[12], "rot 13: Hello!");
+
+        let mut args = OperationArguments::new();
+        args.insert("crib", "HELLO".to_string());
+        let mut data = DishData::Str("Uryyb!".to_string());
+        assert!(matches!(rot_brute_force(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("rot 13: Hello!".to_string()));
+
+        let mut args = OperationArguments::new();
+        args.insert("crib", "nope".to_string());
+        let mut data = DishData::Str("Uryyb!".to_string());
+        assert!(matches!(rot_brute_force(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(String::new()));
+    }
+
     #[test]
     fn test_reverse() {
         let mut data = DishData::Str(ALPHABET.to_string());
@@ -105,5 +1012,298 @@ mod tests {
             DishData::Str("ZYXWVUTSRQPONMLKJIHGFEDCBAzyxwvutsrqponmlkjihgfedcba".to_string());
         assert!(matches!(reverse(&EMPTY_ARGS, &mut data), Ok(())));
         assert_eq!(data, _expected);
+
+        let mut data = DishData::Str("añb👾c€".to_string());
+        assert!(matches!(reverse(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("€c👾bña".to_string()));
+
+        let mut data = DishData::Bin(vec![0xe2, 0x82, 0xac, 1]);
+        assert!(matches!(reverse(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(vec![1, 0xac, 0x82, 0xe2]));
+    }
+
+    #[test]
+    fn test_reverse_lines() {
+        let cases = [
+            ("one\ntwo\nthree", "three\ntwo\none"),
+            ("one\ntwo\nthree\n", "three\ntwo\none\n"),
+            ("a b\n\nc d\n", "c d\n\na b\n"),
+            ("", ""),
+        ];
+
+        for (input, expected) in cases {
+            let mut data = DishData::Str(input.to_string());
+            assert!(matches!(reverse_lines(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_reverse_words() {
+        let cases = [
+            ("hello big world", "world big hello"),
+            ("  a  b\tc\r\nd e\n", "c b a\r\ne d\n"),
+            ("\n", "\n"),
+        ];
+
+        for (input, expected) in cases {
+            let mut data = DishData::Str(input.to_string());
+            assert!(matches!(reverse_words(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+
+        let mut data = DishData::Bin(vec![1, 2]);
+        assert!(reverse_words(&EMPTY_ARGS, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_split_identifier_words() {
+        assert_eq!(
+            split_identifier_words("helloWorld foo_bar"),
+            vec!["hello", "World", "foo", "bar"]
+        );
+        assert_eq!(
+            split_identifier_words("HTTPServer-config2Value"),
+            vec!["HTTP", "Server", "config2", "Value"]
+        );
+    }
+
+    #[test]
+    fn test_case_conversions() {
+        let cases: [(Operation, &str); 4] = [
+            (to_camel_case, "helloWorldFooBar"),
+            (to_snake_case, "hello_world_foo_bar"),
+            (to_kebab_case, "hello-world-foo-bar"),
+            (to_title_case, "Hello World Foo Bar"),
+        ];
+
+        for (op, expected) in cases {
+            let mut data = DishData::Str("helloWorld foo_bar".to_string());
+            assert!(matches!(op(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+
+        let mut data = DishData::Bin(vec![1, 2, 3]);
+        assert!(to_snake_case(&EMPTY_ARGS, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_substitute() {
+        let mut args = OperationArguments::new();
+        args.insert("from", "abc".to_string());
+        args.insert("to", "xyz".to_string());
+
+        let mut data = DishData::Str("aabbcc dd!".to_string());
+        assert!(matches!(substitute(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("xxyyzz dd!".to_string()));
+
+        let mut args = OperationArguments::new();
+        args.insert("from", "abc".to_string());
+        args.insert("to", "xy".to_string());
+        let mut data = DishData::Str("abc".to_string());
+        assert!(substitute(&args, &mut data).is_err());
+    }
+
+    fn keyword_args(keyword: &str) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("keyword", keyword.to_string());
+        args
+    }
+
+    #[test]
+    fn test_keyword_alphabet() {
+        let alphabet = keyword_alphabet("Kryptos").unwrap();
+        assert_eq!(alphabet, b"KRYPTOSABCDEFGHIJLMNQUVWXZ".to_vec());
+        assert_eq!(
+            keyword_alphabet("").unwrap(),
+            (b'A'..=b'Z').collect::<Vec<u8>>()
+        );
+        assert!(keyword_alphabet("two words").is_err());
+    }
+
+    #[test]
+    fn test_keyword_cipher_round_trip() {
+        let input = "Attack at Dawn! The quick brown fox jumps over the lazy dog.";
+        for keyword in ["zebras", "KEYWORD", "b"] {
+            let mut data = DishData::Str(input.to_string());
+            assert!(matches!(
+                keyword_cipher_encode(&keyword_args(keyword), &mut data),
+                Ok(())
+            ));
+            assert_ne!(data, DishData::Str(input.to_string()));
+
+            assert!(matches!(
+                keyword_cipher_decode(&keyword_args(keyword), &mut data),
+                Ok(())
+            ));
+            assert_eq!(data, DishData::Str(input.to_string()));
+        }
+
+        let mut data = DishData::Str("Flee at once".to_string());
+        assert!(matches!(
+            keyword_cipher_encode(&keyword_args("zebras"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str("Siaa zq lkba".to_string()));
+
+        let mut data = DishData::Str("hello".to_string());
+        assert!(keyword_cipher_encode(&keyword_args("k3y"), &mut data).is_err());
+    }
+
+    #[test]
+    fn test_roman_round_trip() {
+        let cases = [
+            ("1", "I"),
+            ("4", "IV"),
+            ("9", "IX"),
+            ("14", "XIV"),
+            ("40", "XL"),
+            ("444", "CDXLIV"),
+            ("1994", "MCMXCIV"),
+            ("3999", "MMMCMXCIX"),
+        ];
+
+        for (number, numeral) in cases {
+            let mut data = DishData::Str(number.to_string());
+            assert!(matches!(to_roman(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(numeral.to_string()));
+
+            assert!(matches!(from_roman(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(number.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_roman_invalid() {
+        for n in ["0", "4000", "-3", "abc"] {
+            let mut data = DishData::Str(n.to_string());
+            assert!(to_roman(&EMPTY_ARGS, &mut data).is_err());
+        }
+
+        for numeral in ["IIII", "IC", "VX", "MMMM", "ABC", ""] {
+            let mut data = DishData::Str(numeral.to_string());
+            assert!(from_roman(&EMPTY_ARGS, &mut data).is_err());
+        }
+    }
+
+    #[test]
+    fn test_number_to_words() {
+        let cases = [
+            ("0", "zero"),
+            ("7", "seven"),
+            ("40", "forty"),
+            ("123", "one hundred twenty-three"),
+            ("-15", "negative fifteen"),
+            ("1000001", "one million one"),
+            (
+                "2147483647",
+                "two billion one hundred forty-seven million four hundred eighty-three thousand six hundred forty-seven",
+            ),
+        ];
+
+        for (number, words) in cases {
+            let mut data = DishData::Str(number.to_string());
+            assert!(matches!(number_to_words(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(words.to_string()));
+        }
+
+        let mut data = DishData::Str("twelve".to_string());
+        assert!(number_to_words(&EMPTY_ARGS, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_nato_round_trip() {
+        let input = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+        let mut data = DishData::Str(input.to_string());
+        assert!(matches!(to_nato(&EMPTY_ARGS, &mut data), Ok(())));
+        let words = match &data {
+            DishData::Str(s) => s.split(' ').count(),
+            DishData::Bin(_) => panic!("to-nato should return a string"),
+        };
+        assert_eq!(words, input.len());
+
+        assert!(matches!(from_nato(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str(input.to_string()));
+    }
+
+    #[test]
+    fn test_nato() {
+        let mut data = DishData::Str("sos 9!".to_string());
+        assert!(matches!(to_nato(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(
+            data,
+            DishData::Str("Sierra Oscar Sierra   Nine !".to_string())
+        );
+
+        let mut data = DishData::Str("alfa  JULIETT\nx-ray niner".to_string());
+        assert!(matches!(from_nato(&EMPTY_ARGS, &mut data), Ok(())));
+        assert_eq!(data, DishData::Str("AJX9".to_string()));
+
+        let mut data = DishData::Str("Alpha Bravo Chocolate".to_string());
+        assert!(from_nato(&EMPTY_ARGS, &mut data).is_err());
+    }
+
+    static CONTROL_INPUT: &str = "a\u{0}b\u{1b}[0m\tc\r\n\u{7f}é\u{85}z\u{200b}w";
+
+    #[test]
+    fn test_remove_non_printable() {
+        let mut data = DishData::Str(CONTROL_INPUT.to_string());
+        assert!(matches!(
+            remove_non_printable(&EMPTY_ARGS, &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str("ab[0m\tc\r\nézw".to_string()));
+
+        let mut data = DishData::Bin(vec![0]);
+        assert!(remove_non_printable(&EMPTY_ARGS, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let cases = [
+            ("b\r\na\rc\n", 1, 0, "b\na\nc"),
+            ("b\r\na\rc\n", 1, 1, "a\nb\nc"),
+            ("b\r\na\r\n", 0, 0, "b\r\na"),
+            ("one line", 1, 0, "one line"),
+            // only one trailing newline is stripped
+            ("a\n\n", 1, 0, "a\n"),
+            ("", 1, 1, ""),
+        ];
+
+        for (input, line_endings, sort, expected) in cases {
+            let mut args = OperationArguments::new();
+            args.insert("line_endings", line_endings);
+            args.insert("sort", sort);
+            let mut data = DishData::Str(input.to_string());
+            assert!(matches!(canonicalize(&args, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()), "{:?}", input);
+        }
+
+        let mut data = DishData::Bin(vec![b'b', b'\r', b'\n', 0xff]);
+        assert!(matches!(
+            canonicalize(&OPINFO_CANONICALIZE.default_arguments(), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Bin(vec![b'b', b'\r', b'\n', 0xff]));
+    }
+
+    #[test]
+    fn test_show_non_printable() {
+        let cases = [
+            ("hex", "a\\x00b\\x1b[0m\tc\r\n\\x7fé\\u{85}z\\u{200b}w"),
+            ("caret", "a^@b^[[0m\tc\r\n^?é\\u{85}z\\u{200b}w"),
+            (
+                "unicode",
+                "a\u{2400}b\u{241b}[0m\tc\r\n\u{2421}é\\u{85}z\\u{200b}w",
+            ),
+        ];
+
+        for (style, expected) in cases {
+            let mut args = OperationArguments::new();
+            args.insert("style", style.to_string());
+            let mut data = DishData::Str(CONTROL_INPUT.to_string());
+            assert!(matches!(show_non_printable(&args, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
     }
 }
@@ -1,4 +1,7 @@
-use crate::{DishData, DishError, DishResult, OperationArgType, OperationArguments, OperationInfo};
+use crate::{
+    DishData, DishError, DishResult, OperationArgDefault, OperationArgType, OperationArguments,
+    OperationInfo,
+};
 
 
 pub static OPINFO_TAKE_BYTES: OperationInfo = OperationInfo {
@@ -7,13 +10,17 @@ pub static OPINFO_TAKE_BYTES: OperationInfo = OperationInfo {
     authors: &["s-viour"],
     category: "Utility",
     arguments: &[("n", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
     op: take_bytes,
 };
 
 fn take_bytes(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     let ni = args.get_integer("n")?;
     if ni < 0 {
-        return Err(DishError("amount to take must be nonnegative".to_string()));
+        return Err(DishError::invalid_argument(
+            "amount to take must be nonnegative",
+        ));
     }
     let n = ni as usize;
     
@@ -37,13 +44,15 @@ pub static OPINFO_DROP_BYTES: OperationInfo = OperationInfo {
     authors: &["s-viour"],
     category: "Utility",
     arguments: &[("n", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
     op: drop_bytes,
 };
 
 fn drop_bytes(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     let ni = args.get_integer("n")?;
     if ni < 0 {
-        return Err(DishError("integer must be nonnegative".to_string()));
+        return Err(DishError::invalid_argument("integer must be nonnegative"));
     }
     let n = ni as usize;
     
@@ -69,3 +78,577 @@ fn drop_bytes(args: &OperationArguments, dish: &mut DishData) -> DishResult {
     
     Ok(())
 }
+
+pub static OPINFO_SLICE: OperationInfo = OperationInfo {
+    name: "slice",
+    description: "keeps the bytes from `start` up to (but not including) `end`. negative indices count from the end",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[
+        ("start", OperationArgType::Integer),
+        ("end", OperationArgType::Integer),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: slice,
+};
+
+/// helper function that resolves a python-style index into a position in `0..=len`
+///
+fn resolve_index(i: i64, len: usize) -> usize {
+    if i < 0 {
+        len.saturating_sub(i.unsigned_abs() as usize)
+    } else {
+        (i as usize).min(len)
+    }
+}
+
+fn slice(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let start = args.get_integer("start")?;
+    let end = args.get_integer("end")?;
+
+    let bytes = dish.as_bytes();
+    let start = resolve_index(start, bytes.len());
+    let end = resolve_index(end, bytes.len());
+
+    // an empty range (start past end) is valid and produces an empty dish
+    let v = if start < end {
+        bytes[start..end].to_vec()
+    } else {
+        Vec::new()
+    };
+
+    *dish = DishData::Bin(v);
+    Ok(())
+}
+
+pub static OPINFO_PAD_BYTES: OperationInfo = OperationInfo {
+    name: "pad-bytes",
+    description:
+        "pads the input up to `length` bytes with `byte` on the given `side` (`left` or `right`)",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[
+        ("length", OperationArgType::Integer),
+        ("byte", OperationArgType::Integer),
+        ("side", OperationArgType::String),
+    ],
+    defaults: &[],
+    aliases: &[],
+    op: pad_bytes,
+};
+
+/// the largest dish in bytes that operations like `pad-bytes` and `repeat` will build
+const MAX_OUTPUT_LEN: usize = 1 << 28;
+
+fn pad_bytes(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let length = args.get_integer("length")?;
+    if length < 0 {
+        return Err(DishError::invalid_argument("length must be nonnegative"));
+    }
+    if length as u64 > MAX_OUTPUT_LEN as u64 {
+        return Err(DishError::invalid_argument(format!(
+            "length must be at most {}. got {}",
+            MAX_OUTPUT_LEN, length
+        )));
+    }
+    let byte: u8 = match args.get_integer("byte")?.try_into() {
+        Ok(b) => b,
+        Err(_) => {
+            return Err(DishError::invalid_argument(
+                "byte must be between 0 and 255",
+            ))
+        }
+    };
+    let side = args.get_string("side")?;
+
+    let mut v = dish.as_bytes().to_vec();
+    let missing = (length as usize).saturating_sub(v.len());
+    match side.as_ref() {
+        "left" => {
+            v.splice(0..0, std::iter::repeat_n(byte, missing));
+        }
+        "right" => v.resize(v.len() + missing, byte),
+        _ => {
+            return Err(DishError::invalid_argument(format!(
+                "side must be either 'left' or 'right'. got '{}'",
+                side
+            )))
+        }
+    }
+
+    *dish = DishData::Bin(v);
+    Ok(())
+}
+
+pub static OPINFO_REPEAT: OperationInfo = OperationInfo {
+    name: "repeat",
+    description: "repeats the input `count` times",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[("count", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: repeat,
+};
+
+fn repeat(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let count = args.get_integer("count")?;
+    if count < 0 {
+        return Err(DishError::invalid_argument("count must be nonnegative"));
+    }
+
+    let len = dish.as_bytes().len();
+    match len.checked_mul(count as usize) {
+        Some(total) if total <= MAX_OUTPUT_LEN => {}
+        _ => {
+            return Err(DishError::invalid_argument(format!(
+                "repeating {} bytes {} times is more than the {} byte limit",
+                len, count, MAX_OUTPUT_LEN
+            )))
+        }
+    }
+
+    *dish = DishData::Bin(dish.as_bytes().repeat(count as usize));
+    Ok(())
+}
+
+pub static OPINFO_LENGTH: OperationInfo = OperationInfo {
+    name: "length",
+    description: "replaces the input with its length in bytes",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: length,
+};
+
+fn length(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    *dish = DishData::Str(dish.as_bytes().len().to_string());
+    Ok(())
+}
+
+/// helper function that makes the permutation `shuffle-bytes` applies to `len` bytes,
+/// as the index in the input that ends up at each position in the output
+///
+/// it's a Fisher-Yates shuffle driven by splitmix64, so the same seed always
+/// gives the same permutation on every platform
+///
+fn seeded_permutation(seed: i64, len: usize) -> Vec<usize> {
+    let mut state = seed as u64;
+    let mut next = || {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    };
+
+    let mut permutation: Vec<usize> = (0..len).collect();
+    for i in (1..len).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        permutation.swap(i, j);
+    }
+    permutation
+}
+
+pub static OPINFO_SHUFFLE_BYTES: OperationInfo = OperationInfo {
+    name: "shuffle-bytes",
+    description: "shuffles the bytes of the input. the same seed always shuffles the same way",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[("seed", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: shuffle_bytes,
+};
+
+fn shuffle_bytes(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let seed = args.get_integer("seed")?;
+    let bytes = dish.as_bytes();
+
+    let shuffled = seeded_permutation(seed, bytes.len())
+        .into_iter()
+        .map(|i| bytes[i])
+        .collect();
+
+    *dish = DishData::Bin(shuffled);
+    Ok(())
+}
+
+pub static OPINFO_UNSHUFFLE_BYTES: OperationInfo = OperationInfo {
+    name: "unshuffle-bytes",
+    description: "undoes `shuffle-bytes` with the same seed",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[("seed", OperationArgType::Integer)],
+    defaults: &[],
+    aliases: &[],
+    op: unshuffle_bytes,
+};
+
+fn unshuffle_bytes(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let seed = args.get_integer("seed")?;
+    let bytes = dish.as_bytes();
+
+    let mut unshuffled = vec![0; bytes.len()];
+    for (from, to) in seeded_permutation(seed, bytes.len())
+        .into_iter()
+        .enumerate()
+    {
+        unshuffled[to] = bytes[from];
+    }
+
+    *dish = DishData::Bin(unshuffled);
+    Ok(())
+}
+
+static STRINGS_ENCODINGS: &[&str] = &["ascii", "utf16le"];
+
+pub static OPINFO_STRINGS: OperationInfo = OperationInfo {
+    name: "strings",
+    description: "finds runs of printable characters at least `min_length` long, one per line, like the unix `strings` tool",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[
+        ("min_length", OperationArgType::Integer),
+        ("encoding", OperationArgType::Choice(STRINGS_ENCODINGS)),
+    ],
+    defaults: &[("encoding", OperationArgDefault::String("ascii"))],
+    aliases: &[],
+    op: strings,
+};
+
+/// predicate of whether or not a byte is printable ASCII (tabs count too, like in `strings`)
+fn is_printable(b: u8) -> bool {
+    b == b'\t' || (0x20..=0x7e).contains(&b)
+}
+
+fn strings(args: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let min_length = args.get_integer("min_length")?;
+    if min_length < 1 {
+        return Err(DishError::invalid_argument("min_length must be at least 1"));
+    }
+    let min_length = min_length as usize;
+    // utf16le characters are a printable byte followed by a zero byte
+    let width = match args.get_string("encoding")?.as_ref() {
+        "utf16le" => 2,
+        _ => 1,
+    };
+
+    let bytes = dish.as_bytes();
+    let mut found: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let is_char = is_printable(bytes[i]) && (width == 1 || bytes.get(i + 1) == Some(&0));
+        if is_char {
+            current.push(bytes[i] as char);
+            i += width;
+        } else {
+            if current.len() >= min_length {
+                found.push(std::mem::take(&mut current));
+            }
+            current.clear();
+            i += 1;
+        }
+    }
+    if current.len() >= min_length {
+        found.push(current);
+    }
+
+    *dish = DishData::Str(found.join("\n"));
+    Ok(())
+}
+
+/// magic numbers checked by `detect-format`, as (offset, bytes, format name)
+/// more specific signatures need to come before shorter ones they start with
+///
+static MAGIC_NUMBERS: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "png"),
+    (0, b"\xff\xd8\xff", "jpeg"),
+    (0, b"GIF87a", "gif"),
+    (0, b"GIF89a", "gif"),
+    (8, b"WEBP", "webp"),
+    (0, b"BM", "bmp"),
+    (0, b"%PDF-", "pdf"),
+    (0, b"\x1f\x8b", "gzip"),
+    (0, b"BZh", "bzip2"),
+    (0, b"\xfd7zXZ\x00", "xz"),
+    (0, b"7z\xbc\xaf\x27\x1c", "7z"),
+    (0, b"Rar!\x1a\x07", "rar"),
+    (0, b"PK\x03\x04", "zip"),
+    (0, b"PK\x05\x06", "zip"),
+    (257, b"ustar", "tar"),
+    (0, b"\x7fELF", "elf"),
+    (0, b"MZ", "pe"),
+    (0, b"\xfe\xed\xfa\xce", "mach-o"),
+    (0, b"\xfe\xed\xfa\xcf", "mach-o"),
+    (0, b"\xce\xfa\xed\xfe", "mach-o"),
+    (0, b"\xcf\xfa\xed\xfe", "mach-o"),
+    (0, b"\x00asm", "wasm"),
+    (0, b"SQLite format 3\x00", "sqlite"),
+    (0, b"OggS", "ogg"),
+    (0, b"fLaC", "flac"),
+    (0, b"ID3", "mp3"),
+];
+
+pub static OPINFO_DETECT_FORMAT: OperationInfo = OperationInfo {
+    name: "detect-format",
+    description: "detects the file format of the input from its magic number (png, gzip, elf, etc.), or `unknown`",
+    authors: &["s-viour"],
+    category: "Utility",
+    arguments: &[],
+    defaults: &[],
+    aliases: &[],
+    op: detect_format,
+};
+
+fn detect_format(_: &OperationArguments, dish: &mut DishData) -> DishResult {
+    let bytes = dish.as_bytes();
+    let format = MAGIC_NUMBERS
+        .iter()
+        .find(|(offset, magic, _)| bytes.get(*offset..).is_some_and(|b| b.starts_with(magic)))
+        .map_or("unknown", |(_, _, name)| name);
+
+    *dish = DishData::Str(format.to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::utility::*;
+    use crate::{DishData, EMPTY_ARGS};
+
+    fn slice_args(start: i64, end: i64) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("start", start);
+        args.insert("end", end);
+        args
+    }
+
+    #[test]
+    fn test_slice() {
+        let cases: [(i64, i64, &[u8]); 6] = [
+            (1, 4, b"ell"),
+            (0, -1, b"hell"),
+            (-3, 5, b"llo"),
+            (2, 100, b"llo"),
+            (-100, 2, b"he"),
+            (4, 2, b""),
+        ];
+
+        for (start, end, expected) in cases {
+            let mut data = DishData::Str("hello".to_string());
+            assert!(matches!(slice(&slice_args(start, end), &mut data), Ok(())));
+            assert_eq!(data, DishData::Bin(expected.to_vec()));
+        }
+    }
+
+    fn pad_args(length: i64, byte: i64, side: &str) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("length", length);
+        args.insert("byte", byte);
+        args.insert("side", side.to_string());
+        args
+    }
+
+    #[test]
+    fn test_pad_bytes() {
+        let mut data = DishData::Str("abc".to_string());
+        assert!(matches!(
+            pad_bytes(&pad_args(5, 0, "right"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Bin(vec![97, 98, 99, 0, 0]));
+
+        let mut data = DishData::Bin(vec![1, 2]);
+        assert!(matches!(
+            pad_bytes(&pad_args(4, 255, "left"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Bin(vec![255, 255, 1, 2]));
+
+        // longer inputs are left untouched
+        let mut data = DishData::Bin(vec![1, 2, 3]);
+        assert!(matches!(
+            pad_bytes(&pad_args(2, 0, "left"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Bin(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_pad_bytes_invalid() {
+        for args in [
+            pad_args(-1, 0, "left"),
+            pad_args(4, 256, "left"),
+            pad_args(4, 0, "middle"),
+            pad_args(MAX_OUTPUT_LEN as i64 + 1, 0, "left"),
+            pad_args(i64::MAX, 0, "right"),
+        ] {
+            let mut data = DishData::Bin(vec![1]);
+            assert!(pad_bytes(&args, &mut data).is_err());
+        }
+    }
+
+    #[test]
+    fn test_repeat() {
+        let mut args = OperationArguments::new();
+        args.insert("count", 3);
+        let mut data = DishData::Str("ab".to_string());
+        assert!(matches!(repeat(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(b"ababab".to_vec()));
+
+        let mut args = OperationArguments::new();
+        args.insert("count", 0);
+        let mut data = DishData::Str("ab".to_string());
+        assert!(matches!(repeat(&args, &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(vec![]));
+
+        let mut args = OperationArguments::new();
+        args.insert("count", -1);
+        assert!(repeat(&args, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_repeat_too_large() {
+        for count in [i64::MAX, (MAX_OUTPUT_LEN / 2 + 1) as i64] {
+            let mut args = OperationArguments::new();
+            args.insert("count", count);
+            let mut data = DishData::Str("ab".to_string());
+            assert!(repeat(&args, &mut data).is_err());
+        }
+    }
+
+    #[test]
+    fn test_length() {
+        let cases = [
+            (DishData::Str(String::new()), "0"),
+            (DishData::Str("héllo".to_string()), "6"),
+            (DishData::Bin(vec![0, 1, 2, 255]), "4"),
+        ];
+
+        for (mut data, expected) in cases {
+            assert!(matches!(length(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+    }
+
+    fn strings_args(min_length: i64, encoding: &str) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("min_length", min_length);
+        args.insert("encoding", encoding.to_string());
+        args
+    }
+
+    #[test]
+    fn test_strings() {
+        let mut data = DishData::Bin(b"\x7fELF\x02\x01hello world\x00ab\x00\xfftab\there".to_vec());
+        assert!(matches!(
+            strings(&strings_args(4, "ascii"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str("hello world\ntab\there".to_string()));
+
+        // "ELF" is too short and nothing matches at all here, which isn't an error
+        let mut data = DishData::Bin(b"\x7fELF\x00\x01".to_vec());
+        assert!(matches!(
+            strings(&strings_args(4, "ascii"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str(String::new()));
+
+        assert!(strings(&strings_args(0, "ascii"), &mut DishData::Bin(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_strings_utf16le() {
+        // a wide string that starts on an odd offset, then an ascii one
+        let mut bytes = vec![0xff];
+        bytes.extend("wide".encode_utf16().flat_map(|c| c.to_le_bytes()));
+        bytes.extend(b"\x00\x00narrow");
+        let mut data = DishData::Bin(bytes.clone());
+        assert!(matches!(
+            strings(&strings_args(4, "utf16le"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str("wide".to_string()));
+
+        let mut data = DishData::Bin(bytes);
+        assert!(matches!(
+            strings(&strings_args(4, "ascii"), &mut data),
+            Ok(())
+        ));
+        assert_eq!(data, DishData::Str("narrow".to_string()));
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let mut tar = vec![0; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+
+        let cases: [(&[u8], &str); 7] = [
+            (b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR", "png"),
+            (b"\x1f\x8b\x08\x00", "gzip"),
+            (b"\x7fELF\x02\x01\x01", "elf"),
+            (b"RIFF\x24\x00\x00\x00WEBPVP8 ", "webp"),
+            (&tar, "tar"),
+            (b"hello world", "unknown"),
+            (b"", "unknown"),
+        ];
+
+        for (bytes, expected) in cases {
+            let mut data = DishData::Bin(bytes.to_vec());
+            assert!(matches!(detect_format(&EMPTY_ARGS, &mut data), Ok(())));
+            assert_eq!(data, DishData::Str(expected.to_string()));
+        }
+    }
+
+    fn seed_args(seed: i64) -> OperationArguments {
+        let mut args = OperationArguments::new();
+        args.insert("seed", seed);
+        args
+    }
+
+    #[test]
+    fn test_shuffle_bytes() {
+        let original: Vec<u8> = (0..=255).collect();
+
+        for seed in [0, 1, -42, i64::MAX] {
+            let mut data = DishData::Bin(original.clone());
+            assert!(matches!(shuffle_bytes(&seed_args(seed), &mut data), Ok(())));
+            let shuffled = data.clone();
+            assert_ne!(shuffled.as_bytes(), original.as_slice());
+
+            // the same bytes, just moved around
+            let mut sorted = shuffled.as_bytes().to_vec();
+            sorted.sort();
+            assert_eq!(sorted, original);
+
+            // and the same way every time
+            let mut again = DishData::Bin(original.clone());
+            assert!(matches!(
+                shuffle_bytes(&seed_args(seed), &mut again),
+                Ok(())
+            ));
+            assert_eq!(again, shuffled);
+
+            assert!(matches!(
+                unshuffle_bytes(&seed_args(seed), &mut data),
+                Ok(())
+            ));
+            assert_eq!(data, DishData::Bin(original.clone()));
+        }
+
+        let mut data = DishData::Str("hello".to_string());
+        assert!(matches!(shuffle_bytes(&seed_args(7), &mut data), Ok(())));
+        assert!(matches!(unshuffle_bytes(&seed_args(8), &mut data), Ok(())));
+        assert_ne!(data, DishData::Bin(b"hello".to_vec()));
+
+        let mut data = DishData::Str(String::new());
+        assert!(matches!(shuffle_bytes(&seed_args(7), &mut data), Ok(())));
+        assert_eq!(data, DishData::Bin(vec![]));
+    }
+}
@@ -0,0 +1,60 @@
+use codebake::{Dish, DishData, OperationArguments, EMPTY_ARGS};
+
+fn data(dish: &Dish) -> &DishData {
+    match dish.data() {
+        Some(data) => data,
+        None => panic!("dish failed: {}", dish),
+    }
+}
+
+#[test]
+fn test_apply_named() {
+    let mut dish = Dish::from_string("hello".to_string());
+    dish.apply_named("to-base64", &EMPTY_ARGS)
+        .apply_named("reverse", &EMPTY_ARGS);
+    assert_eq!(data(&dish), &DishData::Str("=8GbsVGa".to_string()));
+
+    let mut args = OperationArguments::new();
+    args.insert("n", 13);
+    let mut dish = Dish::from_string("uryyb".to_string());
+    dish.apply_named("rot13", &args);
+    assert_eq!(data(&dish), &DishData::Str("hello".to_string()));
+}
+
+#[test]
+fn test_apply_named_defaults() {
+    // `unique` has a default, so it can be left out
+    let mut args = OperationArguments::new();
+    args.insert("type", "ip".to_string());
+    let mut dish = Dish::from_string("1.1.1.1 and 1.1.1.1".to_string());
+    dish.apply_named("extract", &args);
+    assert_eq!(data(&dish), &DishData::Str("1.1.1.1".to_string()));
+
+    // and an operation whose arguments all have defaults works without any
+    let mut dish = Dish::from_string("\u{0}".to_string());
+    dish.apply_named("show-non-printable", &EMPTY_ARGS);
+    assert_eq!(data(&dish), &DishData::Str("\\x00".to_string()));
+}
+
+#[test]
+fn test_apply_named_alias() {
+    let mut dish = Dish::from_string("aGk=".to_string());
+    dish.apply_named("from-b64", &EMPTY_ARGS);
+    assert_eq!(data(&dish), &DishData::Bin(b"hi".to_vec()));
+}
+
+#[test]
+fn test_apply_named_failures() {
+    let mut dish = Dish::from_string("hello".to_string());
+    dish.apply_named("not-an-operation", &EMPTY_ARGS);
+    assert!(dish.is_failure());
+
+    // a missing argument fails the dish rather than panicking
+    let mut dish = Dish::from_string("hello".to_string());
+    dish.apply_named("rot13", &EMPTY_ARGS);
+    assert!(dish.is_failure());
+
+    // and nothing more happens to a failed dish
+    dish.apply_named("to-base64", &EMPTY_ARGS);
+    assert!(dish.is_failure());
+}
@@ -0,0 +1,35 @@
+use codebake::{OperationArguments, EMPTY_ARGS};
+
+#[test]
+fn test_argument_errors() {
+    let mut args = OperationArguments::new();
+    args.insert("radix", 16);
+    args.insert("pattern", "a+".to_string());
+    args.insert("key", vec![1u8, 2, 3]);
+
+    assert_eq!(args.get_integer("radix").unwrap(), 16);
+    assert_eq!(args.get_string("pattern").unwrap(), "a+");
+    assert_eq!(args.get_bytes("key").unwrap(), vec![1, 2, 3]);
+
+    let message = |e: codebake::DishError| e.message().to_string();
+    assert_eq!(
+        message(args.get_integer("width").unwrap_err()),
+        "argument 'width' not found"
+    );
+    assert_eq!(
+        message(EMPTY_ARGS.get_string("radix").unwrap_err()),
+        "argument 'radix' not found"
+    );
+    assert_eq!(
+        message(args.get_string("radix").unwrap_err()),
+        "argument 'radix' expected string, got integer"
+    );
+    assert_eq!(
+        message(args.get_integer("pattern").unwrap_err()),
+        "argument 'pattern' expected integer, got string"
+    );
+    assert_eq!(
+        message(args.get_bytes("pattern").unwrap_err()),
+        "argument 'pattern' expected bytes, got string"
+    );
+}
@@ -0,0 +1,67 @@
+use codebake::{Dish, DishData, DishError, DishErrorKind, EMPTY_ARGS};
+
+#[test]
+fn test_dish_accessors() {
+    let dish = Dish::from_string("hello".to_string());
+    assert!(dish.is_success());
+    assert!(!dish.is_failure());
+    assert!(dish.error().is_none());
+    assert_eq!(dish.data(), Some(&DishData::Str("hello".to_string())));
+
+    let mut dish = Dish::from_string("hello".to_string());
+    dish.apply_named("not-an-operation", &EMPTY_ARGS);
+    assert!(dish.is_failure());
+    assert!(!dish.is_success());
+    assert!(dish.data().is_none());
+
+    let error = dish.error().expect("dish should have failed");
+    assert_eq!(error.message(), "no operation named 'not-an-operation'");
+    assert_eq!(
+        format!("{}", error),
+        "dish error: no operation named 'not-an-operation'"
+    );
+}
+
+#[test]
+fn test_dish_error_kinds() {
+    let kind = |name: &str, input: Dish| {
+        let mut dish = input;
+        dish.apply_named(name, &EMPTY_ARGS);
+        dish.error().expect("dish should have failed").kind()
+    };
+
+    let bytes = || Dish::from_bytes(vec![0xff]);
+    let text = |s: &str| Dish::from_string(s.to_string());
+    assert_eq!(kind("to-title-case", bytes()), DishErrorKind::WrongType);
+    assert_eq!(
+        kind("from-base64", text("not base64!")),
+        DishErrorKind::DecodeError
+    );
+    assert_eq!(kind("rot13", text("hello")), DishErrorKind::InvalidArgument);
+    assert_eq!(
+        kind("not-an-operation", text("hello")),
+        DishErrorKind::Unsupported
+    );
+
+    // the kind doesn't change how the error is displayed
+    let error = DishError::new(DishErrorKind::Io, "could not read file");
+    assert_eq!(error.kind(), DishErrorKind::Io);
+    assert_eq!(format!("{}", error), "dish error: could not read file");
+}
+
+#[test]
+fn test_dish_display() {
+    let long = "a".repeat(100);
+    let data = DishData::Str(long.clone());
+    assert_eq!(format!("{}", data), format!("\"{}...\"", "a".repeat(80)));
+    assert_eq!(data.display_full(), format!("\"{}\"", long));
+    assert_eq!(data.display_truncated(Some(3)), "\"aaa...\"");
+    assert_eq!(data.display_truncated(Some(100)), format!("\"{}\"", long));
+
+    let data = DishData::Bin(vec![b'b'; 40]);
+    assert_eq!(format!("{}", data), format!("[{}...]", "b".repeat(32)));
+    assert_eq!(data.display_full(), format!("[{}]", "b".repeat(40)));
+
+    let dish = Dish::from_string(long.clone());
+    assert_eq!(dish.display_full(), format!("Dish(\"{}\")", long));
+}
@@ -0,0 +1,40 @@
+use codebake::{Dish, DishData, OperationArguments, Recipe, EMPTY_ARGS};
+
+#[test]
+fn test_recipe_bake() {
+    let mut args = OperationArguments::new();
+    args.insert("n", 13);
+    let recipe = Recipe::new()
+        .push("rot13", &args)
+        .push("reverse", &EMPTY_ARGS)
+        .push("to-base64", &EMPTY_ARGS);
+
+    let mut dish = Dish::from_string("hello world!".to_string());
+    recipe.bake(&mut dish);
+    match &dish {
+        Dish::Success(data) => assert_eq!(data, &DishData::Str("IXF5ZWJqIGJ5eXJ1".to_string())),
+        Dish::Failure(e) => panic!("dish failed: {}", e),
+    }
+
+    // an empty recipe does nothing
+    let mut dish = Dish::from_string("hello".to_string());
+    Recipe::new().bake(&mut dish);
+    assert!(matches!(dish, Dish::Success(DishData::Str(s)) if s == "hello"));
+}
+
+#[test]
+fn test_recipe_bake_failure() {
+    let recipe = Recipe::new()
+        .push("to-base64", &EMPTY_ARGS)
+        .push("not-an-operation", &EMPTY_ARGS)
+        .push("reverse", &EMPTY_ARGS);
+
+    let mut dish = Dish::from_string("hello".to_string());
+    recipe.bake(&mut dish);
+    let err = format!("{}", dish);
+    assert!(err.contains("step 2 (not-an-operation)"), "{}", err);
+
+    // baking a failed dish keeps the original error
+    recipe.bake(&mut dish);
+    assert_eq!(format!("{}", dish), err);
+}
@@ -0,0 +1,40 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// runs the REPL binary with `input` on its stdin, returning what it printed
+fn run_repl(input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_codebake"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start codebake");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_read_line() {
+    // the line after `(read-line)` is read by it, not evaluated by the REPL
+    let output = run_repl("(read-line)\nhello there\n(+ 1 2)\n(read-line)\n");
+    assert!(output.contains("codebake> hello there\n"), "{}", output);
+    assert!(output.contains("codebake> 3\n"), "{}", output);
+    assert!(output.contains("codebake> nil\n"), "{}", output);
+}
+
+#[test]
+fn test_read_stdin() {
+    let output = run_repl("(to-hex (read-stdin))\nsome data\n(more\n");
+    assert!(
+        output.contains("73 6f 6d 65 20 64 61 74 61 0a 28 6d 6f 72 65 0a"),
+        "{}",
+        output
+    );
+}
@@ -1,16 +1,39 @@
-use codebake::lisp;
-use web_sys::HtmlTextAreaElement;
+use std::{cell::RefCell, rc::Rc};
+
+use codebake::{lisp, ops::OPERATIONS, Dish, DishData, EMPTY_ARGS};
+use gloo_file::{callbacks::FileReader, Blob};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlAnchorElement, HtmlInputElement, HtmlTextAreaElement, Url};
 use yew::prelude::*;
 
 struct App {
-    reader: lisp::Reader,
+    reader: Rc<lisp::Reader>,
     env: lisp::Environment<'static>,
     text_input: NodeRef,
-    output: String,
+    /// everything evaluated since the page loaded (or the last clear), oldest first
+    transcript: Vec<Entry>,
+    /// whether binary dishes are shown as a hexdump
+    hexdump: bool,
+    /// the in-flight read of an uploaded file, dropping it cancels the read
+    file_reader: Option<FileReader>,
+}
+
+/// A single item in the output transcript
+///
+enum Entry {
+    /// an expression from the script and what it evaluated to
+    Eval(String, Result<lisp::Expression, String>),
+    /// a message from the interpreter itself, like a file being loaded
+    Note(String),
 }
 
 enum Msg {
     Run,
+    Clear,
+    ToggleHexdump,
+    Upload(Option<web_sys::File>),
+    Uploaded(String, Result<Vec<u8>, String>),
+    Download,
 }
 
 impl Component for App {
@@ -18,17 +41,21 @@ impl Component for App {
     type Properties = ();
 
     fn create(_ctx: &Context<Self>) -> Self {
-        let reader = lisp::Reader::new();
+        let reader = lisp::default_reader();
         let env = lisp::default_env(&reader);
+        // the wasm stack is much smaller than a native one
+        env.set_max_depth(1000);
         Self {
             reader: reader,
             env: env,
             text_input: NodeRef::default(),
-            output: String::new(),
+            transcript: Vec::new(),
+            hexdump: true,
+            file_reader: None,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Self::Message::Run => {
                 let input: String = self
@@ -39,27 +66,98 @@ impl Component for App {
                 let split = get_expressions(&input);
                 log::debug!("running script {}", input);
 
+                // definitions land in `self.env`, so they're still around on the next run
                 for expr in split {
-                    if expr == "" {
+                    let expr = expr.trim().to_string();
+                    if expr.is_empty() {
                         continue;
                     }
                     log::debug!("{}", expr);
 
-                    let expr_str = expr.to_string();
-                    match lisp::parse_eval(&self.reader, &mut self.env, &expr_str) {
-                        Ok(expr) => self.output = format!("{}", expr),
-                        Err(e) => self.output = format!("{}", e),
+                    let res = lisp::parse_eval(&self.reader, &mut self.env, &expr)
+                        .map_err(|e| format!("{}", e));
+                    self.transcript.push(Entry::Eval(expr, res));
+                }
+
+                true
+            }
+            Self::Message::Clear => {
+                self.transcript.clear();
+                true
+            }
+            Self::Message::ToggleHexdump => {
+                self.hexdump = !self.hexdump;
+                true
+            }
+            Self::Message::Upload(file) => {
+                let file = match file {
+                    Some(file) => gloo_file::File::from(file),
+                    None => return false,
+                };
+
+                let name = file.name();
+                let link = ctx.link().clone();
+                self.file_reader = Some(gloo_file::callbacks::read_as_bytes(&file, move |res| {
+                    link.send_message(Msg::Uploaded(name, res.map_err(|e| e.to_string())))
+                }));
+
+                false
+            }
+            Self::Message::Uploaded(name, res) => {
+                self.file_reader = None;
+                match res {
+                    Ok(bytes) => {
+                        let len = bytes.len();
+                        let dish = Rc::new(RefCell::new(Dish::from_bytes(bytes)));
+                        self.env.define("input", lisp::Expression::Dish(dish));
+                        let note = format!("loaded {} ({} bytes) into `input`", name, len);
+                        self.transcript.push(Entry::Note(note));
+                    }
+                    Err(e) => {
+                        let note = format!("failed to read {}: {}", name, e);
+                        self.transcript.push(Entry::Note(note));
                     }
                 }
-                log::debug!("output from script: {}", self.output);
 
                 true
             }
+            Self::Message::Download => {
+                let (bytes, filename) = match self.last_result() {
+                    Some(lisp::Expression::Dish(dish)) => match &*dish.borrow() {
+                        Dish::Success(data) => {
+                            let filename = match data {
+                                DishData::Str(_) => "output.txt",
+                                DishData::Bin(_) => "output.bin",
+                            };
+                            (data.as_bytes().to_vec(), filename)
+                        }
+                        Dish::Failure(_) => return false,
+                    },
+                    _ => {
+                        let note = "the last result is not a dish, nothing to download".to_string();
+                        self.transcript.push(Entry::Note(note));
+                        return true;
+                    }
+                };
+
+                if let Err(e) = download(&bytes, filename) {
+                    log::error!("failed to download output: {:?}", e);
+                }
+
+                false
+            }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let onclick = ctx.link().callback(|_: MouseEvent| Msg::Run);
+        let onclear = ctx.link().callback(|_: MouseEvent| Msg::Clear);
+        let ontoggle = ctx.link().callback(|_: Event| Msg::ToggleHexdump);
+        let ondownload = ctx.link().callback(|_: MouseEvent| Msg::Download);
+        let onupload = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::Upload(input.files().and_then(|files| files.get(0)))
+        });
 
         html! {
             <div id="app">
@@ -67,17 +165,92 @@ impl Component for App {
                     <label for="input">{ "script" }</label>
                     <textarea ref={self.text_input.clone()} class="textbox" id="input"></textarea>
                     <button {onclick} type="button">{ "run" }</button>
+                    <label for="upload">{ "load a file into `input`" }</label>
+                    <input type="file" id="upload" onchange={onupload} />
                 </div>
 
                 <div class="half">
                     <label for="output">{ "output from script" }</label>
-                    <textarea disabled=true class="textbox" id="output" value={ self.output.clone() } />
+                    <label for="hexdump">
+                        <input type="checkbox" id="hexdump" checked={ self.hexdump } onchange={ontoggle} />
+                        { "show binary dishes as a hexdump" }
+                    </label>
+                    <textarea disabled=true class="textbox" id="output" value={ self.output() } />
+                    <button onclick={ondownload} type="button">{ "download" }</button>
+                    <button onclick={onclear} type="button">{ "clear output" }</button>
                 </div>
             </div>
         }
     }
 }
 
+impl App {
+    /// Renders the whole transcript, REPL-style, for the output textarea
+    ///
+    fn output(&self) -> String {
+        let lines: Vec<String> = self
+            .transcript
+            .iter()
+            .map(|entry| match entry {
+                Entry::Eval(src, Ok(expr)) => format!("codebake> {}\n{}", src, self.render(expr)),
+                Entry::Eval(src, Err(e)) => format!("codebake> {}\nerror: {}", src, e),
+                Entry::Note(note) => format!("; {}", note),
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    /// The result of the most recently evaluated expression, if it didn't fail
+    ///
+    fn last_result(&self) -> Option<&lisp::Expression> {
+        self.transcript.iter().rev().find_map(|entry| match entry {
+            Entry::Eval(_, res) => Some(res.as_ref().ok()),
+            Entry::Note(_) => None,
+        })?
+    }
+
+    /// Renders a result for the output textarea, using `to-hexdump` for
+    /// binary dishes when that's turned on. Dishes aren't cut off like they
+    /// are with `Display`
+    ///
+    fn render(&self, expr: &lisp::Expression) -> String {
+        match expr {
+            lisp::Expression::Dish(dish) if self.hexdump && expr.is_binary_dish() => {
+                let to_hexdump = OPERATIONS
+                    .iter()
+                    .find(|oi| oi.name == "to-hexdump")
+                    .expect("to-hexdump operation is missing");
+
+                // apply to a copy so the dish bound in the environment stays binary
+                let mut copy = dish.borrow().deep_clone();
+                copy.apply(to_hexdump.op, &EMPTY_ARGS);
+                copy.display_full()
+            }
+            lisp::Expression::Dish(dish) => dish.borrow().display_full(),
+            _ => format!("{}", expr),
+        }
+    }
+}
+
+/// helper function that saves `bytes` as a file by clicking a
+/// temporary link to an object URL
+///
+fn download(bytes: &[u8], filename: &str) -> Result<(), wasm_bindgen::JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("no document to download from")?;
+
+    let blob = Blob::new_with_options(bytes, Some("application/octet-stream"));
+    let url = Url::create_object_url_with_blob(&blob.into())?;
+
+    let anchor: HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    Url::revoke_object_url(&url)
+}
+
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     yew::start_app::<App>();
@@ -85,25 +258,64 @@ fn main() {
 
 /// helper function to get a vector of the expressions in a string
 ///
+/// at the top level an expression ends at its closing paren or bracket, or
+/// for an atom, at the next whitespace, comment, or paren
+///
 fn get_expressions(s: &str) -> Vec<String> {
     let mut count = 0;
     let mut last = 0;
+    let mut string_mode = false;
+    let mut escaped = false;
+    let mut comment = false;
     let mut exprs: Vec<String> = Vec::new();
-    let new_s = s.replace('\n', " ");
 
-    for (i, c) in new_s.chars().enumerate() {
+    for (i, c) in s.char_indices() {
+        // an atom right before this char ends here. quote prefixes like `'` and `,@`
+        // belong to the list they're in front of, so they don't count
+        let top = count == 0 && !string_mode && !comment;
+        let ends_atom = match c {
+            c if c.is_whitespace() || c == ';' => true,
+            '(' | '[' => !s[last..i].trim().chars().all(|c| "'`,@d".contains(c)),
+            _ => false,
+        };
+        if top && ends_atom {
+            exprs.push(s[last..i].to_string());
+            last = i;
+        }
+
+        let end = i + c.len_utf8();
         match c {
-            '(' => count += 1,
-            ')' => count -= 1,
+            '\n' if comment => {
+                comment = false;
+                if count == 0 {
+                    exprs.push(s[last..end].to_string());
+                    last = end;
+                }
+            }
+            _ if comment => {}
+            ';' if !string_mode => comment = true,
+            _ if escaped => escaped = false,
+            '\\' if string_mode => escaped = true,
+            '"' => string_mode = !string_mode,
+            '(' | '[' if !string_mode => count += 1,
+            ')' | ']' if !string_mode => {
+                count -= 1;
+                if count == 0 {
+                    exprs.push(s[last..end].to_string());
+                    last = end;
+                }
+            }
             _ => {}
         }
+    }
 
-        if count == 0 {
-            let slice = &new_s[last..i + 1];
-            exprs.push(slice.to_string());
-            last = i;
-        }
+    // keep whatever's left, so an unfinished expression's parse error ends up
+    // in the transcript
+    if last < s.len() {
+        exprs.push(s[last..].to_string());
     }
 
+    // between expressions there's only whitespace and whole comments
+    exprs.retain(|e| !e.trim().is_empty() && !e.trim_start().starts_with(';'));
     exprs
 }
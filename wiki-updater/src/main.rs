@@ -4,13 +4,19 @@ use std::{
     io::Write,
 };
 
-use codebake::{ops::OPERATIONS as Operations, OperationArgType};
+use codebake::{
+    ops::{validate_operations, OPERATIONS as Operations},
+    OperationArgType,
+};
 use serde::Serialize;
 use tinytemplate::TinyTemplate;
 
 const TEMPLATE: &str = "# { name }
 {{ for op in ops }}
 * **`{ op.name }`** - { op.description } [{ op.authors }]
+{{ if op.aliases }}
+  aliases: { op.aliases }
+{{ endif }}
 {{ if op.arguments }}
   args:
 {{ for arg in op.arguments }}
@@ -24,6 +30,7 @@ struct OperationData<'a> {
     name: &'a str,
     description: &'a str,
     authors: String,
+    aliases: String,
     arguments: Vec<ArgumentData<'a>>,
 }
 
@@ -36,10 +43,16 @@ struct CategoryData<'a> {
 #[derive(Serialize, Clone)]
 struct ArgumentData<'a> {
     name: &'a str,
-    type_string: &'a str,
+    type_string: String,
 }
 
 fn main() {
+    // don't publish a typo'd category or name to the wiki
+    if let Err(problems) = validate_operations() {
+        eprintln!("invalid operations:\n{}", problems.join("\n"));
+        std::process::exit(1);
+    }
+
     let mut tt = TinyTemplate::new();
     tt.add_template("category", TEMPLATE).unwrap();
     let mut categories: HashMap<&str, Vec<OperationData>> = HashMap::new();
@@ -48,13 +61,24 @@ fn main() {
 
     for op in Operations {
         let authors = op.authors.join(", ").to_string();
+        let aliases = op
+            .aliases
+            .iter()
+            .map(|alias| format!("`{}`", alias))
+            .collect::<Vec<String>>()
+            .join(", ");
         let mut arguments: Vec<ArgumentData> = Vec::new();
 
         for (arg_name, arg_type) in op.arguments {
-            let type_string = match arg_type {
-                OperationArgType::Integer => "int",
-                OperationArgType::String => "string",
+            let mut type_string = match arg_type {
+                OperationArgType::Integer => "int".to_string(),
+                OperationArgType::String => "string".to_string(),
+                OperationArgType::Bytes => "bytes".to_string(),
+                OperationArgType::Choice(choices) => format!("one of `{}`", choices.join("`, `")),
             };
+            if let Some(default) = op.default_for(arg_name) {
+                type_string += &format!(" (default `{}`)", default);
+            }
 
             let arg = ArgumentData {
                 name: arg_name,
@@ -68,6 +92,7 @@ fn main() {
             name: op.name,
             description: op.description,
             authors,
+            aliases,
             arguments,
         };
 